@@ -1,5 +1,5 @@
 use crate::event::{Event, EventHandler};
-use crate::screens::{RenderContext, Screen, ScreenStack, Transition};
+use crate::screens::{RenderContext, Return, Screen, ScreenStack, Transition};
 use crate::theme::{DefaultTheme, UiTheme};
 use color_eyre::eyre::eyre;
 use log::debug;
@@ -216,7 +216,8 @@ impl App {
                 {
                     // App is complete
                     match return_value {
-                        _ => Ok(None),
+                        Return::Command(command) => Ok(Some(command)),
+                        _ => Err(eyre!("No command was composed")),
                     }
                 } else {
                     match self
@@ -228,8 +229,9 @@ impl App {
                                 "process return: {}",
                                 screen.name()
                             );
-                            screen.process(return_value)?;
-                            Ok(None)
+                            // The parent may itself complete, or push a screen, in response
+                            let transition = screen.process(return_value)?;
+                            self.handle_transition(transition)
                         }
                         None => Err(eyre!("Screen MUST be found")),
                     }
@@ -12,6 +12,9 @@ pub enum Action {
     InsertCommand,
     LookupArguments,
     RemoveToken,
+    MoveUp,
+    MoveDown,
+    MoveToContext,
 }
 
 impl Action {
@@ -52,6 +55,9 @@ impl KeyBinding for Action {
             InsertCommand => 27,
             LookupArguments => 30,
             RemoveToken => 40,
+            MoveUp => 35,
+            MoveDown => 36,
+            MoveToContext => 37,
         }
     }
 
@@ -80,6 +86,15 @@ impl KeyBinding for Action {
             RemoveToken => (
                 'r', Modifier,
             ),
+            MoveUp => (
+                '<', Modifier,
+            ),
+            MoveDown => (
+                '>', Modifier,
+            ),
+            MoveToContext => (
+                'm', Modifier,
+            ),
         }
     }
 
@@ -91,6 +106,9 @@ impl KeyBinding for Action {
             InsertCommand => t!("action_hints.insert_command").to_string(),
             LookupArguments => t!("action_hints.lookup_arguments").to_string(),
             RemoveToken => t!("action_hints.remove").to_string(),
+            MoveUp => t!("action_hints.move_up").to_string(),
+            MoveDown => t!("action_hints.move_down").to_string(),
+            MoveToContext => t!("action_hints.move_to_context").to_string(),
         }
     }
 }
@@ -1,8 +1,10 @@
 use crate::builder::Token::{
-    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken,
+    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken, UnknownToken,
 };
-use crate::builder::{Action, CommandContext, Token};
-use crate::opencli::v0_1::V0_1;
+use crate::builder::history::{History, Snapshot};
+use crate::builder::{Action, CommandContext, HistoryAction, Token};
+use crate::opencli::operations::find_option;
+use crate::opencli::v0_1::{ArgumentElement, CommandElement, ExitCodeElement, OptionElement, V0_1};
 use crate::util::util::try_insert;
 use color_eyre::eyre::bail;
 use std::cmp::min;
@@ -12,6 +14,9 @@ pub struct Builder {
     pub command_spec: V0_1,
     command_tokens: Vec<Token>,
     command_selected_pos: usize,
+    history: History,
+    /// User preference for grouping short options, see [`Builder::short_option_groups`]
+    group_options: bool,
 }
 
 impl Builder {
@@ -20,17 +25,76 @@ impl Builder {
         token: Token,
         action: Action,
     ) -> color_eyre::Result<()> {
-        match action {
-            Action::ReplaceToken => Ok(()),
+        let before = self.snapshot();
+        let result = match action {
+            Action::ReplaceToken => self.replace_selected_token(token),
             Action::InsertOptionBelow => self.insert_below_selected(token),
             Action::InsertArgument => {
                 self.replace_at_selected(token)?;
                 Ok(())
-            },
-            Action::InsertCommand => Ok(()),
-            Action::LookupArguments => Ok(()),
-            Action::RemoveToken => Ok(()),
+            }
+            Action::InsertCommand | Action::LookupArguments => self.insert_in_scope(token),
+            Action::RemoveToken => self.remove_at_selected(),
+            Action::MoveUp => self.move_selected_up(),
+            Action::MoveDown => self.move_selected_down(),
+            Action::MoveToContext => self.move_selected_to_context(&token),
+        };
+
+        if result.is_err() {
+            self.restore(before);
+        } else if before.tokens != self.command_tokens {
+            self.history
+                .record(before);
         }
+        result
+    }
+
+    pub(crate) fn do_history_action(&mut self, action: HistoryAction) {
+        let current = self.snapshot();
+        let restored = match action {
+            HistoryAction::Undo => self
+                .history
+                .undo(current),
+            HistoryAction::Redo => self
+                .history
+                .redo(current),
+        };
+
+        if let Some(snapshot) = restored {
+            self.restore(snapshot);
+        }
+    }
+
+    /// Undo/redo actions that currently have something to act on
+    pub fn available_history_actions(&self) -> Vec<HistoryAction> {
+        let mut actions = vec![];
+        if self
+            .history
+            .can_undo()
+        {
+            actions.push(HistoryAction::Undo);
+        }
+        if self
+            .history
+            .can_redo()
+        {
+            actions.push(HistoryAction::Redo);
+        }
+        actions
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            tokens: self
+                .command_tokens
+                .clone(),
+            selected_pos: self.command_selected_pos,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.command_tokens = snapshot.tokens;
+        self.command_selected_pos = snapshot.selected_pos;
     }
 }
 
@@ -44,6 +108,8 @@ impl Builder {
             command_spec,
             command_tokens: vec![PlaceholderToken],
             command_selected_pos: 0,
+            history: History::default(),
+            group_options: true,
         }
     }
 
@@ -52,6 +118,8 @@ impl Builder {
             command_spec,
             command_tokens,
             command_selected_pos: 0,
+            history: History::default(),
+            group_options: true,
         };
         rtn.condition_tokens();
         rtn
@@ -71,6 +139,14 @@ impl Builder {
         &self.command_tokens
     }
 
+    pub fn group_options(&self) -> bool {
+        self.group_options
+    }
+
+    pub(crate) fn set_group_options(&mut self, group_options: bool) {
+        self.group_options = group_options;
+    }
+
     fn condition_tokens(&mut self) {
         if let Some(PlaceholderToken) = self
             .command_tokens
@@ -88,27 +164,180 @@ impl Builder {
             None => vec![],
             Some(PlaceholderToken) => vec![],
             Some(CommandToken {
-                ..
-            }) => vec![Action::InsertOptionBelow],
+                ctx,
+            }) => self.command_actions(&ctx.commands),
             Some(OptionToken {
                 spec,
                 ..
             }) => {
-                if spec
+                let mut actions = if spec
                     .arguments
                     .is_some()
                 {
-                    vec![Action::InsertArgument]
+                    vec![
+                        Action::InsertArgument,
+                        Action::ReplaceToken,
+                        Action::RemoveToken,
+                    ]
                 } else {
-                    vec![]
-                }
+                    vec![
+                        Action::ReplaceToken,
+                        Action::RemoveToken,
+                    ]
+                };
+                actions.extend(self.move_actions());
+                actions
             }
             Some(SubCommandToken {
+                ctx,
                 ..
-            }) => vec![Action::InsertOptionBelow],
+            }) => {
+                let mut actions = self.command_actions(
+                    &ctx.spec
+                        .commands,
+                );
+                actions.push(Action::ReplaceToken);
+                actions.push(Action::RemoveToken);
+                actions
+            }
             Some(ArgumentToken {
                 ..
-            }) => vec![],
+            })
+            | Some(UnknownToken {
+                ..
+            }) => {
+                let mut actions = vec![
+                    Action::ReplaceToken,
+                    Action::RemoveToken,
+                ];
+                actions.extend(self.move_actions());
+                actions
+            }
+        }
+    }
+
+    /// Moves the selected token can make without leaving the commands that accept it
+    fn move_actions(&self) -> Vec<Action> {
+        let mut actions = vec![];
+        if self
+            .move_up_target()
+            .is_some()
+        {
+            actions.push(Action::MoveUp);
+        }
+        if self
+            .move_down_target()
+            .is_some()
+        {
+            actions.push(Action::MoveDown);
+        }
+        if !self
+            .context_targets_at_selected()
+            .is_empty()
+        {
+            actions.push(Action::MoveToContext);
+        }
+        actions
+    }
+
+    /// Actions for the selected root or sub command
+    fn command_actions(&self, commands: &Option<Vec<CommandElement>>) -> Vec<Action> {
+        let mut actions = vec![Action::InsertOptionBelow];
+        // A command can only be followed by one of its subcommands
+        if commands
+            .as_ref()
+            .is_some_and(|commands| !commands.is_empty())
+            && !self.has_subcommand_after(self.command_selected_pos)
+        {
+            actions.push(Action::InsertCommand);
+        }
+        if self
+            .next_positional(self.command_selected_pos)
+            .is_some()
+        {
+            actions.push(Action::LookupArguments);
+        }
+        actions
+    }
+
+    fn has_subcommand_after(&self, pos: usize) -> bool {
+        self.command_tokens
+            .iter()
+            .skip(pos + 1)
+            .any(
+                |token| {
+                    matches!(
+                        token,
+                        SubCommandToken { .. }
+                    )
+                },
+            )
+    }
+
+    /// Position of the root or sub command that owns the token at `pos`
+    pub(crate) fn owning_command_pos(&self, pos: usize) -> usize {
+        self.command_tokens
+            .iter()
+            .enumerate()
+            .take(pos + 1)
+            .rev()
+            .find(
+                |(_, token)| {
+                    matches!(
+                        token,
+                        CommandToken { .. } | SubCommandToken { .. }
+                    )
+                },
+            )
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Position just after the last token owned by the command at `pos`
+    pub(crate) fn scope_end(&self, pos: usize) -> usize {
+        self.command_tokens
+            .iter()
+            .enumerate()
+            .skip(pos + 1)
+            .find(
+                |(_, token)| {
+                    matches!(
+                        token,
+                        SubCommandToken { .. } | PlaceholderToken
+                    )
+                },
+            )
+            .map(|(i, _)| i)
+            .unwrap_or(
+                self.command_tokens
+                    .len(),
+            )
+    }
+
+    /// The subcommands the selected subcommand could be swapped for
+    pub fn sibling_commands_at_selected(&self) -> Option<&Vec<CommandElement>> {
+        let pos = self.command_selected_pos;
+        if pos == 0 {
+            return None;
+        }
+
+        match self
+            .command_tokens
+            .get(self.owning_command_pos(pos - 1))
+        {
+            Some(CommandToken {
+                ctx,
+            }) => ctx
+                .commands
+                .as_ref(),
+            Some(SubCommandToken {
+                ctx,
+                ..
+            }) => ctx
+                .spec
+                .commands
+                .as_ref(),
+            _ => None,
         }
     }
 
@@ -184,6 +413,510 @@ impl Builder {
         }
     }
 
+    /// Insert at the end of the selected command's scope and select the new token
+    pub(crate) fn insert_in_scope(&mut self, token: Token) -> color_eyre::Result<()> {
+        let insertion_pos = self.scope_end(self.owning_command_pos(self.command_selected_pos));
+        try_insert(
+            &mut self.command_tokens,
+            insertion_pos,
+            token,
+        )?;
+        self.command_selected_pos = insertion_pos;
+        self.condition_tokens();
+        Ok(())
+    }
+
+    /// Replace the selected token, keeping what still fits when a subcommand is swapped
+    pub(crate) fn replace_selected_token(&mut self, token: Token) -> color_eyre::Result<()> {
+        if let (
+            Some(SubCommandToken {
+                ctx: old,
+                ..
+            }),
+            SubCommandToken {
+                ctx: new,
+                ..
+            },
+        ) = (
+            self.token_at_selected(),
+            &token,
+        ) {
+            let (level, new) = (
+                old.level,
+                new.clone(),
+            );
+            self.rehome_descendants(
+                self.command_selected_pos + 1,
+                level,
+                &new,
+            );
+        }
+
+        self.replace_at_selected(token)
+    }
+
+    /// Remove the selected token, a subcommand takes everything in its context with it
+    pub(crate) fn remove_at_selected(&mut self) -> color_eyre::Result<()> {
+        let pos = self.command_selected_pos;
+        match self.token_at_selected() {
+            None => bail!(
+                "Index {} out of bounds",
+                pos
+            ),
+            Some(PlaceholderToken)
+            | Some(CommandToken {
+                ..
+            }) => return Ok(()),
+            Some(_) => {}
+        }
+
+        if let SubCommandToken {
+            ctx,
+            ..
+        } = self
+            .command_tokens
+            .remove(pos)
+        {
+            self.remove_descendants(
+                pos, ctx.level,
+            );
+        }
+
+        self.condition_tokens();
+        self.command_selected_pos = min(
+            pos,
+            self.command_tokens
+                .len()
+                - 1,
+        );
+        Ok(())
+    }
+
+    pub(crate) fn move_selected_up(&mut self) -> color_eyre::Result<()> {
+        match self.move_up_target() {
+            None => Ok(()),
+            Some(token) => {
+                let pos = self.command_selected_pos;
+                self.command_tokens
+                    .remove(pos);
+                self.command_tokens
+                    .insert(
+                        pos - 1,
+                        token,
+                    );
+                self.command_selected_pos = pos - 1;
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn move_selected_down(&mut self) -> color_eyre::Result<()> {
+        match self.move_down_target() {
+            None => Ok(()),
+            Some(token) => {
+                let pos = self.command_selected_pos;
+                self.command_tokens
+                    .remove(pos);
+                self.command_tokens
+                    .insert(
+                        pos + 1,
+                        token,
+                    );
+                self.command_selected_pos = pos + 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Move the selected token to the end of the scope of `command`, a root or sub command token
+    pub(crate) fn move_selected_to_context(&mut self, command: &Token) -> color_eyre::Result<()> {
+        let Some(command_pos) = self
+            .command_tokens
+            .iter()
+            .position(|token| token == command)
+        else {
+            bail!(
+                "Command {} is not part of the builder",
+                command
+            );
+        };
+        let Some(token) = self
+            .token_at_selected()
+            .and_then(
+                |token| {
+                    self.rehome_token(
+                        token,
+                        command_pos,
+                    )
+                },
+            )
+        else {
+            return Ok(());
+        };
+
+        let pos = self.command_selected_pos;
+        let mut insertion_pos = self.scope_end(command_pos);
+        self.command_tokens
+            .remove(pos);
+        if pos < insertion_pos {
+            insertion_pos -= 1;
+        }
+        try_insert(
+            &mut self.command_tokens,
+            insertion_pos,
+            token,
+        )?;
+        self.command_selected_pos = insertion_pos;
+        Ok(())
+    }
+
+    /// The root and sub commands other than its own that the selected token could be moved to
+    pub fn context_targets_at_selected(&self) -> Vec<Token> {
+        let pos = self.command_selected_pos;
+        let owner = self.owning_command_pos(pos);
+        match self.token_at_selected() {
+            Some(
+                token @ OptionToken {
+                    ..
+                },
+            )
+            | Some(
+                token @ ArgumentToken {
+                    ..
+                },
+            )
+            | Some(
+                token @ UnknownToken {
+                    ..
+                },
+            ) => self
+                .command_tokens
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != owner)
+                .filter(
+                    |(i, command)| {
+                        matches!(
+                            command,
+                            CommandToken { .. } | SubCommandToken { .. }
+                        ) && self
+                            .rehome_token(
+                                token, *i,
+                            )
+                            .is_some()
+                    },
+                )
+                .map(|(_, command)| command.clone())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// The selected token with its new context, if it can swap places with the token above
+    fn move_up_target(&self) -> Option<Token> {
+        let pos = self.command_selected_pos;
+        if pos < 2 {
+            return None;
+        }
+        self.movable_at_selected()
+            .and_then(
+                |token| {
+                    self.rehome_token(
+                        token,
+                        self.owning_command_pos(pos - 2),
+                    )
+                },
+            )
+    }
+
+    /// The selected token with its new context, if it can swap places with the token below
+    fn move_down_target(&self) -> Option<Token> {
+        let pos = self.command_selected_pos;
+        match self
+            .command_tokens
+            .get(pos + 1)
+        {
+            None | Some(PlaceholderToken) => None,
+            Some(_) => self
+                .movable_at_selected()
+                .and_then(
+                    |token| {
+                        self.rehome_token(
+                            token,
+                            self.owning_command_pos(pos + 1),
+                        )
+                    },
+                ),
+        }
+    }
+
+    fn movable_at_selected(&self) -> Option<&Token> {
+        self.token_at_selected()
+            .filter(
+                |token| {
+                    matches!(
+                        token,
+                        OptionToken { .. } | ArgumentToken { .. } | UnknownToken { .. }
+                    )
+                },
+            )
+    }
+
+    /// `token` as it would be owned by the command at `command_pos`
+    ///
+    /// Options go to the nearest command in the chain that declares them, only recursive options
+    /// can be used below their owning command. `None` when the command does not accept the token.
+    fn rehome_token(&self, token: &Token, command_pos: usize) -> Option<Token> {
+        let chain = self.command_chain(command_pos);
+        let innermost = *chain.first()?;
+
+        match token {
+            OptionToken {
+                spec,
+                arg,
+                details,
+                ..
+            } => chain
+                .iter()
+                .enumerate()
+                .find_map(
+                    |(depth, ctx)| {
+                        find_option(
+                            self.declarations(*ctx)
+                                .0,
+                            &spec.name,
+                        )
+                        .map(
+                            |option| {
+                                (
+                                    depth, ctx, option,
+                                )
+                            },
+                        )
+                    },
+                )
+                .filter(|(depth, _, option)| *depth == 0 || option.recursive)
+                .map(
+                    |(_, ctx, option)| OptionToken {
+                        ctx: ctx.cloned(),
+                        spec: option.clone(),
+                        arg: arg.clone(),
+                        details: details.clone(),
+                    },
+                ),
+            ArgumentToken {
+                spec,
+                arg,
+                details,
+                ..
+            } => self
+                .declarations(innermost)
+                .1
+                .iter()
+                .flatten()
+                .find(|argument| argument.name == spec.name)
+                .map(
+                    |argument| ArgumentToken {
+                        ctx: innermost.cloned(),
+                        spec: argument.clone(),
+                        arg: arg.clone(),
+                        details: details.clone(),
+                    },
+                ),
+            UnknownToken {
+                arg,
+                details,
+                ..
+            } => Some(
+                UnknownToken {
+                    ctx: innermost.cloned(),
+                    arg: arg.clone(),
+                    details: details.clone(),
+                },
+            ),
+            _ => None,
+        }
+    }
+
+    /// Contexts of the commands leading to the one at `command_pos`, innermost first
+    ///
+    /// The root command has no context and is always last.
+    pub(crate) fn command_chain(&self, command_pos: usize) -> Vec<Option<&CommandContext>> {
+        self.command_tokens
+            .iter()
+            .take(command_pos + 1)
+            .filter_map(
+                |token| match token {
+                    CommandToken {
+                        ..
+                    } => Some(None),
+                    SubCommandToken {
+                        ctx,
+                        ..
+                    } => Some(Some(ctx)),
+                    _ => None,
+                },
+            )
+            .rev()
+            .collect()
+    }
+
+    /// Options and arguments declared by the command with `ctx`, the root when `None`
+    pub(crate) fn declarations<'a>(
+        &'a self,
+        ctx: Option<&'a CommandContext>,
+    ) -> (
+        &'a Option<Vec<OptionElement>>,
+        &'a Option<Vec<ArgumentElement>>,
+    ) {
+        match ctx {
+            None => (
+                &self
+                    .command_spec
+                    .options,
+                &self
+                    .command_spec
+                    .arguments,
+            ),
+            Some(ctx) => (
+                &ctx.spec
+                    .options,
+                &ctx.spec
+                    .arguments,
+            ),
+        }
+    }
+
+    /// Exit codes documented by the command with `ctx`, the root when `None`
+    pub(crate) fn exit_codes<'a>(
+        &'a self,
+        ctx: Option<&'a CommandContext>,
+    ) -> &'a Option<Vec<ExitCodeElement>> {
+        match ctx {
+            None => {
+                &self
+                    .command_spec
+                    .exit_codes
+            }
+            Some(ctx) => {
+                &ctx.spec
+                    .exit_codes
+            }
+        }
+    }
+
+    /// The spec's entry for exit `code`, from the innermost command documenting it
+    pub fn exit_code(&self, code: i64) -> Option<&ExitCodeElement> {
+        let innermost = self.owning_command_pos(
+            self.command_tokens
+                .len()
+                .saturating_sub(1),
+        );
+
+        self.command_chain(innermost)
+            .into_iter()
+            .find_map(
+                |ctx| {
+                    self.exit_codes(ctx)
+                        .iter()
+                        .flatten()
+                        .find(|exit| exit.code == code)
+                },
+            )
+    }
+
+    /// Whether `token` lives in the context of a subcommand at `level` (or deeper)
+    fn is_descendant(token: &Token, level: usize) -> bool {
+        match token {
+            SubCommandToken {
+                ctx,
+                ..
+            } => ctx.level >= level,
+            OptionToken {
+                ctx: Some(ctx),
+                ..
+            }
+            | ArgumentToken {
+                ctx: Some(ctx),
+                ..
+            }
+            | UnknownToken {
+                ctx: Some(ctx),
+                ..
+            } => ctx.level >= level,
+            _ => false,
+        }
+    }
+
+    fn remove_descendants(&mut self, from: usize, level: usize) {
+        let mut i = from;
+        while i < self
+            .command_tokens
+            .len()
+        {
+            if Self::is_descendant(
+                &self.command_tokens[i],
+                level,
+            ) {
+                self.command_tokens
+                    .remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Move the children of a replaced subcommand to `new`, dropping those it does not declare
+    fn rehome_descendants(&mut self, from: usize, level: usize, new: &CommandContext) {
+        let mut i = from;
+        while i < self
+            .command_tokens
+            .len()
+        {
+            let token = &mut self.command_tokens[i];
+            let keep = match token {
+                OptionToken {
+                    ctx: Some(ctx),
+                    spec,
+                    ..
+                } if ctx.level == level => {
+                    let declared = new
+                        .spec
+                        .options
+                        .iter()
+                        .flatten()
+                        .any(|option| option.name == spec.name);
+                    *ctx = new.clone();
+                    declared
+                }
+                ArgumentToken {
+                    ctx: Some(ctx),
+                    spec,
+                    ..
+                } if ctx.level == level => {
+                    let declared = new
+                        .spec
+                        .arguments
+                        .iter()
+                        .flatten()
+                        .any(|argument| argument.name == spec.name);
+                    *ctx = new.clone();
+                    declared
+                }
+                token => !Self::is_descendant(
+                    token, level,
+                ),
+            };
+
+            if keep {
+                i += 1;
+            } else {
+                self.command_tokens
+                    .remove(i);
+            }
+        }
+    }
+
     pub(crate) fn selected_up(&mut self) {
         self.command_selected_pos = self
             .command_selected_pos
@@ -250,11 +983,20 @@ conventions:
 options:
   - name: "--kubeconfig"
     description: Path to the kubeconfig file to use
+    recursive: true
   - name: "--context"
     description: The name of the kubeconfig context to use
+exitCodes:
+  - code: 0
+    description: Success
+  - code: 1
+    description: Generic failure
 commands:
   - name: get
     description: Display one or many resources
+    exitCodes:
+      - code: 1
+        description: Resource not found
     arguments:
       - name: resource
         required: true
@@ -615,6 +1357,258 @@ interactive: false
         Ok(())
     }
 
+    fn parsed_builder(line: &str) -> Builder {
+        let spec = sample_v0_1();
+        let args: Vec<String> = line
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        Builder::new_demo(
+            spec.clone(),
+            crate::builder::parse_command_line(
+                &spec, &args,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_remove_subcommand_removes_its_context() -> Result<()> {
+        let mut builder = parsed_builder("--context dev get pods --namespace default");
+        builder.command_selected_pos = 3;
+        builder.remove_at_selected()?;
+
+        // `dev` is not a value of `--context` and stays as an unknown root word
+        assert_eq!(
+            builder
+                .tokens()
+                .len(),
+            4
+        );
+        assert!(
+            matches!(
+                builder.command_tokens[3],
+                PlaceholderToken
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_command_token_is_ignored() -> Result<()> {
+        let mut builder = parsed_builder("get pods");
+        builder.command_selected_pos = 0;
+        builder.remove_at_selected()?;
+
+        assert_eq!(
+            builder
+                .tokens()
+                .len(),
+            4
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_subcommand_keeps_declared_children() -> Result<()> {
+        let mut builder = parsed_builder("get pods --namespace default");
+        builder.command_selected_pos = 1;
+        let delete = sample_v0_1()
+            .commands
+            .unwrap()[2]
+            .clone();
+        builder.replace_selected_token(
+            SubCommandToken {
+                ctx: CommandContext::new(
+                    1, delete,
+                ),
+                details: vec![],
+            },
+        )?;
+
+        // `resource` and `name` are declared by delete, `--namespace` is not
+        assert_eq!(
+            builder
+                .tokens()
+                .len(),
+            5
+        );
+        assert!(
+            matches!(
+                &builder.command_tokens[2],
+                ArgumentToken { ctx: Some(ctx), .. } if ctx.spec.name == "delete"
+            )
+        );
+        assert!(
+            matches!(
+                builder.command_tokens[3],
+                ArgumentToken { .. }
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_insert_in_scope_appends_to_selected_command() -> Result<()> {
+        let mut builder = parsed_builder("--context dev get pods");
+        builder.command_selected_pos = 0;
+        builder.insert_in_scope(
+            SubCommandToken {
+                ctx: CommandContext::new(
+                    1,
+                    sample_command_element(),
+                ),
+                details: vec![],
+            },
+        )?;
+
+        // Root scope ends where `get` starts
+        assert_eq!(
+            builder.pos_at_selected(),
+            3
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_and_redo_restore_tokens_and_selection() -> Result<()> {
+        let mut builder = parsed_builder("get pods --namespace default");
+        let original = builder
+            .tokens()
+            .to_vec();
+        builder.command_selected_pos = 1;
+        builder.do_token_action(
+            PlaceholderToken,
+            Action::RemoveToken,
+        )?;
+        assert_eq!(
+            builder.available_history_actions(),
+            vec![HistoryAction::Undo]
+        );
+
+        builder.do_history_action(HistoryAction::Undo);
+        assert_eq!(
+            builder.tokens(),
+            original.as_slice()
+        );
+        assert_eq!(
+            builder.pos_at_selected(),
+            1
+        );
+
+        builder.do_history_action(HistoryAction::Redo);
+        assert_eq!(
+            builder
+                .tokens()
+                .len(),
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_noop_actions_are_not_recorded() -> Result<()> {
+        let mut builder = parsed_builder("get pods");
+        builder.command_selected_pos = 0;
+        builder.do_token_action(
+            PlaceholderToken,
+            Action::RemoveToken,
+        )?;
+
+        assert!(
+            builder
+                .available_history_actions()
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_keeps_option_inside_owning_command() -> Result<()> {
+        let mut builder = parsed_builder("get pods --namespace");
+        builder.command_selected_pos = 3;
+        builder.do_token_action(
+            PlaceholderToken,
+            Action::MoveUp,
+        )?;
+
+        assert!(
+            matches!(
+                &builder.command_tokens[2],
+                OptionToken { ctx: Some(ctx), .. } if ctx.spec.name == "get"
+            )
+        );
+        // Above `get` the root does not declare `--namespace`
+        assert!(
+            !builder
+                .available_actions()
+                .contains(&Action::MoveUp)
+        );
+        assert!(
+            builder
+                .context_targets_at_selected()
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_recursive_options_move_below_their_command() -> Result<()> {
+        let mut builder = parsed_builder("--kubeconfig --context get");
+        builder.command_selected_pos = 1;
+        builder.do_token_action(
+            PlaceholderToken,
+            Action::MoveDown,
+        )?;
+        builder.do_token_action(
+            PlaceholderToken,
+            Action::MoveDown,
+        )?;
+        assert!(
+            matches!(
+                &builder.command_tokens[3],
+                OptionToken { ctx: None, spec, .. } if spec.name == "--kubeconfig"
+            )
+        );
+
+        builder.command_selected_pos = 1;
+        assert!(
+            !builder
+                .available_actions()
+                .contains(&Action::MoveDown)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_to_context() -> Result<()> {
+        let mut builder = parsed_builder("get pods --kubeconfig");
+        builder.command_selected_pos = 3;
+        let targets = builder.context_targets_at_selected();
+        assert_eq!(
+            targets.len(),
+            1
+        );
+
+        builder.do_token_action(
+            targets[0].clone(),
+            Action::MoveToContext,
+        )?;
+        assert_eq!(
+            builder.pos_at_selected(),
+            1
+        );
+        assert!(
+            matches!(
+                &builder.command_tokens[1],
+                OptionToken {
+                    ctx: None,
+                    ..
+                }
+            )
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_selected_up_down() {
         let spec = sample_v0_1();
@@ -666,4 +1660,34 @@ interactive: false
             0
         );
     }
+
+    #[test]
+    fn test_exit_code_from_innermost_command() {
+        let builder = parsed_builder("get pods");
+        assert_eq!(
+            builder
+                .exit_code(1)
+                .and_then(
+                    |exit| exit
+                        .description
+                        .as_deref()
+                ),
+            Some("Resource not found")
+        );
+        assert_eq!(
+            builder
+                .exit_code(0)
+                .and_then(
+                    |exit| exit
+                        .description
+                        .as_deref()
+                ),
+            Some("Success")
+        );
+        assert!(
+            builder
+                .exit_code(2)
+                .is_none()
+        );
+    }
 }
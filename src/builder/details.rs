@@ -0,0 +1,184 @@
+use crate::builder::Token::{ArgumentToken, OptionToken};
+use crate::builder::{Builder, Detail};
+
+impl Builder {
+    /// What the details panel shows for the selected token
+    ///
+    /// Diagnostics come first and the active command's exit codes last, the innermost command
+    /// documenting any standing in for a subcommand that documents none.
+    pub fn details_at_selected(&self) -> Vec<Detail> {
+        let pos = self.pos_at_selected();
+        let Some(token) = self.token_at_selected() else {
+            return vec![];
+        };
+        let chain = self.command_chain(self.owning_command_pos(pos));
+
+        let mut details: Vec<Detail> = self
+            .validate()
+            .into_iter()
+            .filter(|diagnostic| diagnostic.pos == pos)
+            .map(Detail::Diagnostic)
+            .collect();
+
+        let value_spec = match token {
+            OptionToken {
+                ctx,
+                spec,
+                ..
+            } => {
+                if chain
+                    .first()
+                    .is_some_and(|active| *active != ctx.as_ref())
+                {
+                    details.push(
+                        Detail::InheritedFrom(
+                            match ctx {
+                                None => self
+                                    .cmd_title()
+                                    .clone(),
+                                Some(ctx) => ctx
+                                    .spec
+                                    .name
+                                    .clone(),
+                            },
+                        ),
+                    );
+                }
+                spec.arguments
+                    .iter()
+                    .flatten()
+                    .next()
+            }
+            ArgumentToken {
+                spec,
+                ..
+            } => Some(spec),
+            _ => None,
+        };
+        details.extend(
+            value_spec
+                .and_then(|spec| spec.default_value())
+                .map(Detail::Default),
+        );
+
+        details.extend(
+            token
+                .details()
+                .iter()
+                .cloned(),
+        );
+
+        details.extend(
+            chain
+                .into_iter()
+                .find_map(
+                    |ctx| {
+                        self.exit_codes(ctx)
+                            .clone()
+                            .filter(|codes| !codes.is_empty())
+                    },
+                )
+                .map(Detail::ExitCodes),
+        );
+
+        details
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{Severity, ValueSource, parse_command_line};
+    use crate::opencli::v0_1::V0_1;
+
+    fn builder(line: &str) -> Builder {
+        let spec: V0_1 = serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: kubectl
+  version: 1.29.0
+exitCodes:
+  - code: 1
+    description: Error
+options:
+  - name: "--namespace"
+    recursive: true
+    arguments:
+      - name: NAMESPACE
+        metadata:
+          - name: cmdi.default
+            value: default
+commands:
+  - name: get
+    arguments:
+      - name: resource
+        required: true
+        acceptedValues: [pods, services]
+"#,
+        )
+        .unwrap();
+        let args: Vec<String> = line
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        Builder::new_demo(
+            spec.clone(),
+            parse_command_line(
+                &spec, &args,
+            ),
+        )
+    }
+
+    fn select(builder: &mut Builder, pos: usize) {
+        for _ in 0..pos {
+            builder.selected_down();
+        }
+    }
+
+    #[test]
+    fn test_inherited_option_details() {
+        let mut builder = builder("get --namespace kube-system pods");
+        select(
+            &mut builder,
+            2,
+        );
+
+        assert_eq!(
+            builder.details_at_selected(),
+            vec![
+                Detail::InheritedFrom("kubectl".to_string()),
+                Detail::Default("default".to_string()),
+                Detail::Source(ValueSource::History),
+                Detail::ExitCodes(
+                    builder
+                        .exit_code(1)
+                        .cloned()
+                        .into_iter()
+                        .collect()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_come_first() {
+        let mut builder = builder("get nodes");
+        select(
+            &mut builder,
+            2,
+        );
+        let details = builder.details_at_selected();
+
+        assert!(
+            matches!(
+                &details[0],
+                Detail::Diagnostic(diagnostic) if diagnostic.severity == Severity::Error
+            )
+        );
+        assert_eq!(
+            details[1],
+            Detail::Source(ValueSource::History)
+        );
+    }
+}
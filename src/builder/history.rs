@@ -0,0 +1,192 @@
+use crate::builder::HistoryAction::*;
+use crate::builder::Token;
+use crate::screens::KeyBinding;
+use crate::screens::KeyBindingType;
+use crate::screens::KeyBindingType::Modifier;
+
+/// How many edits can be undone
+const HISTORY_LIMIT: usize = 100;
+
+/// The builder's tokens and selection at one point in time
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Snapshot {
+    pub(crate) tokens: Vec<Token>,
+    pub(crate) selected_pos: usize,
+}
+
+/// Undo and redo stacks of builder edits
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl History {
+    /// Remember the state from before an edit, a new edit invalidates everything that was undone
+    pub(crate) fn record(&mut self, before: Snapshot) {
+        if self
+            .undo
+            .len()
+            == HISTORY_LIMIT
+        {
+            self.undo
+                .remove(0);
+        }
+        self.undo
+            .push(before);
+        self.redo
+            .clear();
+    }
+
+    /// Trade `current` for the state before the last edit
+    pub(crate) fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self
+            .undo
+            .pop()?;
+        self.redo
+            .push(current);
+        Some(previous)
+    }
+
+    /// Trade `current` for the state the last undo went back from
+    pub(crate) fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self
+            .redo
+            .pop()?;
+        self.undo
+            .push(current);
+        Some(next)
+    }
+
+    pub(crate) fn can_undo(&self) -> bool {
+        !self
+            .undo
+            .is_empty()
+    }
+
+    pub(crate) fn can_redo(&self) -> bool {
+        !self
+            .redo
+            .is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum HistoryAction {
+    Undo,
+    Redo,
+}
+
+impl HistoryAction {
+    pub(crate) fn keybinding_to_action(
+        keybinding: char,
+        actions: Vec<HistoryAction>,
+    ) -> Option<HistoryAction> {
+        actions
+            .into_iter()
+            .find(
+                |action| {
+                    action
+                        .keybinding()
+                        .0
+                        == keybinding
+                },
+            )
+    }
+}
+
+impl KeyBinding for HistoryAction {
+    fn rank(&self) -> usize {
+        match self {
+            Undo => 50,
+            Redo => 51,
+        }
+    }
+
+    fn keybinding(
+        &self,
+    ) -> (
+        char,
+        KeyBindingType,
+    ) {
+        match self {
+            Undo => (
+                'u', Modifier,
+            ),
+            Redo => (
+                'U', Modifier,
+            ),
+        }
+    }
+
+    fn display_name(&self) -> String {
+        match self {
+            Undo => t!("action_hints.undo").to_string(),
+            Redo => t!("action_hints.redo").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Token::PlaceholderToken;
+
+    fn snapshot(selected_pos: usize) -> Snapshot {
+        Snapshot {
+            tokens: vec![PlaceholderToken],
+            selected_pos,
+        }
+    }
+
+    #[test]
+    fn test_undo_then_redo_round_trips() {
+        let mut history = History::default();
+        history.record(snapshot(0));
+
+        assert_eq!(
+            history.undo(snapshot(1)),
+            Some(snapshot(0))
+        );
+        assert!(!history.can_undo());
+        assert_eq!(
+            history.redo(snapshot(0)),
+            Some(snapshot(1))
+        );
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut history = History::default();
+        history.record(snapshot(0));
+        history.undo(snapshot(1));
+        history.record(snapshot(2));
+
+        assert!(!history.can_redo());
+        assert_eq!(
+            history.redo(snapshot(3)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = History::default();
+        for i in 0..HISTORY_LIMIT + 10 {
+            history.record(snapshot(i));
+        }
+        let mut count = 0;
+        while history
+            .undo(snapshot(0))
+            .is_some()
+        {
+            count += 1;
+        }
+
+        assert_eq!(
+            count,
+            HISTORY_LIMIT
+        );
+    }
+}
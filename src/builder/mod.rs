@@ -3,7 +3,20 @@ mod token;
 pub use token::*;
 mod action;
 mod builder;
+mod details;
+mod history;
+mod parse;
+mod positional;
+mod render;
+mod scope;
+mod validate;
 
 pub use action::*;
 
 pub use builder::*;
+pub use history::HistoryAction;
+pub use parse::*;
+pub use positional::*;
+pub use render::RenderedSegment;
+pub use scope::*;
+pub use validate::*;
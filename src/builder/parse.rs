@@ -0,0 +1,755 @@
+use crate::builder::Token::{
+    ArgumentToken, CommandToken, OptionToken, SubCommandToken, UnknownToken,
+};
+use crate::builder::{CommandContext, Detail, Token, ValueSource};
+use crate::opencli::operations::{find_command, find_option};
+use crate::opencli::v0_1::{ArgumentElement, CommandElement, OptionElement, V0_1};
+use std::iter::Peekable;
+use std::slice::Iter;
+
+/// Parse an existing command line (without the program name) into builder tokens
+///
+/// Subcommands, options and positional arguments are matched against the spec. Words the spec
+/// does not describe become [`UnknownToken`]s so nothing the user typed is lost.
+pub fn parse_command_line(spec: &V0_1, args: &[String]) -> Vec<Token> {
+    let mut parser = CommandLineParser::new(spec);
+    parser.parse(args);
+    parser.tokens
+}
+
+struct CommandLineParser<'a> {
+    spec: &'a V0_1,
+    /// Subcommands seen so far, innermost last
+    chain: Vec<CommandContext>,
+    /// Index into the current command's positional arguments
+    positional: usize,
+    /// Values already given to the current positional argument
+    positional_values: usize,
+    tokens: Vec<Token>,
+}
+
+impl<'a> CommandLineParser<'a> {
+    fn new(spec: &'a V0_1) -> Self {
+        Self {
+            spec,
+            chain: vec![],
+            positional: 0,
+            positional_values: 0,
+            tokens: vec![
+                CommandToken {
+                    ctx: spec.clone(),
+                },
+            ],
+        }
+    }
+
+    fn parse(&mut self, args: &[String]) {
+        let mut words = args
+            .iter()
+            .peekable();
+        let mut options_ended = false;
+
+        while let Some(word) = words.next() {
+            if !options_ended && word == "--" {
+                // Kept so the rendered command stays identical
+                options_ended = true;
+                self.push_unknown(word);
+            } else if !options_ended && word.starts_with('-') && word.len() > 1 {
+                self.parse_option(
+                    word, &mut words,
+                );
+            } else if let Some(command) = self
+                .subcommand_for(word)
+                .filter(|_| !options_ended)
+            {
+                self.push_subcommand(command);
+            } else {
+                self.parse_positional(word);
+            }
+        }
+    }
+
+    /// A subcommand of the current command, only before any of its positionals are given
+    fn subcommand_for(&self, word: &str) -> Option<CommandElement> {
+        if self.positional > 0 || self.positional_values > 0 {
+            return None;
+        }
+
+        let commands = match self
+            .chain
+            .last()
+        {
+            None => {
+                &self
+                    .spec
+                    .commands
+            }
+            Some(ctx) => {
+                &ctx.spec
+                    .commands
+            }
+        };
+
+        find_command(
+            commands, word,
+        )
+        .cloned()
+    }
+
+    fn current_arguments(&self) -> &Option<Vec<ArgumentElement>> {
+        match self
+            .chain
+            .last()
+        {
+            None => {
+                &self
+                    .spec
+                    .arguments
+            }
+            Some(ctx) => {
+                &ctx.spec
+                    .arguments
+            }
+        }
+    }
+
+    fn push_subcommand(&mut self, command: CommandElement) {
+        let ctx = CommandContext::new(
+            self.chain
+                .len()
+                + 1,
+            command,
+        );
+        self.tokens
+            .push(
+                SubCommandToken {
+                    ctx: ctx.clone(),
+                    details: vec![],
+                },
+            );
+        self.chain
+            .push(ctx);
+        self.positional = 0;
+        self.positional_values = 0;
+    }
+
+    fn push_unknown(&mut self, word: &str) {
+        self.tokens
+            .push(
+                UnknownToken {
+                    ctx: self
+                        .chain
+                        .last()
+                        .cloned(),
+                    arg: word.to_owned(),
+                    details: vec![Detail::Source(ValueSource::History)],
+                },
+            );
+    }
+
+    /// Find an option in the current command, then a recursive one in its ancestors and the root
+    fn lookup_option(
+        &self,
+        name: &str,
+    ) -> Option<(
+        Option<CommandContext>,
+        OptionElement,
+    )> {
+        // Only the innermost command's own options need not be recursive
+        let inherited = |option: &&OptionElement, innermost: bool| innermost || option.recursive;
+        let innermost = self
+            .chain
+            .len();
+
+        self.chain
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(
+                |(i, ctx)| {
+                    find_option(
+                        &ctx.spec
+                            .options,
+                        name,
+                    )
+                    .filter(
+                        |option| {
+                            inherited(
+                                option,
+                                i + 1 == innermost,
+                            )
+                        },
+                    )
+                    .map(
+                        |option| {
+                            (
+                                Some(ctx.clone()),
+                                option.clone(),
+                            )
+                        },
+                    )
+                },
+            )
+            .or_else(
+                || {
+                    find_option(
+                        &self
+                            .spec
+                            .options,
+                        name,
+                    )
+                    .filter(
+                        |option| {
+                            inherited(
+                                option,
+                                innermost == 0,
+                            )
+                        },
+                    )
+                    .map(
+                        |option| {
+                            (
+                                None,
+                                option.clone(),
+                            )
+                        },
+                    )
+                },
+            )
+    }
+
+    fn parse_option(&mut self, word: &str, words: &mut Peekable<Iter<String>>) {
+        let (name, inline_value) = match word.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (
+                name,
+                Some(value.to_owned()),
+            ),
+            _ => (
+                word, None,
+            ),
+        };
+
+        match self.lookup_option(name) {
+            Some((ctx, option)) => self.push_option(
+                ctx,
+                option,
+                inline_value,
+                words,
+            ),
+            None => {
+                if !self.parse_short_options(
+                    word, words,
+                ) {
+                    self.push_unknown(word);
+                }
+            }
+        }
+    }
+
+    /// Add the option with its values, the inline one first and then as many words as it takes
+    fn push_option(
+        &mut self,
+        ctx: Option<CommandContext>,
+        option: OptionElement,
+        mut inline_value: Option<String>,
+        words: &mut Peekable<Iter<String>>,
+    ) {
+        let mut values: Vec<String> = vec![];
+        for argument in option
+            .arguments
+            .iter()
+            .flatten()
+        {
+            let mut taken = 0;
+            // Only the first argument can receive the inline value
+            if let Some(value) = inline_value.take() {
+                values.push(value);
+                taken += 1;
+            }
+
+            while argument
+                .max_values()
+                .is_none_or(|max| taken < max)
+            {
+                match words.peek() {
+                    Some(next) if taken < argument.min_values() || !next.starts_with('-') => {
+                        values.push(
+                            words
+                                .next()
+                                .unwrap()
+                                .to_owned(),
+                        );
+                        taken += 1;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        // A value the spec has no argument for is still part of the command
+        values.extend(inline_value);
+
+        // A flag has no value to have come from anywhere
+        let details = match values.is_empty() {
+            true => vec![],
+            false => vec![Detail::Source(ValueSource::History)],
+        };
+        self.tokens
+            .push(
+                OptionToken {
+                    ctx,
+                    spec: option,
+                    arg: values,
+                    details,
+                },
+            );
+    }
+
+    /// Split a short option word the way getopt reads it
+    ///
+    /// A value may be attached to a short option, like `-nprod`. Short options grouped into one
+    /// word, like `-alh` or `-xfbackup.tar`, are expanded only when the spec allows grouping, and
+    /// the first one taking a value gets the rest of the word. Returns false when `word` is
+    /// neither.
+    fn parse_short_options(&mut self, word: &str, words: &mut Peekable<Iter<String>>) -> bool {
+        if word.starts_with("--") {
+            return false;
+        }
+
+        let letters: Vec<char> = word
+            .chars()
+            .skip(1)
+            .collect();
+        let mut options = vec![];
+        for (i, letter) in letters
+            .iter()
+            .enumerate()
+        {
+            let Some((ctx, option)) = self.lookup_option(&format!("-{letter}")) else {
+                return false;
+            };
+            let takes_value = option
+                .arguments
+                .as_ref()
+                .is_some_and(|arguments| !arguments.is_empty());
+            let rest: String = letters[i + 1..]
+                .iter()
+                .collect();
+            if takes_value && !rest.is_empty() {
+                options.push(
+                    (
+                        ctx,
+                        option,
+                        Some(rest),
+                    ),
+                );
+                break;
+            }
+            options.push(
+                (
+                    ctx, option, None,
+                ),
+            );
+        }
+
+        let allowed = match options.as_slice() {
+            [(_, _, value)] => value.is_some(),
+            _ => self
+                .spec
+                .groups_short_options(),
+        };
+        if !allowed {
+            return false;
+        }
+
+        for (ctx, option, value) in options {
+            self.push_option(
+                ctx, option, value, words,
+            );
+        }
+        true
+    }
+
+    fn parse_positional(&mut self, word: &str) {
+        let Some(arguments) = self
+            .current_arguments()
+            .clone()
+        else {
+            self.push_unknown(word);
+            return;
+        };
+
+        while let Some(argument) = arguments.get(self.positional) {
+            if argument
+                .max_values()
+                .is_some_and(|max| self.positional_values >= max)
+            {
+                self.positional += 1;
+                self.positional_values = 0;
+                continue;
+            }
+
+            self.positional_values += 1;
+            self.tokens
+                .push(
+                    ArgumentToken {
+                        ctx: self
+                            .chain
+                            .last()
+                            .cloned(),
+                        spec: argument.clone(),
+                        arg: word.to_owned(),
+                        details: vec![Detail::Source(ValueSource::History)],
+                    },
+                );
+            return;
+        }
+
+        self.push_unknown(word);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_v0_1() -> V0_1 {
+        serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: kubectl
+  version: 1.29.0
+options:
+  - name: "--kubeconfig"
+    recursive: true
+    arguments:
+      - name: FILE
+  - name: "--context"
+    arguments:
+      - name: CONTEXT
+commands:
+  - name: get
+    aliases: [g]
+    arguments:
+      - name: resource
+        required: true
+      - name: name
+        arity:
+          minimum: 0
+    options:
+      - name: "--namespace"
+        aliases: ["-n"]
+        arguments:
+          - name: NAMESPACE
+            required: true
+      - name: "--output"
+        aliases: ["-o"]
+        arguments:
+          - name: FORMAT
+      - name: "--watch"
+        aliases: ["-w"]
+"#,
+        )
+        .unwrap()
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace()
+            .map(String::from)
+            .collect()
+    }
+
+    fn describe(tokens: &[Token]) -> Vec<String> {
+        tokens
+            .iter()
+            .map(
+                |token| match token {
+                    CommandToken {
+                        ctx,
+                    } => format!(
+                        "cmd:{}",
+                        ctx.info
+                            .title
+                    ),
+                    SubCommandToken {
+                        ctx,
+                        ..
+                    } => format!(
+                        "sub:{}",
+                        ctx.spec
+                            .name
+                    ),
+                    OptionToken {
+                        spec,
+                        arg,
+                        ..
+                    } => format!(
+                        "opt:{}={}",
+                        spec.name,
+                        arg.join(",")
+                    ),
+                    ArgumentToken {
+                        spec,
+                        arg,
+                        ..
+                    } => format!(
+                        "arg:{}={}",
+                        spec.name, arg
+                    ),
+                    UnknownToken {
+                        arg,
+                        ..
+                    } => format!(
+                        "unknown:{}",
+                        arg
+                    ),
+                    other => other.to_string(),
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_subcommand_options_and_arguments() {
+        let tokens = parse_command_line(
+            &sample_v0_1(),
+            &args("g pods -n prod -o yaml --watch"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:kubectl",
+                "sub:get",
+                "arg:resource=pods",
+                "opt:--namespace=prod",
+                "opt:--output=yaml",
+                "opt:--watch=",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_values_and_inherited_options() {
+        let tokens = parse_command_line(
+            &sample_v0_1(),
+            &args("get --namespace=kube-system pods --kubeconfig /tmp/cfg"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:kubectl",
+                "sub:get",
+                "opt:--namespace=kube-system",
+                "arg:resource=pods",
+                "opt:--kubeconfig=/tmp/cfg",
+            ]
+        );
+        assert!(
+            matches!(
+                &tokens[4],
+                OptionToken {
+                    ctx: None,
+                    ..
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_variadic_arguments() {
+        let tokens = parse_command_line(
+            &sample_v0_1(),
+            &args("get pods a b"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:kubectl",
+                "sub:get",
+                "arg:resource=pods",
+                "arg:name=a",
+                "arg:name=b",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_words_are_flagged() {
+        let tokens = parse_command_line(
+            &sample_v0_1(),
+            &args("describe --bogus -- x"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:kubectl",
+                "unknown:describe",
+                "unknown:--bogus",
+                "unknown:--",
+                "unknown:x",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_required_option_value_may_start_with_dash() {
+        let tokens = parse_command_line(
+            &sample_v0_1(),
+            &args("get -n -weird -o"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:kubectl",
+                "sub:get",
+                "opt:--namespace=-weird",
+                "opt:--output=",
+            ]
+        );
+    }
+
+    fn tar_v0_1(group_options: bool) -> V0_1 {
+        serde_yml::from_str(
+            &format!(
+                r#"
+opencli: '0.1'
+info:
+  title: tar
+  version: '1.35'
+conventions:
+  groupOptions: {}
+options:
+  - name: "-x"
+  - name: "--verbose"
+    aliases: ["-v"]
+  - name: "-f"
+    arguments:
+      - name: ARCHIVE
+        required: true
+"#,
+                group_options
+            ),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_expands_grouped_short_options() {
+        let tokens = parse_command_line(
+            &tar_v0_1(true),
+            &args("-xvf backup.tar"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:tar",
+                "opt:-x=",
+                "opt:--verbose=",
+                "opt:-f=backup.tar",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_groups_the_spec_does_not_allow() {
+        // Without the convention, or with a letter the spec doesn't know
+        assert_eq!(
+            describe(
+                &parse_command_line(
+                    &tar_v0_1(false),
+                    &args("-xv"),
+                ),
+            ),
+            vec![
+                "cmd:tar",
+                "unknown:-xv",
+            ]
+        );
+        assert_eq!(
+            describe(
+                &parse_command_line(
+                    &tar_v0_1(true),
+                    &args("-xq"),
+                ),
+            ),
+            vec![
+                "cmd:tar",
+                "unknown:-xq",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_attached_short_values() {
+        assert_eq!(
+            describe(
+                &parse_command_line(
+                    &sample_v0_1(),
+                    &args("get pods -nprod -oyaml"),
+                ),
+            ),
+            vec![
+                "cmd:kubectl",
+                "sub:get",
+                "arg:resource=pods",
+                "opt:--namespace=prod",
+                "opt:--output=yaml",
+            ]
+        );
+        // The first option taking a value gets the rest of a group
+        assert_eq!(
+            describe(
+                &parse_command_line(
+                    &tar_v0_1(true),
+                    &args("-xfbackup.tar"),
+                ),
+            ),
+            vec![
+                "cmd:tar",
+                "opt:-x=",
+                "opt:-f=backup.tar",
+            ]
+        );
+        // A flag can't have a value attached
+        assert_eq!(
+            describe(
+                &parse_command_line(
+                    &sample_v0_1(),
+                    &args("get pods -wyes"),
+                ),
+            ),
+            vec![
+                "cmd:kubectl",
+                "sub:get",
+                "arg:resource=pods",
+                "unknown:-wyes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_only_recursive_options_are_inherited() {
+        let tokens = parse_command_line(
+            &sample_v0_1(),
+            &args("--context dev get pods --context prod"),
+        );
+
+        assert_eq!(
+            describe(&tokens),
+            vec![
+                "cmd:kubectl",
+                "opt:--context=dev",
+                "sub:get",
+                "arg:resource=pods",
+                "unknown:--context",
+                "arg:name=prod",
+            ]
+        );
+    }
+}
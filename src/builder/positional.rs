@@ -0,0 +1,257 @@
+use crate::builder::Token::{ArgumentToken, CommandToken, SubCommandToken};
+use crate::builder::{Builder, CommandContext};
+use crate::opencli::v0_1::ArgumentElement;
+
+/// A required positional argument the command is still missing, drawn as a placeholder row
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostPositional {
+    /// Position of the token the row is drawn before, the end of the command's scope
+    pub pos: usize,
+    pub level: usize,
+    pub argument: ArgumentElement,
+    /// How many more values the argument needs
+    pub missing: usize,
+}
+
+impl Builder {
+    /// The positional arguments of the command at `command_pos` with how many values each has
+    fn positional_counts(
+        &self,
+        command_pos: usize,
+    ) -> Vec<(
+        ArgumentElement,
+        usize,
+    )> {
+        let ctx = self
+            .command_chain(command_pos)
+            .into_iter()
+            .next()
+            .flatten();
+        let scope = &self.tokens()[command_pos + 1..self.scope_end(command_pos)];
+
+        self.declarations(ctx)
+            .1
+            .iter()
+            .flatten()
+            .map(
+                |argument| {
+                    let given = scope
+                        .iter()
+                        .filter(
+                            |token| {
+                                matches!(
+                                    token,
+                                    ArgumentToken { spec, .. } if spec.name == argument.name
+                                )
+                            },
+                        )
+                        .count();
+                    (
+                        argument.clone(),
+                        given,
+                    )
+                },
+            )
+            .collect()
+    }
+
+    /// The first positional of the command at `command_pos`, in spec order, that can take
+    /// another value
+    ///
+    /// A variadic argument keeps being offered, as the parser would keep filling it.
+    pub fn next_positional(&self, command_pos: usize) -> Option<ArgumentElement> {
+        self.positional_counts(command_pos)
+            .into_iter()
+            .find(
+                |(argument, given)| {
+                    argument
+                        .max_values()
+                        .is_none_or(|max| *given < max)
+                },
+            )
+            .map(|(argument, _)| argument)
+    }
+
+    /// The next positional for the command owning the selected token, with its context
+    pub fn next_positional_at_selected(
+        &self,
+    ) -> Option<(
+        Option<CommandContext>,
+        ArgumentElement,
+    )> {
+        let command_pos = self.owning_command_pos(self.pos_at_selected());
+        let ctx = self
+            .command_chain(command_pos)
+            .into_iter()
+            .next()
+            .flatten()
+            .cloned();
+
+        self.next_positional(command_pos)
+            .map(
+                |argument| {
+                    (
+                        ctx, argument,
+                    )
+                },
+            )
+    }
+
+    /// Required positionals still missing from every command in the builder
+    pub fn ghost_positionals(&self) -> Vec<GhostPositional> {
+        self.tokens()
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(pos, token)| match token {
+                    CommandToken {
+                        ..
+                    } => Some(
+                        (
+                            pos, 1,
+                        ),
+                    ),
+                    SubCommandToken {
+                        ctx,
+                        ..
+                    } => Some(
+                        (
+                            pos, ctx.level,
+                        ),
+                    ),
+                    _ => None,
+                },
+            )
+            .flat_map(
+                |(command_pos, level)| {
+                    let end = self.scope_end(command_pos);
+                    self.positional_counts(command_pos)
+                        .into_iter()
+                        .filter(|(argument, given)| *given < argument.min_values())
+                        .map(
+                            move |(argument, given)| GhostPositional {
+                                pos: end,
+                                level,
+                                missing: argument.min_values() - given,
+                                argument,
+                            },
+                        )
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::parse_command_line;
+    use crate::opencli::v0_1::V0_1;
+
+    fn sample_v0_1() -> V0_1 {
+        serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: kubectl
+  version: 1.29.0
+commands:
+  - name: get
+    arguments:
+      - name: resource
+        required: true
+      - name: name
+        arity:
+          minimum: 0
+    options:
+      - name: "--watch"
+  - name: cp
+    arguments:
+      - name: source
+        required: true
+      - name: destination
+        required: true
+"#,
+        )
+        .unwrap()
+    }
+
+    fn builder(line: &str) -> Builder {
+        let spec = sample_v0_1();
+        let args: Vec<String> = line
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        Builder::new_demo(
+            spec.clone(),
+            parse_command_line(
+                &spec, &args,
+            ),
+        )
+    }
+
+    fn next_name(builder: &Builder, command_pos: usize) -> Option<String> {
+        builder
+            .next_positional(command_pos)
+            .map(|argument| argument.name)
+    }
+
+    #[test]
+    fn test_next_positional_follows_spec_order_and_arity() {
+        assert_eq!(
+            next_name(
+                &builder("get --watch"),
+                1
+            ),
+            Some("resource".to_string())
+        );
+        assert_eq!(
+            next_name(
+                &builder("get pods"),
+                1
+            ),
+            Some("name".to_string())
+        );
+        // `name` is variadic so it stays on offer
+        assert_eq!(
+            next_name(
+                &builder("get pods web db"),
+                1
+            ),
+            Some("name".to_string())
+        );
+        assert_eq!(
+            next_name(
+                &builder("cp a b"),
+                1
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_required_positionals_are_ghosts() {
+        let builder = builder("cp a");
+        let ghosts = builder.ghost_positionals();
+
+        assert_eq!(
+            ghosts.len(),
+            1
+        );
+        assert_eq!(
+            ghosts[0]
+                .argument
+                .name,
+            "destination"
+        );
+        assert_eq!(
+            ghosts[0].pos,
+            3
+        );
+        assert!(
+            self::builder("get pods")
+                .ghost_positionals()
+                .is_empty()
+        );
+    }
+}
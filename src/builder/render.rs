@@ -0,0 +1,563 @@
+use crate::builder::Token::{
+    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken, UnknownToken,
+};
+use crate::builder::{Builder, Token};
+use crate::opencli::v0_1::V0_1;
+use crate::shell::Quoter;
+use std::ops::Range;
+
+/// Separator used between an option and its value when the spec does not declare one
+const DEFAULT_OPTION_SEPARATOR: &str = " ";
+
+/// The words a single token contributes to the final command
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedSegment {
+    /// Position of the token in the builder
+    pub pos: usize,
+    /// Number of tokens rendered by the segment, more than one for grouped short options
+    pub len: usize,
+    pub words: Vec<String>,
+}
+
+impl Builder {
+    /// The command split into per token word groups, placeholders are skipped
+    pub fn render_segments(&self) -> Vec<RenderedSegment> {
+        let tokens = self.tokens();
+        let groups = self.short_option_groups();
+        let mut segments = vec![];
+
+        let mut pos = 0;
+        while pos < tokens.len() {
+            let segment = match groups
+                .iter()
+                .find(|group| group.start == pos)
+            {
+                Some(group) => RenderedSegment {
+                    pos,
+                    len: group.len(),
+                    words: group_words(&tokens[group.clone()]),
+                },
+                None => RenderedSegment {
+                    pos,
+                    len: 1,
+                    words: token_words(
+                        &tokens[pos],
+                        &self.command_spec,
+                    ),
+                },
+            };
+            pos += segment.len;
+
+            if !segment
+                .words
+                .is_empty()
+            {
+                segments.push(segment);
+            }
+        }
+
+        segments
+    }
+
+    /// Runs of at least two short options that render as one word, like `-alh`
+    ///
+    /// Needs both the spec convention and the user setting. Only the last option of a run can
+    /// take a value, given or not, and a given value only when values are separate words.
+    pub fn short_option_groups(&self) -> Vec<Range<usize>> {
+        if !self.group_options()
+            || !self
+                .command_spec
+                .groups_short_options()
+        {
+            return vec![];
+        }
+
+        let values_allowed = option_separator(&self.command_spec)
+            .trim()
+            .is_empty();
+        let mut groups = vec![];
+        let mut start: Option<usize> = None;
+
+        for (pos, token) in self
+            .tokens()
+            .iter()
+            .enumerate()
+        {
+            let (groupable, closes) = match token {
+                OptionToken {
+                    spec,
+                    arg,
+                    ..
+                } if spec
+                    .short_flag()
+                    .is_some() =>
+                {
+                    // An option taking a value ends the run even before the value is given, as
+                    // getopt would read the next letters as the value
+                    let takes_value = !arg.is_empty()
+                        || spec
+                            .arguments
+                            .as_ref()
+                            .is_some_and(|arguments| !arguments.is_empty());
+                    match takes_value {
+                        false => (
+                            true, false,
+                        ),
+                        true => (
+                            arg.is_empty() || values_allowed,
+                            true,
+                        ),
+                    }
+                }
+                _ => (
+                    false, false,
+                ),
+            };
+
+            if groupable {
+                let run_start = *start.get_or_insert(pos);
+                if closes {
+                    groups.push(run_start..pos + 1);
+                    start = None;
+                }
+            } else if let Some(run_start) = start.take() {
+                groups.push(run_start..pos);
+            }
+        }
+        if let Some(run_start) = start {
+            groups.push(
+                run_start
+                    ..self
+                        .tokens()
+                        .len(),
+            );
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// The command as an argument vector, ready to be executed without a shell
+    pub fn argv(&self) -> Vec<String> {
+        self.render_segments()
+            .into_iter()
+            .flat_map(|segment| segment.words)
+            .collect()
+    }
+
+    /// The command as a single line that can be pasted into the quoter's shell
+    pub fn render(&self, quoter: &dyn Quoter) -> String {
+        quoter.join(&self.argv())
+    }
+
+    /// The segments with their quoted text, laid out in lines
+    ///
+    /// The multi-line form starts a new line at every option so a long command reads as a list
+    /// of flags, continuation markers are left to the caller.
+    pub fn preview_lines(
+        &self,
+        quoter: &dyn Quoter,
+        multiline: bool,
+    ) -> Vec<
+        Vec<(
+            RenderedSegment,
+            String,
+        )>,
+    > {
+        let mut lines: Vec<Vec<_>> = vec![vec![]];
+
+        for segment in self.render_segments() {
+            let breaks = multiline
+                && matches!(
+                    self.tokens()[segment.pos],
+                    OptionToken { .. }
+                )
+                && lines
+                    .last()
+                    .is_some_and(|line| !line.is_empty());
+            if breaks {
+                lines.push(vec![]);
+            }
+
+            let quoted = quoter.join(&segment.words);
+            if let Some(line) = lines.last_mut() {
+                line.push(
+                    (
+                        segment, quoted,
+                    ),
+                );
+            }
+        }
+
+        lines
+    }
+}
+
+fn option_separator(spec: &V0_1) -> &str {
+    spec.conventions
+        .as_ref()
+        .and_then(
+            |conventions| {
+                conventions
+                    .option_separator
+                    .as_deref()
+            },
+        )
+        .filter(|separator| !separator.is_empty())
+        .unwrap_or(DEFAULT_OPTION_SEPARATOR)
+}
+
+/// The raw words for consecutive short options, the flags joined into one word
+fn group_words(tokens: &[Token]) -> Vec<String> {
+    let mut flags = String::from("-");
+    let mut values = vec![];
+    for token in tokens {
+        if let OptionToken {
+            spec: option,
+            arg,
+            ..
+        } = token
+        {
+            flags.extend(option.short_flag());
+            values.extend_from_slice(arg);
+        }
+    }
+
+    let mut words = vec![flags];
+    words.extend(values);
+    words
+}
+
+/// The raw (unquoted) words for a token
+pub fn token_words(token: &Token, spec: &V0_1) -> Vec<String> {
+    match token {
+        PlaceholderToken => vec![],
+        CommandToken {
+            ctx,
+        } => vec![
+            ctx.info
+                .title
+                .to_owned(),
+        ],
+        SubCommandToken {
+            ctx,
+            ..
+        } => vec![
+            ctx.spec
+                .name
+                .to_owned(),
+        ],
+        OptionToken {
+            spec: option,
+            arg,
+            ..
+        } => {
+            let separator = option_separator(spec);
+            match arg.split_first() {
+                None => vec![
+                    option
+                        .name
+                        .to_owned(),
+                ],
+                Some((first, rest))
+                    if separator
+                        .trim()
+                        .is_empty() =>
+                {
+                    let mut words = vec![
+                        option
+                            .name
+                            .to_owned(),
+                        first.to_owned(),
+                    ];
+                    words.extend_from_slice(rest);
+                    words
+                }
+                Some((first, rest)) => {
+                    let mut words = vec![
+                        format!(
+                            "{}{}{}",
+                            option.name, separator, first
+                        ),
+                    ];
+                    words.extend_from_slice(rest);
+                    words
+                }
+            }
+        }
+        ArgumentToken {
+            arg,
+            ..
+        }
+        | UnknownToken {
+            arg,
+            ..
+        } => vec![arg.to_owned()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::CommandContext;
+    use crate::opencli::v0_1::CommandElement;
+    use crate::shell::Shell;
+
+    fn sample_v0_1(separator: &str) -> V0_1 {
+        serde_yml::from_str(
+            &format!(
+                r#"
+opencli: '0.1'
+info:
+  title: kubectl
+  version: 1.29.0
+conventions:
+  optionSeparator: "{}"
+commands:
+  - name: get
+    arguments:
+      - name: resource
+    options:
+      - name: "--selector"
+"#,
+                separator
+            ),
+        )
+        .unwrap()
+    }
+
+    fn sample_builder(separator: &str, value: &str) -> Builder {
+        let spec = sample_v0_1(separator);
+        let get: CommandElement = spec
+            .commands
+            .as_ref()
+            .unwrap()[0]
+            .clone();
+        let ctx = CommandContext::new(
+            1,
+            get.clone(),
+        );
+
+        Builder::new_demo(
+            spec.clone(),
+            vec![
+                CommandToken {
+                    ctx: spec,
+                },
+                SubCommandToken {
+                    ctx: ctx.clone(),
+                    details: vec![],
+                },
+                ArgumentToken {
+                    ctx: Some(ctx.clone()),
+                    spec: get
+                        .arguments
+                        .as_ref()
+                        .unwrap()[0]
+                        .clone(),
+                    arg: "pods".to_string(),
+                    details: vec![],
+                },
+                OptionToken {
+                    ctx: Some(ctx),
+                    spec: get
+                        .options
+                        .as_ref()
+                        .unwrap()[0]
+                        .clone(),
+                    arg: vec![value.to_string()],
+                    details: vec![],
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_render_with_space_separator() {
+        let builder = sample_builder(
+            " ", "app=web",
+        );
+
+        assert_eq!(
+            builder.render(Shell::Bash.quoter()),
+            "kubectl get pods --selector app=web"
+        );
+        assert_eq!(
+            builder.argv(),
+            vec![
+                "kubectl",
+                "get",
+                "pods",
+                "--selector",
+                "app=web"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_with_equals_separator() {
+        let builder = sample_builder(
+            "=",
+            "tier in (a, b)",
+        );
+
+        assert_eq!(
+            builder.render(Shell::Bash.quoter()),
+            "kubectl get pods '--selector=tier in (a, b)'"
+        );
+    }
+
+    #[test]
+    fn test_render_segments_skip_placeholder() {
+        let builder = sample_builder(
+            " ", "x",
+        );
+        let segments = builder.render_segments();
+
+        assert_eq!(
+            segments.len(),
+            4
+        );
+        assert_eq!(
+            segments[3].pos,
+            3
+        );
+    }
+
+    fn grouped_builder(line: &str) -> Builder {
+        let spec: V0_1 = serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: tar
+  version: '1.35'
+conventions:
+  groupOptions: true
+options:
+  - name: "-x"
+  - name: "--verbose"
+    aliases: ["-v"]
+  - name: "--file"
+    aliases: ["-f"]
+    arguments:
+      - name: ARCHIVE
+  - name: "--exclude"
+    arguments:
+      - name: PATTERN
+"#,
+        )
+        .unwrap();
+        let args: Vec<String> = line
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+
+        Builder::new_demo(
+            spec.clone(),
+            crate::builder::parse_command_line(
+                &spec, &args,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_short_options_render_grouped() {
+        let builder = grouped_builder("-x -v -f a.tar --exclude *.log -v -x");
+        let segments = builder.render_segments();
+
+        assert_eq!(
+            builder.argv(),
+            vec![
+                "tar",
+                "-xvf",
+                "a.tar",
+                "--exclude",
+                "*.log",
+                "-vx"
+            ]
+        );
+        assert_eq!(
+            segments[1].len,
+            3
+        );
+        assert_eq!(
+            segments[3].pos,
+            5
+        );
+    }
+
+    #[test]
+    fn test_option_waiting_for_its_value_closes_the_group() {
+        let builder = grouped_builder("-x -f -v");
+
+        assert_eq!(
+            builder.argv(),
+            vec![
+                "tar",
+                "-xf",
+                "--verbose"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grouping_can_be_turned_off() {
+        let mut builder = grouped_builder("-xvf a.tar");
+        builder.set_group_options(false);
+
+        assert_eq!(
+            builder.argv(),
+            vec![
+                "tar",
+                "-x",
+                "--verbose",
+                "--file",
+                "a.tar"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preview_lines_break_at_options() {
+        let builder = sample_builder(
+            " ", "app=web",
+        );
+        let texts = |multiline: bool| -> Vec<Vec<String>> {
+            builder
+                .preview_lines(
+                    Shell::Bash.quoter(),
+                    multiline,
+                )
+                .into_iter()
+                .map(
+                    |line| {
+                        line.into_iter()
+                            .map(|(_, text)| text)
+                            .collect()
+                    },
+                )
+                .collect()
+        };
+
+        assert_eq!(
+            texts(false),
+            vec![
+                vec![
+                    "kubectl",
+                    "get",
+                    "pods",
+                    "--selector app=web"
+                ]
+            ]
+        );
+        assert_eq!(
+            texts(true),
+            vec![
+                vec![
+                    "kubectl", "get", "pods"
+                ],
+                vec!["--selector app=web"]
+            ]
+        );
+    }
+}
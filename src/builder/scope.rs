@@ -0,0 +1,216 @@
+use crate::builder::{Builder, CommandContext};
+use crate::opencli::v0_1::OptionElement;
+
+/// An option usable at some point of the command line, with the command that declares it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopedOption {
+    pub ctx: Option<CommandContext>,
+    pub spec: OptionElement,
+    /// Name of the declaring command
+    pub origin: String,
+    /// Declared by an ancestor and passed down because it is recursive
+    pub inherited: bool,
+}
+
+impl Builder {
+    /// Options of the command at `command_pos`, followed by recursive options of its ancestors
+    ///
+    /// An option declared closer to the command hides an inherited one with the same name.
+    pub fn options_in_scope(&self, command_pos: usize) -> Vec<ScopedOption> {
+        let mut scoped: Vec<ScopedOption> = vec![];
+
+        for (depth, ctx) in self
+            .command_chain(command_pos)
+            .into_iter()
+            .enumerate()
+        {
+            let origin = match ctx {
+                None => self
+                    .cmd_title()
+                    .clone(),
+                Some(ctx) => ctx
+                    .spec
+                    .name
+                    .clone(),
+            };
+
+            for option in self
+                .declarations(ctx)
+                .0
+                .iter()
+                .flatten()
+                .filter(|option| depth == 0 || option.recursive)
+            {
+                if scoped
+                    .iter()
+                    .any(
+                        |known| {
+                            known
+                                .spec
+                                .name
+                                == option.name
+                        },
+                    )
+                {
+                    continue;
+                }
+                scoped.push(
+                    ScopedOption {
+                        ctx: ctx.cloned(),
+                        spec: option.clone(),
+                        origin: origin.clone(),
+                        inherited: depth > 0,
+                    },
+                );
+            }
+        }
+
+        scoped
+    }
+
+    /// Options usable where the selected token sits
+    pub fn options_in_scope_at_selected(&self) -> Vec<ScopedOption> {
+        self.options_in_scope(self.owning_command_pos(self.pos_at_selected()))
+    }
+
+    /// Recursive options the selected token's command inherits from its ancestors
+    pub fn inherited_options_at_selected(&self) -> Vec<ScopedOption> {
+        self.options_in_scope_at_selected()
+            .into_iter()
+            .filter(|option| option.inherited)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::parse_command_line;
+    use crate::opencli::v0_1::V0_1;
+
+    fn sample_v0_1() -> V0_1 {
+        serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: kubectl
+  version: 1.29.0
+options:
+  - name: "--kubeconfig"
+    recursive: true
+  - name: "--namespace"
+    recursive: true
+  - name: "--version"
+commands:
+  - name: config
+    options:
+      - name: "--namespace"
+    commands:
+      - name: view
+        options:
+          - name: "--raw"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn builder(line: &str) -> Builder {
+        let spec = sample_v0_1();
+        let args: Vec<String> = line
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        Builder::new_demo(
+            spec.clone(),
+            parse_command_line(
+                &spec, &args,
+            ),
+        )
+    }
+
+    fn names(
+        options: &[ScopedOption],
+    ) -> Vec<(
+        &str,
+        &str,
+    )> {
+        options
+            .iter()
+            .map(
+                |option| {
+                    (
+                        option
+                            .spec
+                            .name
+                            .as_str(),
+                        option
+                            .origin
+                            .as_str(),
+                    )
+                },
+            )
+            .collect()
+    }
+
+    #[test]
+    fn test_root_scope_has_all_root_options() {
+        let builder = builder("config view");
+
+        assert_eq!(
+            names(&builder.options_in_scope(0)),
+            vec![
+                (
+                    "--kubeconfig",
+                    "kubectl"
+                ),
+                (
+                    "--namespace",
+                    "kubectl"
+                ),
+                (
+                    "--version",
+                    "kubectl"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recursive_options_are_inherited_and_shadowed() {
+        let builder = builder("config view");
+        let scoped = builder.options_in_scope(2);
+
+        assert_eq!(
+            names(&scoped),
+            vec![
+                (
+                    "--raw", "view"
+                ),
+                (
+                    "--kubeconfig",
+                    "kubectl"
+                ),
+                (
+                    "--namespace",
+                    "kubectl"
+                ),
+            ]
+        );
+        assert!(scoped[1].inherited);
+
+        // `config` declares its own `--namespace`, which hides the root's but is not passed on
+        assert_eq!(
+            names(&builder.options_in_scope(1)),
+            vec![
+                (
+                    "--namespace",
+                    "config"
+                ),
+                (
+                    "--kubeconfig",
+                    "kubectl"
+                ),
+            ]
+        );
+    }
+}
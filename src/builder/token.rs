@@ -1,8 +1,9 @@
+use crate::builder::Diagnostic;
 use crate::builder::token::Token::*;
-use crate::opencli::v0_1::{ArgumentElement, CommandElement, OptionElement, V0_1};
+use crate::opencli::v0_1::{ArgumentElement, CommandElement, ExitCodeElement, OptionElement, V0_1};
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     PlaceholderToken,
     CommandToken {
@@ -24,12 +25,44 @@ pub enum Token {
         arg: String,
         details: Vec<Detail>,
     },
+    /// A word the spec does not describe, kept so the command is not silently changed
+    UnknownToken {
+        ctx: Option<CommandContext>,
+        arg: String,
+        details: Vec<Detail>,
+    },
+}
+
+/// Context about a token shown in the details panel
+///
+/// Tokens only carry their [`Detail::Source`], the rest is derived from the builder when the
+/// token is selected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Detail {
+    /// A problem validation found with the token
+    Diagnostic(Diagnostic),
+    /// The option is declared by this enclosing command
+    InheritedFrom(String),
+    /// The value the spec documents for when none is given
+    Default(String),
+    /// How the token's value was entered
+    Source(ValueSource),
+    /// What the active command's exit codes mean
+    ExitCodes(Vec<ExitCodeElement>),
 }
 
-#[derive(Clone, Debug)]
-pub enum Detail {}
+/// Where a token's value came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueSource {
+    /// Entered as free text
+    Typed,
+    /// Picked from accepted values or a completion provider
+    Completed,
+    /// Part of the command line cmdi was started with, usually recalled from shell history
+    History,
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CommandContext {
     pub level: usize,
     pub(crate) spec: CommandElement,
@@ -75,6 +108,12 @@ impl Display for Token {
                 f,
                 "ArgumentToken"
             ),
+            UnknownToken {
+                ..
+            } => write!(
+                f,
+                "UnknownToken"
+            ),
         }
     }
 }
@@ -82,13 +121,92 @@ impl Display for Token {
 impl Token {
     pub(crate) fn args(&self) -> Vec<String> {
         match self {
-            PlaceholderToken => vec!(),
-            CommandToken { .. } => vec!(),
-            OptionToken { arg, ..} => arg.to_owned(),
-            SubCommandToken { .. } => vec!(),
-            ArgumentToken {arg, .. } =>  vec!(arg.to_owned())
+            PlaceholderToken => vec![],
+            CommandToken {
+                ..
+            } => vec![],
+            OptionToken {
+                arg,
+                ..
+            } => arg.to_owned(),
+            SubCommandToken {
+                ..
+            } => vec![],
+            ArgumentToken {
+                arg,
+                ..
+            } => vec![arg.to_owned()],
+            UnknownToken {
+                arg,
+                ..
+            } => vec![arg.to_owned()],
+        }
+    }
+    /// The details the token carries
+    pub fn details(&self) -> &[Detail] {
+        match self {
+            PlaceholderToken
+            | CommandToken {
+                ..
+            } => &[],
+            OptionToken {
+                details,
+                ..
+            }
+            | SubCommandToken {
+                details,
+                ..
+            }
+            | ArgumentToken {
+                details,
+                ..
+            }
+            | UnknownToken {
+                details,
+                ..
+            } => details,
+        }
+    }
+
+    /// Where the token's value came from, if it has one
+    pub fn source(&self) -> Option<ValueSource> {
+        self.details()
+            .iter()
+            .find_map(
+                |detail| match detail {
+                    Detail::Source(source) => Some(*source),
+                    _ => None,
+                },
+            )
+    }
+
+    /// Record where the token's value came from, replacing any earlier source
+    pub(crate) fn set_source(&mut self, source: ValueSource) {
+        if let OptionToken {
+            details,
+            ..
+        }
+        | ArgumentToken {
+            details,
+            ..
+        }
+        | UnknownToken {
+            details,
+            ..
+        } = self
+        {
+            details.retain(
+                |detail| {
+                    !matches!(
+                        detail,
+                        Detail::Source(_)
+                    )
+                },
+            );
+            details.push(Detail::Source(source));
         }
     }
+
     pub(crate) fn level(&self) -> usize {
         match self {
             CommandToken {
@@ -107,6 +225,10 @@ impl Token {
                 ctx,
                 ..
             } => Self::opt_to_level(ctx),
+            UnknownToken {
+                ctx,
+                ..
+            } => Self::opt_to_level(ctx),
         }
     }
 
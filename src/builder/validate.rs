@@ -0,0 +1,554 @@
+use crate::builder::Token::{
+    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken, UnknownToken,
+};
+use crate::builder::{Builder, Token};
+use crate::opencli::v0_1::{ArgumentElement, OptionElement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found with the token at `pos`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub pos: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(pos: usize, message: String) -> Self {
+        Self {
+            pos,
+            severity: Severity::Error,
+            message,
+        }
+    }
+
+    fn warning(pos: usize, message: String) -> Self {
+        Self {
+            pos,
+            severity: Severity::Warning,
+            message,
+        }
+    }
+}
+
+impl Builder {
+    /// Check the composed command against the spec
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let tokens = self.tokens();
+        let mut diagnostics = vec![];
+
+        for (pos, token) in tokens
+            .iter()
+            .enumerate()
+        {
+            match token {
+                CommandToken {
+                    ctx,
+                } => diagnostics.extend(
+                    check_command(
+                        tokens,
+                        pos,
+                        &ctx.options,
+                        &ctx.arguments,
+                    ),
+                ),
+                SubCommandToken {
+                    ctx,
+                    ..
+                } => diagnostics.extend(
+                    check_command(
+                        tokens,
+                        pos,
+                        &ctx.spec
+                            .options,
+                        &ctx.spec
+                            .arguments,
+                    ),
+                ),
+                OptionToken {
+                    spec,
+                    arg,
+                    ..
+                } => diagnostics.extend(
+                    check_option(
+                        pos, spec, arg,
+                    ),
+                ),
+                ArgumentToken {
+                    spec,
+                    arg,
+                    ..
+                } => diagnostics.extend(
+                    check_value(
+                        pos, spec, arg,
+                    ),
+                ),
+                UnknownToken {
+                    arg,
+                    ..
+                } => diagnostics.push(
+                    Diagnostic::warning(
+                        pos,
+                        t!(
+                            "diagnostics.unknown",
+                            word = arg
+                        )
+                        .to_string(),
+                    ),
+                ),
+                PlaceholderToken => {}
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Whether the command has problems that should be confirmed before it is emitted
+    pub fn has_errors(&self) -> bool {
+        self.validate()
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+/// The tokens owned by the command at `pos`, up to the next subcommand
+fn command_scope(tokens: &[Token], pos: usize) -> &[Token] {
+    let end = tokens
+        .iter()
+        .enumerate()
+        .skip(pos + 1)
+        .find(
+            |(_, token)| {
+                matches!(
+                    token,
+                    SubCommandToken { .. }
+                )
+            },
+        )
+        .map(|(i, _)| i)
+        .unwrap_or(tokens.len());
+
+    &tokens[pos + 1..end]
+}
+
+/// Required options and positional arguments of the command at `pos`
+fn check_command(
+    tokens: &[Token],
+    pos: usize,
+    options: &Option<Vec<OptionElement>>,
+    arguments: &Option<Vec<ArgumentElement>>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let scope = command_scope(
+        tokens, pos,
+    );
+
+    for option in options
+        .iter()
+        .flatten()
+        .filter(|option| option.required)
+    {
+        // A recursive option may also be given to any subcommand
+        let given_to = match option.recursive {
+            true => &tokens[pos + 1..],
+            false => scope,
+        };
+        let present = given_to
+            .iter()
+            .any(
+                |token| {
+                    matches!(
+                        token,
+                        OptionToken { spec, .. } if spec.name == option.name
+                    )
+                },
+            );
+        if !present {
+            diagnostics.push(
+                Diagnostic::error(
+                    pos,
+                    t!(
+                        "diagnostics.missing_option",
+                        name = option.name
+                    )
+                    .to_string(),
+                ),
+            );
+        }
+    }
+
+    for argument in arguments
+        .iter()
+        .flatten()
+    {
+        let given = scope
+            .iter()
+            .filter(
+                |token| {
+                    matches!(
+                        token,
+                        ArgumentToken { spec, .. } if spec.name == argument.name
+                    )
+                },
+            )
+            .count();
+
+        if given < argument.min_values() {
+            diagnostics.push(
+                Diagnostic::error(
+                    pos,
+                    t!(
+                        "diagnostics.missing_argument",
+                        name = argument
+                            .name
+                            .to_uppercase()
+                    )
+                    .to_string(),
+                ),
+            );
+        }
+        if let Some(max) = argument
+            .max_values()
+            .filter(|max| given > *max)
+        {
+            diagnostics.push(
+                Diagnostic::error(
+                    pos,
+                    t!(
+                        "diagnostics.too_many_values",
+                        name = argument
+                            .name
+                            .to_uppercase(),
+                        max = max
+                    )
+                    .to_string(),
+                ),
+            );
+        }
+    }
+
+    diagnostics
+}
+
+/// Value counts and accepted values of an option's arguments
+fn check_option(pos: usize, option: &OptionElement, values: &[String]) -> Vec<Diagnostic> {
+    let arguments = option
+        .arguments
+        .as_deref()
+        .unwrap_or_default();
+    let mut diagnostics = vec![];
+
+    if arguments.is_empty() {
+        if !values.is_empty() {
+            diagnostics.push(
+                Diagnostic::error(
+                    pos,
+                    t!(
+                        "diagnostics.unexpected_value",
+                        name = option.name
+                    )
+                    .to_string(),
+                ),
+            );
+        }
+        return diagnostics;
+    }
+
+    let (assigned, remaining) = option.split_values(values);
+    for (argument, mine) in arguments
+        .iter()
+        .zip(assigned)
+    {
+        if mine.len() < argument.min_values() {
+            diagnostics.push(
+                Diagnostic::error(
+                    pos,
+                    t!(
+                        "diagnostics.missing_argument",
+                        name = argument
+                            .name
+                            .to_uppercase()
+                    )
+                    .to_string(),
+                ),
+            );
+        }
+        for value in mine {
+            diagnostics.extend(
+                check_value(
+                    pos, argument, value,
+                ),
+            );
+        }
+    }
+
+    if !remaining.is_empty() {
+        diagnostics.push(
+            Diagnostic::error(
+                pos,
+                t!(
+                    "diagnostics.unexpected_value",
+                    name = option.name
+                )
+                .to_string(),
+            ),
+        );
+    }
+
+    diagnostics
+}
+
+fn check_value(pos: usize, argument: &ArgumentElement, value: &str) -> Option<Diagnostic> {
+    let accepted = argument
+        .accepted_values
+        .as_ref()?;
+
+    if accepted
+        .iter()
+        .any(|accepted| accepted == value)
+    {
+        None
+    } else {
+        Some(
+            Diagnostic::error(
+                pos,
+                t!(
+                    "diagnostics.not_accepted",
+                    value = value,
+                    accepted = accepted.join(", ")
+                )
+                .to_string(),
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::parse_command_line;
+    use crate::opencli::v0_1::V0_1;
+
+    fn sample_v0_1() -> V0_1 {
+        serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: kubectl
+  version: 1.29.0
+commands:
+  - name: get
+    arguments:
+      - name: resource
+        required: true
+        acceptedValues: [pods, services]
+    options:
+      - name: "--output"
+        arguments:
+          - name: FORMAT
+            required: true
+            acceptedValues: [json, yaml]
+      - name: "--watch"
+  - name: apply
+    options:
+      - name: "-f"
+        required: true
+        arguments:
+          - name: FILE
+            arity:
+              minimum: 1
+              maximum: 2
+"#,
+        )
+        .unwrap()
+    }
+
+    fn validate(line: &str) -> Vec<Diagnostic> {
+        let spec = sample_v0_1();
+        let args: Vec<String> = line
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        Builder::new_demo(
+            spec.clone(),
+            parse_command_line(
+                &spec, &args,
+            ),
+        )
+        .validate()
+    }
+
+    #[test]
+    fn test_valid_command_has_no_diagnostics() {
+        assert_eq!(
+            validate("get pods --output yaml --watch"),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_missing_required_items() {
+        let diagnostics = validate("apply");
+        assert_eq!(
+            diagnostics.len(),
+            1
+        );
+        assert_eq!(
+            diagnostics[0].pos,
+            1
+        );
+
+        let diagnostics = validate("get");
+        assert_eq!(
+            diagnostics.len(),
+            1
+        );
+        assert_eq!(
+            diagnostics[0].severity,
+            Severity::Error
+        );
+    }
+
+    #[test]
+    fn test_values_outside_accepted_values() {
+        let diagnostics = validate("get nodes --output xml");
+        let positions: Vec<usize> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.pos)
+            .collect();
+
+        assert_eq!(
+            positions,
+            vec![
+                2, 3
+            ]
+        );
+    }
+
+    #[test]
+    fn test_arity_is_checked() {
+        let spec = sample_v0_1();
+        let apply = spec
+            .commands
+            .as_ref()
+            .unwrap()[1]
+            .clone();
+        let option = apply
+            .options
+            .as_ref()
+            .unwrap()[0]
+            .clone();
+
+        assert_eq!(
+            check_option(
+                0,
+                &option,
+                &[]
+            )
+            .len(),
+            1
+        );
+        assert_eq!(
+            check_option(
+                0,
+                &option,
+                &[
+                    "a".to_string(),
+                    "b".to_string()
+                ]
+            )
+            .len(),
+            0
+        );
+        assert_eq!(
+            check_option(
+                0,
+                &option,
+                &[
+                    "a".to_string(),
+                    "b".to_string(),
+                    "c".to_string()
+                ]
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_unknown_words_warn() {
+        let diagnostics = validate("get pods --bogus");
+
+        assert_eq!(
+            diagnostics.len(),
+            1
+        );
+        assert_eq!(
+            diagnostics[0].severity,
+            Severity::Warning
+        );
+    }
+
+    #[test]
+    fn test_required_options_belong_to_their_command() {
+        let spec: V0_1 = serde_yml::from_str(
+            r#"
+opencli: '0.1'
+info:
+  title: app
+  version: '1.0'
+options:
+  - name: "--name"
+    required: true
+    arguments:
+      - name: NAME
+  - name: "--profile"
+    required: true
+    recursive: true
+    arguments:
+      - name: PROFILE
+commands:
+  - name: create
+    options:
+      - name: "--name"
+        required: true
+        arguments:
+          - name: NAME
+"#,
+        )
+        .unwrap();
+        let validate = |line: &str| {
+            let args: Vec<String> = line
+                .split_whitespace()
+                .map(String::from)
+                .collect();
+            Builder::new_demo(
+                spec.clone(),
+                parse_command_line(
+                    &spec, &args,
+                ),
+            )
+            .validate()
+        };
+
+        // The subcommand's `--name` leaves the program's missing, `--profile` is inherited
+        let diagnostics = validate("create --name x --profile dev");
+        assert_eq!(
+            diagnostics.len(),
+            1
+        );
+        assert_eq!(
+            diagnostics[0].pos,
+            0
+        );
+        assert_eq!(
+            validate("--name x create --name y --profile dev"),
+            vec![]
+        );
+    }
+}
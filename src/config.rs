@@ -1,29 +1,206 @@
-use clap::Parser;
+use crate::opencli::docs::DocsFormat;
+use crate::shell::{Shell, split_words};
+use clap::{Parser, Subcommand};
+use color_eyre::eyre::eyre;
 use config::{Config, Environment, File};
 use directories::ProjectDirs;
 use serde::Deserialize;
+use std::path::PathBuf;
 
 /// Command-line arguments
 #[derive(Parser, Debug)]
-#[command(name = "cmdi", version, about)]
+#[command(name = "cmdi", version, about, args_conflicts_with_subcommands = true)]
 pub struct Cli {
-    /// The [COMMAND] line program to build
-    pub cmd: String,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+
+    /// The [COMMAND] line program to build, or a path to its OpenCLI spec
+    #[arg(required_unless_present = "line")]
+    pub cmd: Option<String>,
+
+    /// An existing command line to start from, given after `--`
+    #[arg(last = true)]
+    pub args: Vec<String>,
+
+    /// An existing command line as one string, program name included, split like the shell would
+    ///
+    /// Its first word is the program to build when [COMMAND] is not given.
+    #[arg(long, conflicts_with = "args")]
+    pub line: Option<String>,
+
+    /// Write the composed command to this file instead of stdout
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// Start from a pre-filled demo command instead of an empty one
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Shell to quote the composed command for (defaults to $SHELL)
+    #[arg(long, value_enum)]
+    pub shell: Option<Shell>,
+}
+
+/// Subcommands next to building a command
+#[derive(Subcommand, Debug)]
+pub enum CliCommand {
+    /// Print the integration script for SHELL, binding Ctrl-X c to edit the current line
+    Init {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Create and inspect OpenCLI specs
+    Spec {
+        #[command(subcommand)]
+        command: SpecCommand,
+    },
+}
+
+/// Ways of producing a spec, and what can be generated from one
+#[derive(Subcommand, Debug)]
+pub enum SpecCommand {
+    /// Generate a spec from PROGRAM's --help output and that of its subcommands
+    ImportHelp {
+        program: String,
+
+        /// How many levels of subcommands to run --help for
+        #[arg(long, default_value_t = 2)]
+        depth: usize,
+
+        /// Where to write the spec, defaults to the user spec directory
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Replace an existing spec
+        #[arg(long)]
+        force: bool,
+    },
+    /// Generate a spec from the man page of PROGRAM
+    ImportMan {
+        /// A program name, or the path of a man page like `ls.1` or `ls.1.gz`
+        page: String,
+
+        /// Where to write the spec, defaults to the user spec directory
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Replace an existing spec
+        #[arg(long)]
+        force: bool,
+    },
+    /// Generate a spec from the fish or zsh completion installed for PROGRAM
+    ImportCompletion {
+        /// A program name, or the path of a completion file like `git.fish` or `_git`
+        program: String,
+
+        /// Where to write the spec, defaults to the user spec directory
+        #[arg(long)]
+        output: Option<PathBuf>,
+
+        /// Replace an existing spec
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print a completion script for PROGRAM generated from its spec
+    Completions {
+        /// A program name, or the path of its OpenCLI spec
+        program: String,
+
+        /// Shell to write the script for (defaults to $SHELL)
+        #[arg(long, value_enum)]
+        shell: Option<Shell>,
+
+        /// Write the script to this file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Write reference pages for PROGRAM and each of its commands, generated from its spec
+    Docs {
+        /// A program name, or the path of its OpenCLI spec
+        program: String,
+
+        #[arg(long, value_enum, default_value_t = DocsFormat::Man)]
+        format: DocsFormat,
+
+        /// Directory to write the pages to
+        #[arg(long, default_value = ".")]
+        output: PathBuf,
+    },
+}
+
+impl Cli {
+    /// The program to build and the arguments to start from
+    pub fn command_line(
+        &self,
+    ) -> color_eyre::Result<(
+        String,
+        Vec<String>,
+    )> {
+        let Some(line) = &self.line else {
+            return Ok(
+                (
+                    self.cmd
+                        .clone()
+                        .ok_or_else(|| eyre!("No command to build"))?,
+                    self.args
+                        .clone(),
+                ),
+            );
+        };
+
+        let mut words = split_words(line)?.into_iter();
+        let program = words
+            .next()
+            .ok_or_else(|| eyre!("The command line to start from is empty"))?;
+
+        Ok(
+            (
+                self.cmd
+                    .clone()
+                    .unwrap_or(program),
+                words.collect(),
+            ),
+        )
+    }
 }
 
 /// Main app configuration
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
-pub struct Settings {}
+pub struct Settings {
+    /// Quoting dialect for the composed command
+    pub shell: Shell,
+
+    /// Render short flags as `-alh` when the spec allows grouping
+    pub group_options: bool,
+
+    /// Backend used to pick options, commands and values
+    pub selector: Selector,
+}
+
+/// How lists are searched and picked from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selector {
+    /// Built-in fuzzy finder drawn inside the TUI
+    #[default]
+    Native,
+    /// The external `fzf` binary, needs it on the `PATH`
+    Fzf,
+}
 
 impl Default for Settings {
     fn default() -> Self {
-        Self {}
+        Self {
+            shell: Shell::detect(),
+            group_options: true,
+            selector: Selector::default(),
+        }
     }
 }
 
 /// Load settings from defaults, file, env, and CLI
-pub fn load(_cli: &Cli, directories: ProjectDirs) -> Result<Settings, config::ConfigError> {
+pub fn load(cli: &Cli, directories: ProjectDirs) -> Result<Settings, config::ConfigError> {
     let config_file = directories
         .config_dir()
         .join("config");
@@ -37,11 +214,93 @@ pub fn load(_cli: &Cli, directories: ProjectDirs) -> Result<Settings, config::Co
             )
             .required(false),
         )
-        .add_source(Environment::with_prefix("CMDI_"));
-
-    //TODO add useful commandline args here
+        .add_source(Environment::with_prefix("CMDI_"))
+        .set_override_option(
+            "shell",
+            cli.shell
+                .map(|shell| shell.to_string()),
+        )?;
 
     builder
         .build()?
         .try_deserialize()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_line(
+        args: &[&str],
+    ) -> (
+        String,
+        Vec<String>,
+    ) {
+        Cli::try_parse_from(args)
+            .unwrap()
+            .command_line()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_command_line_from_args_or_line() {
+        assert_eq!(
+            command_line(
+                &[
+                    "cmdi", "kubectl", "--", "get", "pods"
+                ]
+            ),
+            (
+                "kubectl".to_string(),
+                vec![
+                    "get".to_string(),
+                    "pods".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            command_line(
+                &[
+                    "cmdi",
+                    "--line=kubectl get 'my pod'"
+                ]
+            ),
+            (
+                "kubectl".to_string(),
+                vec![
+                    "get".to_string(),
+                    "my pod".to_string()
+                ]
+            )
+        );
+        // An explicit spec replaces the line's program
+        assert_eq!(
+            command_line(
+                &[
+                    "cmdi",
+                    "./kubectl.yaml",
+                    "--line",
+                    "kubectl get"
+                ]
+            )
+            .0,
+            "./kubectl.yaml"
+        );
+        assert!(
+            matches!(
+                Cli::try_parse_from(
+                    [
+                        "cmdi", "init", "zsh"
+                    ]
+                )
+                .unwrap()
+                .command,
+                Some(
+                    CliCommand::Init {
+                        shell: Shell::Zsh
+                    }
+                )
+            )
+        );
+    }
+}
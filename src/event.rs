@@ -30,6 +30,8 @@ pub enum Event {
     OptionSelection(OptionElement),
     Selection(Vec<usize>),
     NoSelection,
+    /// A command run from cmdi finished, with its exit code when it has one
+    CommandExited(Option<i32>),
     /// Direct exit event
     ///
     ///
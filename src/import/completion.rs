@@ -0,0 +1,171 @@
+//! Find the completion definitions installed for a program and import them
+
+use crate::import::{fish, zsh};
+use crate::opencli::v0_1::V0_1;
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// System wide fish completion directories, after the user's
+const FISH_DIRS: [&str; 4] = [
+    "/usr/local/share/fish/vendor_completions.d",
+    "/usr/local/share/fish/completions",
+    "/usr/share/fish/vendor_completions.d",
+    "/usr/share/fish/completions",
+];
+
+/// System wide zsh function directories, after `$FPATH`
+const ZSH_DIRS: [&str; 3] = [
+    "/usr/local/share/zsh/site-functions",
+    "/usr/share/zsh/site-functions",
+    "/usr/share/zsh/vendor-completions",
+];
+
+/// Where zsh keeps its own completions, one subdirectory per platform
+const ZSH_COMPLETION_DIR: &str = "/usr/share/zsh/functions/Completion";
+
+/// The syntax of a completion file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Syntax {
+    Fish,
+    Zsh,
+}
+
+impl Syntax {
+    /// Tell the syntax from the file name, or a `#compdef` line for zsh functions
+    pub fn detect(path: &Path, text: &str) -> Option<Self> {
+        let name = path
+            .file_name()?
+            .to_string_lossy();
+        if name.ends_with(".fish") {
+            Some(Syntax::Fish)
+        } else if name.starts_with('_') || zsh::compdef_program(text).is_some() {
+            Some(Syntax::Zsh)
+        } else {
+            None
+        }
+    }
+}
+
+/// Completion files that may define `program`, fish ones first
+fn candidates(program: &str) -> Vec<PathBuf> {
+    let mut fish_dirs: Vec<PathBuf> = vec![];
+    if let Some(config) = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    {
+        fish_dirs.push(config.join("fish/completions"));
+    }
+    fish_dirs.extend(
+        FISH_DIRS
+            .iter()
+            .map(PathBuf::from),
+    );
+
+    let mut zsh_dirs: Vec<PathBuf> = env::var("FPATH")
+        .map(|fpath| env::split_paths(&fpath).collect())
+        .unwrap_or_default();
+    zsh_dirs.extend(
+        ZSH_DIRS
+            .iter()
+            .map(PathBuf::from),
+    );
+    if let Ok(entries) = fs::read_dir(ZSH_COMPLETION_DIR) {
+        let mut platforms: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        platforms.sort();
+        zsh_dirs.extend(platforms);
+    }
+
+    fish_dirs
+        .iter()
+        .map(
+            |dir| {
+                dir.join(
+                    format!(
+                        "{}.fish",
+                        program
+                    ),
+                )
+            },
+        )
+        .chain(
+            zsh_dirs
+                .iter()
+                .map(
+                    |dir| {
+                        dir.join(
+                            format!(
+                                "_{}",
+                                program
+                            ),
+                        )
+                    },
+                ),
+        )
+        .collect()
+}
+
+/// The first installed completion file for `program`
+pub fn find_completion(program: &str) -> Option<PathBuf> {
+    candidates(program)
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+}
+
+/// Import the completion definitions in `path`
+///
+/// The program is the one the file names, `git.fish` or `#compdef git`.
+pub fn import_file(path: &Path) -> color_eyre::Result<V0_1> {
+    let text = fs::read_to_string(path).wrap_err_with(
+        || {
+            format!(
+                "Failed to read completion file '{}'",
+                path.display()
+            )
+        },
+    )?;
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    match Syntax::detect(
+        path, &text,
+    ) {
+        Some(Syntax::Fish) => Ok(
+            fish::parse_fish(
+                name.trim_end_matches(".fish"),
+                &text,
+            ),
+        ),
+        Some(Syntax::Zsh) => {
+            let program = zsh::compdef_program(&text).unwrap_or(name.trim_start_matches('_'));
+            Ok(
+                zsh::parse_zsh(
+                    program, &text,
+                ),
+            )
+        }
+        None => bail!(
+            "'{}' is neither a fish completion (*.fish) nor a zsh function (_name)",
+            path.display()
+        ),
+    }
+}
+
+/// Import the completion installed for `program`
+pub fn import_installed(program: &str) -> color_eyre::Result<V0_1> {
+    let path = find_completion(program).ok_or_else(
+        || {
+            eyre!(
+                "No fish or zsh completion found for '{}'",
+                program
+            )
+        },
+    )?;
+    import_file(&path)
+}
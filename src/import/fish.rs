@@ -0,0 +1,678 @@
+//! Build an OpenCLI spec from a fish completion file
+//!
+//! Every `complete -c PROGRAM` line declares an option, or with `-a` the words an argument takes.
+//! Fish runs the `-n` condition to decide where a line applies, the importer reads the common
+//! helpers instead: `__fish_seen_subcommand_from X` and `__fish_*_using_command X` put a line on
+//! subcommand X, and words offered until a subcommand is seen are the subcommands.
+
+use crate::import::{
+    argument, command_mut, conventions, drop_inherited, glob_metadatum, merge_option, non_empty,
+    option, spec, value_name,
+};
+use crate::opencli::v0_1::{ArgumentElement, CommandElement, OptionElement, V0_1};
+use crate::shell::split_words;
+use log::warn;
+use std::path::Path;
+
+/// Flags of `complete` that take a value
+const VALUE_FLAGS: &str = "cpsloadnwC";
+
+/// Stands in for the tab of a `value\tdescription` word while it is split
+const TAB: char = '\u{1f}';
+
+/// One `complete` line
+#[derive(Debug, Default)]
+struct Complete {
+    command: Option<String>,
+    short: Vec<String>,
+    long: Vec<String>,
+    old: Vec<String>,
+    arguments: Vec<String>,
+    description: Option<String>,
+    condition: Option<String>,
+    require_parameter: bool,
+    no_files: bool,
+    erase: bool,
+}
+
+impl Complete {
+    fn set(&mut self, flag: char, value: String) {
+        match flag {
+            'c' => self.command = Some(value),
+            'p' => {
+                self.command = Path::new(&value)
+                    .file_name()
+                    .map(
+                        |name| {
+                            name.to_string_lossy()
+                                .into_owned()
+                        },
+                    )
+            }
+            's' => self
+                .short
+                .push(
+                    format!(
+                        "-{}",
+                        value
+                    ),
+                ),
+            'l' => self
+                .long
+                .push(
+                    format!(
+                        "--{}",
+                        value
+                    ),
+                ),
+            'o' => self
+                .old
+                .push(
+                    format!(
+                        "-{}",
+                        value
+                    ),
+                ),
+            'a' => self
+                .arguments
+                .push(value),
+            'd' => self.description = Some(value),
+            'n' => self.condition = Some(value),
+            _ => {}
+        }
+    }
+
+    fn flag(&mut self, flag: char) {
+        match flag {
+            'r' => self.require_parameter = true,
+            'f' => self.no_files = true,
+            'x' => {
+                self.require_parameter = true;
+                self.no_files = true;
+            }
+            'F' => self.no_files = false,
+            'e' => self.erase = true,
+            _ => {}
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.long
+            .iter()
+            .chain(&self.short)
+            .chain(&self.old)
+            .cloned()
+            .collect()
+    }
+
+    /// The literal words `-a` offers with their `\t` descriptions, command substitutions and
+    /// variables are left out
+    fn candidates(
+        &self,
+    ) -> Vec<(
+        String,
+        Option<String>,
+    )> {
+        self.arguments
+            .iter()
+            .flat_map(
+                |arguments| {
+                    split_words(
+                        &arguments.replace(
+                            "\\t",
+                            &TAB.to_string(),
+                        ),
+                    )
+                    .unwrap_or_default()
+                },
+            )
+            .filter(
+                |word| {
+                    !word.contains(
+                        [
+                            '(', '$',
+                        ],
+                    )
+                },
+            )
+            .map(
+                |word| match word.split_once(TAB) {
+                    Some((value, description)) => (
+                        value.to_owned(),
+                        Some(description.to_owned()),
+                    ),
+                    None => (
+                        word, None,
+                    ),
+                },
+            )
+            .collect()
+    }
+
+    /// The option this line declares, `None` when it only offers arguments
+    fn option(&self) -> Option<OptionElement> {
+        let names = self.names();
+        let (name, aliases) = names.split_first()?;
+        let mut option = option(name);
+        option.aliases = non_empty(aliases.to_vec());
+        option.description = self
+            .description
+            .clone();
+
+        if self.require_parameter
+            || !self
+                .arguments
+                .is_empty()
+        {
+            let mut value = argument(&value_name(name));
+            value.required = true;
+            value.accepted_values = non_empty(
+                self.candidates()
+                    .into_iter()
+                    .map(|(value, _)| value)
+                    .collect(),
+            );
+            if value
+                .accepted_values
+                .is_none()
+            {
+                value.metadata = self
+                    .path_glob()
+                    .map(|glob| vec![glob_metadatum(glob)]);
+            }
+            option.arguments = Some(vec![value]);
+        }
+        Some(option)
+    }
+
+    /// Paths completed for the value, which fish offers unless `-f` is given
+    fn path_glob(&self) -> Option<&'static str> {
+        if self
+            .arguments
+            .iter()
+            .any(|arguments| arguments.contains("__fish_complete_directories"))
+        {
+            Some("*/")
+        } else if !self.no_files
+            || self
+                .arguments
+                .iter()
+                .any(|arguments| arguments.contains("__fish_complete_path"))
+        {
+            Some("*")
+        } else {
+            None
+        }
+    }
+}
+
+/// Read the flags of a `complete` command, getopt style so `-xa VALUES` works
+fn parse_complete(words: &[String]) -> Option<Complete> {
+    let mut complete = Complete::default();
+    let mut words = words.iter();
+
+    while let Some(word) = words.next() {
+        if let Some(long) = word.strip_prefix("--") {
+            let (flag, inline) = match long.split_once('=') {
+                Some((flag, value)) => (
+                    flag,
+                    Some(value.to_owned()),
+                ),
+                None => (
+                    long, None,
+                ),
+            };
+            let flag = match flag {
+                "command" => 'c',
+                "path" => 'p',
+                "short-option" => 's',
+                "long-option" => 'l',
+                "old-option" => 'o',
+                "arguments" => 'a',
+                "description" => 'd',
+                "condition" => 'n',
+                "wraps" => 'w',
+                "do-complete" => 'C',
+                "require-parameter" => 'r',
+                "no-files" => 'f',
+                "force-files" => 'F',
+                "exclusive" => 'x',
+                "erase" => 'e',
+                _ => continue,
+            };
+            match VALUE_FLAGS.contains(flag) {
+                true => {
+                    let value = match inline {
+                        Some(value) => value,
+                        None => words
+                            .next()?
+                            .clone(),
+                    };
+                    complete.set(
+                        flag, value,
+                    );
+                }
+                false => complete.flag(flag),
+            }
+        } else if let Some(flags) = word.strip_prefix('-') {
+            for (i, flag) in flags.char_indices() {
+                if VALUE_FLAGS.contains(flag) {
+                    let rest = &flags[i + flag.len_utf8()..];
+                    let value = match rest.is_empty() {
+                        true => words
+                            .next()?
+                            .clone(),
+                        false => rest.to_owned(),
+                    };
+                    complete.set(
+                        flag, value,
+                    );
+                    break;
+                }
+                complete.flag(flag);
+            }
+        } else if complete
+            .command
+            .is_none()
+        {
+            // `complete git -l all` names the command without `-c`
+            complete.command = Some(word.clone());
+        }
+    }
+
+    Some(complete)
+}
+
+/// Where a line applies according to its condition
+#[derive(Debug, PartialEq)]
+enum Scope {
+    /// Everywhere, no condition or none the importer understands
+    Anywhere,
+    /// Only until a subcommand is given
+    BeforeSubcommand,
+    /// After one of these subcommands
+    Subcommands(Vec<String>),
+}
+
+fn scope(condition: Option<&str>) -> Scope {
+    let Some(condition) = condition else {
+        return Scope::Anywhere;
+    };
+    let words = split_words(
+        &condition.replace(
+            ';', " ; ",
+        ),
+    )
+    .unwrap_or_default();
+    let separator = |word: &str| {
+        [
+            ";", "and", "or", "&&", "||",
+        ]
+        .contains(&word)
+    };
+
+    let mut subcommands = vec![];
+    let mut before = false;
+    let mut negated = false;
+    let mut words = words
+        .iter()
+        .peekable();
+    while let Some(word) = words.next() {
+        if word == "not" || word == "!" {
+            negated = true;
+            continue;
+        }
+
+        if word.ends_with("use_subcommand") || word.ends_with("needs_command") {
+            before = true;
+        } else if word.ends_with("seen_subcommand_from")
+            || word.ends_with("using_command")
+            || word.ends_with("using_subcommand")
+        {
+            let mut names = vec![];
+            while let Some(name) = words.next_if(|word| !separator(word)) {
+                if !name.starts_with(
+                    [
+                        '$', '-', '(',
+                    ],
+                ) {
+                    names.push(name.clone());
+                }
+            }
+            match negated {
+                true => before = true,
+                false => subcommands.extend(names),
+            }
+        }
+        negated = false;
+    }
+
+    if !subcommands.is_empty() {
+        Scope::Subcommands(subcommands)
+    } else if before {
+        Scope::BeforeSubcommand
+    } else {
+        Scope::Anywhere
+    }
+}
+
+/// The `complete` commands of a file, with `\` continuations joined
+fn complete_lines(text: &str) -> Vec<Vec<String>> {
+    let mut lines = vec![];
+    let mut line = String::new();
+
+    for physical in text.lines() {
+        match physical.strip_suffix('\\') {
+            Some(continued) => {
+                line.push_str(continued);
+                continue;
+            }
+            None => line.push_str(physical),
+        }
+
+        let logical = std::mem::take(&mut line);
+        let trimmed = logical.trim_start();
+        if !trimmed.starts_with("complete ") {
+            continue;
+        }
+        match split_words(trimmed) {
+            Ok(words) => lines.push(words),
+            Err(e) => warn!(
+                "Skipping completion line: {}",
+                e
+            ),
+        }
+    }
+
+    lines
+}
+
+/// The argument taking the words `-a` offers outside of an option
+fn offered_argument(arguments: &mut Option<Vec<ArgumentElement>>, values: Vec<String>) {
+    let arguments = arguments.get_or_insert_with(|| vec![argument("VALUE")]);
+    arguments[0]
+        .accepted_values
+        .get_or_insert_with(Vec::new)
+        .extend(values);
+}
+
+/// Build the spec for `program` from the `complete` lines of a fish completion file
+pub fn parse_fish(program: &str, text: &str) -> V0_1 {
+    let mut options: Vec<OptionElement> = vec![];
+    let mut arguments: Option<Vec<ArgumentElement>> = None;
+    let mut commands: Vec<CommandElement> = vec![];
+    let mut everywhere: Vec<String> = vec![];
+
+    for words in complete_lines(text) {
+        let Some(complete) = parse_complete(&words[1..]) else {
+            continue;
+        };
+        if complete.erase
+            || complete
+                .command
+                .as_deref()
+                != Some(program)
+        {
+            continue;
+        }
+
+        let scope = scope(
+            complete
+                .condition
+                .as_deref(),
+        );
+        match (
+            complete.option(),
+            scope,
+        ) {
+            (Some(option), Scope::Subcommands(names)) => {
+                for name in names {
+                    merge_option(
+                        command_mut(
+                            &mut commands,
+                            &name,
+                        )
+                        .options
+                        .get_or_insert_with(Vec::new),
+                        option.clone(),
+                    );
+                }
+            }
+            (Some(option), scope) => {
+                if scope == Scope::Anywhere {
+                    everywhere.push(
+                        option
+                            .name
+                            .clone(),
+                    );
+                }
+                merge_option(
+                    &mut options,
+                    option,
+                );
+            }
+            (None, Scope::BeforeSubcommand) => {
+                for (name, description) in complete.candidates() {
+                    let command = command_mut(
+                        &mut commands,
+                        &name,
+                    );
+                    command.description = description.or_else(
+                        || {
+                            complete
+                                .description
+                                .clone()
+                        },
+                    );
+                }
+            }
+            (None, Scope::Subcommands(names)) => {
+                let values: Vec<String> = complete
+                    .candidates()
+                    .into_iter()
+                    .map(|(value, _)| value)
+                    .collect();
+                if values.is_empty() {
+                    continue;
+                }
+                for name in names {
+                    offered_argument(
+                        &mut command_mut(
+                            &mut commands,
+                            &name,
+                        )
+                        .arguments,
+                        values.clone(),
+                    );
+                }
+            }
+            (None, Scope::Anywhere) => {
+                let values: Vec<String> = complete
+                    .candidates()
+                    .into_iter()
+                    .map(|(value, _)| value)
+                    .collect();
+                if !values.is_empty() {
+                    offered_argument(
+                        &mut arguments,
+                        values,
+                    );
+                }
+            }
+        }
+    }
+
+    // Lines without a condition apply after any subcommand too
+    if !commands.is_empty() {
+        options
+            .iter_mut()
+            .filter(|option| everywhere.contains(&option.name))
+            .for_each(|option| option.recursive = true);
+    }
+
+    drop_inherited(
+        &mut options,
+        &mut commands,
+    );
+
+    let mut spec = spec(
+        program, "unknown",
+    );
+    spec.conventions = conventions(&options);
+    spec.options = non_empty(options);
+    spec.arguments = arguments;
+    spec.commands = non_empty(commands);
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opencli::operations::find_option;
+
+    fn sample() -> V0_1 {
+        parse_fish(
+            "backup",
+            include_str!("fixtures/fish-backup.fish"),
+        )
+    }
+
+    #[test]
+    fn test_options_and_values() {
+        let spec = sample();
+
+        let verbose = find_option(
+            &spec.options,
+            "-v",
+        )
+        .expect("short alias");
+        assert_eq!(
+            verbose.name,
+            "--verbose"
+        );
+        assert!(
+            verbose
+                .arguments
+                .is_none()
+        );
+
+        let mode = find_option(
+            &spec.options,
+            "--mode",
+        )
+        .unwrap();
+        assert_eq!(
+            mode.arguments
+                .as_ref()
+                .unwrap()[0]
+                .accepted_values,
+            Some(
+                vec![
+                    "full".to_string(),
+                    "incremental".to_string()
+                ]
+            )
+        );
+        assert!(
+            find_option(
+                &spec.options,
+                "--help"
+            )
+            .is_some_and(|help| help.recursive)
+        );
+    }
+
+    #[test]
+    fn test_conditions_place_lines_on_subcommands() {
+        let spec = sample();
+        let commands = spec
+            .commands
+            .unwrap();
+
+        assert_eq!(
+            commands
+                .iter()
+                .map(
+                    |command| (
+                        command
+                            .name
+                            .as_str(),
+                        command
+                            .description
+                            .as_deref()
+                    )
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "restore",
+                    Some("restore files from a backup")
+                ),
+                (
+                    "list",
+                    Some("list backups")
+                )
+            ]
+        );
+        assert!(
+            find_option(
+                &commands[0].options,
+                "--force"
+            )
+            .is_some()
+        );
+
+        // `value\tdescription` words keep only the value
+        let format = find_option(
+            &commands[1].options,
+            "--format",
+        )
+        .unwrap();
+        assert_eq!(
+            format
+                .arguments
+                .as_ref()
+                .unwrap()[0]
+                .accepted_values,
+            Some(
+                vec![
+                    "table".to_string(),
+                    "json".to_string()
+                ]
+            )
+        );
+        assert!(
+            find_option(
+                &commands[1].options,
+                "-since"
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn test_getopt_style_flags() {
+        let words: Vec<String> = split_words("-c tool -rfs o -l output -d'the file'").unwrap();
+        let complete = parse_complete(&words).unwrap();
+
+        assert_eq!(
+            complete
+                .command
+                .as_deref(),
+            Some("tool")
+        );
+        assert_eq!(
+            complete.names(),
+            vec![
+                "--output", "-o"
+            ]
+        );
+        assert!(complete.require_parameter && complete.no_files);
+        assert_eq!(
+            complete
+                .description
+                .as_deref(),
+            Some("the file")
+        );
+    }
+}
@@ -0,0 +1,19 @@
+# fish completion for backup
+
+set -l commands restore list
+
+complete -c backup -f
+complete -c backup -s h -l help -d 'show this help message and exit'
+
+complete -c backup -n "not __fish_seen_subcommand_from $commands" -s v -l verbose -d 'print every file copied'
+complete -c backup -n "not __fish_seen_subcommand_from $commands" -l mode -x -a 'full incremental' -d 'what to copy'
+complete -c backup -n "not __fish_seen_subcommand_from $commands" -s o -l output -r \
+    -a '(__fish_complete_directories)' -d 'where backups are written'
+
+complete -c backup -n "not __fish_seen_subcommand_from $commands" -a restore -d 'restore files from a backup'
+complete -c backup -n "not __fish_seen_subcommand_from $commands" -a list -d 'list backups'
+
+complete -c backup -n '__fish_seen_subcommand_from restore' -l force -d 'overwrite existing files'
+complete -c backup -n '__fish_seen_subcommand_from restore' -rF -l from -d 'the backup to restore'
+complete -c backup -n '__fish_seen_subcommand_from list' -l format -xa "table\t'aligned columns' json" -d 'output format'
+complete -c backup -n '__fish_seen_subcommand_from list' -o since -r -d 'only backups after DATE'
@@ -0,0 +1,1418 @@
+//! Build an OpenCLI spec from a program's `--help` output
+//!
+//! Help layouts differ, the parser relies on what clap, cobra, argparse and GNU getopt programs
+//! share: headings ending in `:`, and indented entries whose term is followed by its description,
+//! two spaces away or on the lines below. Options are recognised by their leading `-` anywhere,
+//! as GNU tools list them without a heading. Arguments missing from the page are taken from the
+//! usage line.
+
+use crate::import::{
+    argument, arity, command, conventions, default_metadatum, drop_inherited, non_empty, option,
+    spec, value_name,
+};
+use crate::opencli::v0_1::{ArgumentElement, Arity, CommandElement, OptionElement, V0_1};
+use crate::util::process::output_with_timeout;
+use color_eyre::eyre::bail;
+use log::warn;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// How long a `--help` or `--version` run may take
+const HELP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What one help page describes
+#[derive(Debug, Default, PartialEq)]
+pub struct HelpPage {
+    pub description: Option<String>,
+    pub options: Vec<OptionElement>,
+    pub arguments: Vec<ArgumentElement>,
+    pub commands: Vec<CommandElement>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    /// Text before the first heading
+    Preamble,
+    Usage,
+    Options,
+    Arguments,
+    Commands,
+    /// Examples, environment and the like
+    Other,
+}
+
+impl Section {
+    /// The section `line` starts, with text following an inline `Usage:`
+    fn from_heading(
+        line: &str,
+    ) -> Option<(
+        Section,
+        &str,
+    )> {
+        if line.starts_with(char::is_whitespace) || line.starts_with('-') {
+            return None;
+        }
+        let line = line.trim_end();
+        if line
+            .to_lowercase()
+            .starts_with("usage:")
+        {
+            return Some(
+                (
+                    Section::Usage,
+                    line["usage:".len()..].trim(),
+                ),
+            );
+        }
+
+        let heading = line
+            .strip_suffix(':')?
+            .to_lowercase();
+        if heading.len() > 40 || heading.contains("  ") {
+            return None;
+        }
+        let section = if heading.contains("command") {
+            Section::Commands
+        } else if heading.contains("option") || heading.contains("flag") {
+            Section::Options
+        } else if heading.contains("argument") || heading.contains("positional") {
+            Section::Arguments
+        } else if heading == "usage" {
+            Section::Usage
+        } else {
+            Section::Other
+        };
+        Some(
+            (
+                section, "",
+            ),
+        )
+    }
+}
+
+/// An indented term with its description
+#[derive(Debug)]
+struct Entry {
+    section: Section,
+    indent: usize,
+    term: String,
+    description: Vec<String>,
+    /// Column the description started at when it shares the term's line
+    description_col: Option<usize>,
+}
+
+impl Entry {
+    /// The names an argparse `{a,b}` term offers, which are listed as entries below it
+    fn choices(&self) -> Vec<&str> {
+        self.term
+            .strip_prefix('{')
+            .and_then(|term| term.strip_suffix('}'))
+            .map(
+                |choices| {
+                    choices
+                        .split(',')
+                        .collect()
+                },
+            )
+            .unwrap_or_default()
+    }
+
+    /// Whether `content` at `indent` starts another entry instead of continuing this one
+    fn ends_at(&self, content: &str, indent: usize) -> bool {
+        let word = content
+            .split_whitespace()
+            .next()
+            .unwrap_or_default();
+
+        indent <= self.indent
+            || (content.starts_with('-')
+                && self
+                    .description_col
+                    .is_none_or(|col| indent < col))
+            || self
+                .choices()
+                .contains(&word)
+    }
+
+    fn description(&self) -> Option<String> {
+        let description = self
+            .description
+            .join(" ");
+        (!description.is_empty()).then_some(description)
+    }
+}
+
+/// A help page cut into description, usage lines and entries
+#[derive(Debug, Default)]
+struct Layout {
+    description: Vec<String>,
+    usage: Vec<String>,
+    entries: Vec<Entry>,
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace(
+        '\t', "        ",
+    )
+}
+
+fn layout(text: &str) -> Layout {
+    let mut layout = Layout::default();
+    let mut section = Section::Preamble;
+    let mut description_done = false;
+    // Indented lines right after a usage line wrap it
+    let mut in_usage = false;
+    let mut open_entry = false;
+
+    for line in text
+        .lines()
+        .map(expand_tabs)
+    {
+        let content = line.trim();
+        let indent = line.len()
+            - line
+                .trim_start()
+                .len();
+        if content.is_empty() {
+            description_done |= !layout
+                .description
+                .is_empty();
+            in_usage = false;
+            continue;
+        }
+
+        if let Some((heading, usage)) = Section::from_heading(&line) {
+            section = heading;
+            in_usage = heading == Section::Usage;
+            open_entry = false;
+            if !usage.is_empty() {
+                layout
+                    .usage
+                    .push(usage.to_owned());
+            }
+            continue;
+        }
+
+        if indent == 0 {
+            in_usage = false;
+            open_entry = false;
+            if matches!(
+                section,
+                Section::Preamble | Section::Usage
+            ) && !description_done
+            {
+                layout
+                    .description
+                    .push(content.to_owned());
+            }
+            continue;
+        }
+
+        if in_usage {
+            layout
+                .usage
+                .push(content.to_owned());
+            continue;
+        }
+
+        if let Some(entry) = layout
+            .entries
+            .last_mut()
+            .filter(|_| open_entry)
+            && !entry.ends_at(
+                content, indent,
+            )
+        {
+            entry
+                .description
+                .push(content.to_owned());
+            continue;
+        }
+
+        let lists_entries = matches!(
+            section,
+            Section::Options | Section::Arguments | Section::Commands
+        );
+        if !lists_entries && !content.starts_with('-') {
+            open_entry = false;
+            continue;
+        }
+
+        let (term, description) = match content.find("  ") {
+            Some(gap) => (
+                &content[..gap],
+                Some(
+                    (
+                        content[gap..].trim_start(),
+                        indent + content.len()
+                            - content[gap..]
+                                .trim_start()
+                                .len(),
+                    ),
+                ),
+            ),
+            None => (
+                content, None,
+            ),
+        };
+        layout
+            .entries
+            .push(
+                Entry {
+                    section,
+                    indent,
+                    term: term.to_owned(),
+                    description: description
+                        .iter()
+                        .map(|(text, _)| text.to_string())
+                        .collect(),
+                    description_col: description.map(|(_, col)| col),
+                },
+            );
+        open_entry = true;
+    }
+
+    layout
+}
+
+/// The text between `start` and the next `end`, like the `x` of `[default: x]`
+fn enclosed<'a>(text: &'a str, start: &str, end: char) -> Option<&'a str> {
+    let from = text.find(start)? + start.len();
+    let to = from + text[from..].find(end)?;
+    Some(text[from..to].trim())
+}
+
+/// A value the description documents as the default
+fn described_default(description: &str) -> Option<String> {
+    enclosed(
+        description,
+        "[default: ",
+        ']',
+    )
+    .or_else(
+        || {
+            enclosed(
+                description,
+                "(default: ",
+                ')',
+            )
+        },
+    )
+    .or_else(
+        || {
+            enclosed(
+                description,
+                "(default ",
+                ')',
+            )
+        },
+    )
+    .map(|value| value.trim_matches('"'))
+    .filter(|value| !value.is_empty())
+    .map(String::from)
+}
+
+/// Values listed by clap as `[possible values: a, b]`
+fn described_values(description: &str) -> Option<Vec<String>> {
+    enclosed(
+        description,
+        "[possible values: ",
+        ']',
+    )
+    .map(
+        |values| {
+            values
+                .split(", ")
+                .map(String::from)
+                .collect()
+        },
+    )
+}
+
+/// `FILE`, `<file>`, `[FILE]` or `FILE...` as name, optional and variadic
+fn placeholder(
+    word: &str,
+) -> Option<(
+    String,
+    bool,
+    bool,
+)> {
+    let mut word = word;
+    let mut variadic = false;
+    if let Some(stripped) = word.strip_suffix("...") {
+        word = stripped;
+        variadic = true;
+    }
+    let optional = word.starts_with('[');
+    if optional {
+        word = word
+            .strip_prefix('[')?
+            .strip_suffix(']')?;
+    }
+    if let Some(stripped) = word.strip_suffix("...") {
+        word = stripped;
+        variadic = true;
+    }
+    word = word
+        .strip_prefix('<')
+        .and_then(|word| word.strip_suffix('>'))
+        .unwrap_or(word);
+
+    (!word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c)))
+    .then(
+        || {
+            (
+                word.to_owned(),
+                optional,
+                variadic,
+            )
+        },
+    )
+}
+
+/// The argument an option's value placeholder describes
+fn option_value(option: &str, value: &str, description: &str) -> ArgumentElement {
+    let (value, optional) = match value
+        .strip_prefix("[=")
+        .or_else(|| value.strip_prefix('['))
+    {
+        Some(value) => (
+            value.trim_end_matches(']'),
+            true,
+        ),
+        None => (
+            value, false,
+        ),
+    };
+    let (value, variadic) = match value.strip_suffix("...") {
+        Some(value) => (
+            value, true,
+        ),
+        None => (
+            value, false,
+        ),
+    };
+    let value = value
+        .trim_start_matches('<')
+        .trim_end_matches('>');
+
+    let choices = value
+        .strip_prefix('{')
+        .and_then(|value| value.strip_suffix('}'));
+    // cobra names the type, like `string` or `duration`, rather than the value
+    let name = if choices.is_some()
+        || value.starts_with(|c: char| c.is_ascii_lowercase())
+        || value.is_empty()
+    {
+        value_name(option)
+    } else {
+        value.to_owned()
+    };
+
+    let mut argument = argument(&name);
+    argument.required = !optional;
+    argument.arity = match optional {
+        true => Some(
+            Arity {
+                minimum: Some(0),
+                maximum: Some(1),
+            },
+        ),
+        false => arity(
+            false, variadic,
+        ),
+    };
+    argument.accepted_values = choices
+        .map(
+            |choices| {
+                choices
+                    .split(',')
+                    .map(String::from)
+                    .collect()
+            },
+        )
+        .or_else(|| described_values(description));
+    argument.metadata = described_default(description)
+        .as_deref()
+        .map(|value| vec![default_metadatum(value)]);
+    argument
+}
+
+/// An option from a term like `-o, --output <FILE>`, `--color[=WHEN]` or `-A, --all=false:`
+pub(crate) fn parse_option(term: &str, description: Option<String>) -> Option<OptionElement> {
+    // kubectl puts the default after `=` and ends the term with `:`
+    let defaults_inline = term.ends_with(':');
+    let term = term.trim_end_matches(':');
+
+    let mut flags: Vec<String> = vec![];
+    let mut value: Option<String> = None;
+    for part in term.split(", ") {
+        let mut words = part.split_whitespace();
+        let Some(first) = words
+            .next()
+            .filter(|word| word.starts_with('-'))
+        else {
+            continue;
+        };
+
+        let (flag, attached) = match first
+            .find("[=")
+            .or_else(|| first.find('='))
+        {
+            Some(i) if first[i..].starts_with('[') => (
+                &first[..i],
+                Some(first[i..].to_owned()),
+            ),
+            Some(i) => (
+                &first[..i],
+                Some(first[i + 1..].to_owned()),
+            ),
+            None => (
+                first, None,
+            ),
+        };
+        flags.push(
+            flag.trim_end_matches("...")
+                .to_owned(),
+        );
+
+        let rest = words
+            .collect::<Vec<_>>()
+            .join(" ");
+        value = value
+            .or(attached)
+            .or((!rest.is_empty()).then_some(rest));
+    }
+
+    let name = flags
+        .iter()
+        .find(|flag| flag.starts_with("--"))
+        .or(flags.first())?
+        .clone();
+    let mut option = option(&name);
+    option.aliases = non_empty(
+        flags
+            .into_iter()
+            .filter(|flag| *flag != name)
+            .collect(),
+    );
+
+    let description_text = description
+        .clone()
+        .unwrap_or_default();
+    option.arguments = match (
+        value,
+        defaults_inline,
+    ) {
+        (None, _) => None,
+        (Some(default), true) => {
+            let default = default.trim_matches('\'');
+            if default == "true" || default == "false" {
+                None
+            } else {
+                let mut argument = argument(&value_name(&name));
+                argument.required = true;
+                argument.metadata = (!default.is_empty() && default != "[]")
+                    .then(|| vec![default_metadatum(default)]);
+                Some(vec![argument])
+            }
+        }
+        (Some(value), false) => Some(
+            vec![
+                option_value(
+                    &name,
+                    &value,
+                    &description_text,
+                ),
+            ],
+        ),
+    };
+    option.description = description;
+
+    Some(option)
+}
+
+/// A subcommand from a term like `build, b`
+fn parse_command(term: &str, description: Option<String>) -> Option<CommandElement> {
+    let mut names = term
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty());
+    let name = names.next()?;
+    // Also skips placeholder rows like cargo's `...  See all commands with --list`
+    if name == "help"
+        || !name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-:.".contains(c))
+    {
+        return None;
+    }
+
+    let mut command = command(name);
+    command.aliases = non_empty(
+        names
+            .map(String::from)
+            .collect(),
+    );
+    command.description = description;
+    Some(command)
+}
+
+/// A positional argument from a term like `<FILE>...` or `[PATH]`
+fn parse_argument(term: &str, description: Option<String>) -> Option<ArgumentElement> {
+    let (name, optional, variadic) = placeholder(
+        term.split_whitespace()
+            .next()?,
+    )?;
+
+    let mut argument = argument(&name);
+    argument.required = !optional;
+    argument.arity = arity(
+        optional, variadic,
+    );
+    argument.accepted_values = description
+        .as_deref()
+        .and_then(described_values);
+    argument.description = description;
+    Some(argument)
+}
+
+/// Arguments named in the usage, `<NAME>` or upper case, when the page has no section for them
+pub(crate) fn usage_arguments(usage: &[&str]) -> Vec<ArgumentElement> {
+    usage
+        .iter()
+        .filter_map(
+            |word| {
+                placeholder(word).map(
+                    |placeholder| {
+                        (
+                            word,
+                            placeholder,
+                        )
+                    },
+                )
+            },
+        )
+        .filter(
+            |(word, (name, _, _))| {
+                let upper = name.to_uppercase();
+                // Flags like `-P` in `[-p | --paginate | -P]` are upper case too
+                !word
+                    .trim_start_matches(
+                        [
+                            '[', '(', '|',
+                        ],
+                    )
+                    .starts_with('-')
+                    && (word.contains('<') || *name == upper)
+                    && name
+                        .chars()
+                        .any(|c| c.is_ascii_alphabetic())
+                    && ![
+                        "OPTION", "FLAG", "COMMAND",
+                    ]
+                    .iter()
+                    .any(|skip| upper.contains(skip))
+            },
+        )
+        .map(
+            |(_, (name, optional, variadic))| {
+                let mut argument = argument(&name);
+                argument.required = !optional;
+                argument.arity = arity(
+                    optional, variadic,
+                );
+                argument
+            },
+        )
+        .collect()
+}
+
+/// Relax what the arguments section can't say, argparse only shows `[paths ...]` in the usage
+fn refine_from_usage(argument: &mut ArgumentElement, usage: &[&str]) {
+    let Some(i) = usage
+        .iter()
+        .position(
+            |word| {
+                placeholder(
+                    word.trim_start_matches('[')
+                        .trim_end_matches(']'),
+                )
+                .is_some_and(|(name, _, _)| name == argument.name)
+            },
+        )
+    else {
+        return;
+    };
+
+    // Also optional when the bracket opened on an earlier word, like `[-- <ARGS>...]`
+    let word = usage[i];
+    let optional = word.contains(
+        [
+            '[', ']',
+        ],
+    );
+    let variadic = word
+        .trim_end_matches(']')
+        .ends_with("...")
+        || usage
+            .get(i + 1)
+            .is_some_and(|next| next.trim_end_matches(']') == "...");
+
+    argument.required &= !optional;
+    if variadic {
+        argument.arity = arity(
+            optional, true,
+        );
+    }
+}
+
+/// Parse one help page
+pub fn parse_help(text: &str) -> HelpPage {
+    let layout = layout(text);
+    let mut page = HelpPage {
+        description: non_empty(
+            layout
+                .description
+                .clone(),
+        )
+        .map(|lines| lines.join(" ")),
+        ..HelpPage::default()
+    };
+
+    // Subcommands argparse lists as `{a,b}` among the positional arguments
+    let mut choices: Vec<String> = vec![];
+    for entry in &layout.entries {
+        let description = entry.description();
+        if entry
+            .term
+            .starts_with('-')
+        {
+            if let Some(option) = parse_option(
+                &entry.term,
+                description,
+            ) && !page
+                .options
+                .iter()
+                .any(|known| known.name == option.name)
+            {
+                page.options
+                    .push(option);
+            }
+        } else if entry.section == Section::Commands || choices.contains(&entry.term) {
+            page.commands
+                .extend(
+                    parse_command(
+                        &entry.term,
+                        description,
+                    ),
+                );
+        } else if entry.section == Section::Arguments {
+            match entry.choices() {
+                found if !found.is_empty() => choices.extend(
+                    found
+                        .into_iter()
+                        .map(String::from),
+                ),
+                _ => page
+                    .arguments
+                    .extend(
+                        parse_argument(
+                            &entry.term,
+                            description,
+                        ),
+                    ),
+            }
+        }
+    }
+
+    // Lines starting with the program again are alternatives, only the first one is used
+    let program = layout
+        .usage
+        .first()
+        .and_then(
+            |line| {
+                line.split_whitespace()
+                    .next()
+            },
+        );
+    let usage: Vec<&str> = layout
+        .usage
+        .iter()
+        .enumerate()
+        .take_while(
+            |(i, line)| {
+                *i == 0
+                    || line
+                        .split_whitespace()
+                        .next()
+                        != program
+            },
+        )
+        .flat_map(|(_, line)| line.split_whitespace())
+        .collect();
+    if page
+        .arguments
+        .is_empty()
+        && choices.is_empty()
+    {
+        page.arguments = usage_arguments(&usage);
+    } else {
+        for argument in &mut page.arguments {
+            refine_from_usage(
+                argument, &usage,
+            );
+        }
+    }
+
+    page
+}
+
+/// Run `program path.. --help`, the page is on stdout or, for some parsers, stderr
+fn run_help(program: &str, path: &[String]) -> color_eyre::Result<String> {
+    let mut command = Command::new(program);
+    command
+        .args(path)
+        .arg("--help");
+    let output = output_with_timeout(
+        command,
+        HELP_TIMEOUT,
+    )?;
+
+    let text = match output
+        .stdout
+        .is_empty()
+    {
+        true => output.stderr,
+        false => output.stdout,
+    };
+    if text.is_empty() {
+        bail!(
+            "`{} {} --help` printed nothing",
+            program,
+            path.join(" ")
+        );
+    }
+    Ok(String::from_utf8_lossy(&text).into_owned())
+}
+
+/// The first word of `program --version` that looks like a version number
+fn program_version(program: &str) -> Option<String> {
+    let mut command = Command::new(program);
+    command.arg("--version");
+    let output = output_with_timeout(
+        command,
+        HELP_TIMEOUT,
+    )
+    .ok()?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_ascii_alphanumeric()))
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+        .map(String::from)
+}
+
+/// Fill in `commands` from their own help pages, `depth` levels down
+fn describe_commands(
+    commands: &mut [CommandElement],
+    path: &[String],
+    depth: usize,
+    help: &dyn Fn(&[String]) -> color_eyre::Result<String>,
+) {
+    if depth == 0 {
+        return;
+    }
+
+    for command in commands {
+        let mut path = path.to_vec();
+        path.push(
+            command
+                .name
+                .clone(),
+        );
+        let page = match help(&path) {
+            Ok(text) => parse_help(&text),
+            Err(e) => {
+                warn!(
+                    "Skipping help of {}: {}",
+                    path.join(" "),
+                    e
+                );
+                continue;
+            }
+        };
+
+        let mut subcommands = page.commands;
+        describe_commands(
+            &mut subcommands,
+            &path,
+            depth - 1,
+            help,
+        );
+        command.description = command
+            .description
+            .take()
+            .or(page.description);
+        command.options = non_empty(page.options);
+        command.arguments = non_empty(page.arguments);
+        command.commands = non_empty(subcommands);
+    }
+}
+
+/// Build the spec for `title` from help pages `help` returns for a subcommand path
+fn import_with(
+    title: &str,
+    version: &str,
+    depth: usize,
+    help: &dyn Fn(&[String]) -> color_eyre::Result<String>,
+) -> color_eyre::Result<V0_1> {
+    let page = parse_help(&help(&[])?);
+    let mut commands = page.commands;
+    let mut options = page.options;
+    describe_commands(
+        &mut commands,
+        &[],
+        depth,
+        help,
+    );
+    drop_inherited(
+        &mut options,
+        &mut commands,
+    );
+
+    let mut spec = spec(
+        title, version,
+    );
+    spec.info
+        .description = page.description;
+    spec.conventions = conventions(&options);
+    spec.options = non_empty(options);
+    spec.arguments = non_empty(page.arguments);
+    spec.commands = non_empty(commands);
+    Ok(spec)
+}
+
+/// Import `program` by running it with `--help`, and its subcommands `depth` levels down
+pub fn import_help(program: &str, depth: usize) -> color_eyre::Result<V0_1> {
+    let title = Path::new(program)
+        .file_name()
+        .map(
+            |name| {
+                name.to_string_lossy()
+                    .into_owned()
+            },
+        )
+        .unwrap_or_else(|| program.to_owned());
+    let version = program_version(program).unwrap_or_else(|| "unknown".to_string());
+
+    import_with(
+        &title,
+        &version,
+        depth,
+        &|path| {
+            run_help(
+                program, path,
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opencli::operations::find_option;
+
+    fn option_named<'a>(page: &'a HelpPage, name: &str) -> &'a OptionElement {
+        page.options
+            .iter()
+            .find(|option| option.name == name)
+            .unwrap_or_else(
+                || {
+                    panic!(
+                        "no option {}",
+                        name
+                    )
+                },
+            )
+    }
+
+    fn value(option: &OptionElement) -> &ArgumentElement {
+        &option
+            .arguments
+            .as_ref()
+            .expect("option takes a value")[0]
+    }
+
+    #[test]
+    fn test_clap_layout() {
+        let page = parse_help(include_str!("fixtures/clap-cmdi.txt"));
+
+        assert_eq!(
+            page.description
+                .as_deref(),
+            Some("An opinionated command composer for mission-ready terminal maneuvers")
+        );
+        assert_eq!(
+            page.commands
+                .iter()
+                .map(
+                    |command| command
+                        .name
+                        .as_str()
+                )
+                .collect::<Vec<_>>(),
+            vec!["init"]
+        );
+        assert_eq!(
+            page.arguments
+                .iter()
+                .map(
+                    |argument| (
+                        argument
+                            .name
+                            .as_str(),
+                        argument.min_values(),
+                        argument.max_values()
+                    )
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "CMD",
+                    0,
+                    Some(1)
+                ),
+                (
+                    "ARGS", 0, None
+                )
+            ]
+        );
+
+        let shell = option_named(
+            &page, "--shell",
+        );
+        assert_eq!(
+            value(shell).name,
+            "SHELL"
+        );
+        assert_eq!(
+            value(shell).accepted_values,
+            Some(
+                vec![
+                    "bash".into(),
+                    "zsh".into(),
+                    "fish".into(),
+                    "powershell".into()
+                ]
+            )
+        );
+        assert!(
+            option_named(
+                &page, "--line"
+            )
+            .description
+            .as_deref()
+            .is_some_and(|description| description.ends_with("is not given."))
+        );
+        assert_eq!(
+            option_named(
+                &page, "--help"
+            )
+            .aliases,
+            Some(vec!["-h".to_string()])
+        );
+        assert!(
+            option_named(
+                &page, "--demo"
+            )
+            .arguments
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_commands_skip_placeholder_rows() {
+        let page = parse_help(include_str!("fixtures/clap-cargo.txt"));
+
+        assert_eq!(
+            page.commands
+                .len(),
+            16
+        );
+        assert!(
+            page.commands
+                .iter()
+                .all(|command| command.name != "...")
+        );
+        let build = &page.commands[0];
+        assert_eq!(
+            build.name,
+            "build"
+        );
+        assert_eq!(
+            build.aliases,
+            Some(vec!["b".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_usage_flags_are_not_arguments() {
+        let page = parse_help(include_str!("fixtures/git.txt"));
+
+        let names: Vec<&str> = page
+            .arguments
+            .iter()
+            .map(
+                |argument| {
+                    argument
+                        .name
+                        .as_str()
+                },
+            )
+            .collect();
+        assert_eq!(
+            names,
+            ["args"]
+        );
+    }
+
+    #[test]
+    fn test_gnu_layout() {
+        let page = parse_help(include_str!("fixtures/gnu-ls.txt"));
+
+        assert!(
+            page.description
+                .as_deref()
+                .is_some_and(|description| description.starts_with("List information"))
+        );
+        assert_eq!(
+            page.arguments
+                .len(),
+            1
+        );
+        assert_eq!(
+            page.arguments[0].name,
+            "FILE"
+        );
+        assert_eq!(
+            page.arguments[0].max_values(),
+            None
+        );
+        assert!(!page.arguments[0].required);
+
+        let block_size = option_named(
+            &page,
+            "--block-size",
+        );
+        assert_eq!(
+            value(block_size).name,
+            "SIZE"
+        );
+        assert!(
+            block_size
+                .description
+                .as_deref()
+                .is_some_and(|description| description.ends_with("see SIZE format below"))
+        );
+        let color = option_named(
+            &page, "--color",
+        );
+        assert_eq!(
+            value(color).min_values(),
+            0
+        );
+        assert_eq!(
+            value(color).name,
+            "WHEN"
+        );
+        assert!(
+            option_named(
+                &page, "-c"
+            )
+            .arguments
+            .is_none()
+        );
+        assert_eq!(
+            option_named(
+                &page,
+                "--dereference-command-line"
+            )
+            .description
+            .as_deref(),
+            Some("follow symbolic links listed on the command line")
+        );
+    }
+
+    #[test]
+    fn test_argparse_layout() {
+        let page = parse_help(include_str!("fixtures/argparse-backup.txt"));
+
+        assert_eq!(
+            page.commands
+                .iter()
+                .map(
+                    |command| (
+                        command
+                            .name
+                            .as_str(),
+                        command
+                            .description
+                            .as_deref()
+                    )
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "restore",
+                    Some("restore files from a backup")
+                ),
+                (
+                    "list",
+                    Some("list backups")
+                )
+            ]
+        );
+        assert!(
+            page.arguments
+                .is_empty()
+        );
+        let mode = option_named(
+            &page, "--mode",
+        );
+        assert_eq!(
+            value(mode).accepted_values,
+            Some(
+                vec![
+                    "full".into(),
+                    "incremental".into()
+                ]
+            )
+        );
+        assert_eq!(
+            value(mode)
+                .default_value()
+                .as_deref(),
+            Some("full")
+        );
+        assert_eq!(
+            value(
+                option_named(
+                    &page, "--output"
+                )
+            )
+            .name,
+            "DIR"
+        );
+        assert_eq!(
+            option_named(
+                &page, "--output"
+            )
+            .aliases,
+            Some(vec!["-o".to_string()])
+        );
+
+        let restore = parse_help(include_str!("fixtures/argparse-backup-restore.txt"));
+        assert_eq!(
+            restore
+                .arguments
+                .iter()
+                .map(
+                    |argument| (
+                        argument
+                            .name
+                            .as_str(),
+                        argument.min_values(),
+                        argument.max_values()
+                    )
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "archive",
+                    1,
+                    Some(1)
+                ),
+                (
+                    "paths", 0, None
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cobra_layouts() {
+        let get = parse_help(include_str!("fixtures/cobra-kubectl-get.txt"));
+        assert_eq!(
+            get.description
+                .as_deref(),
+            Some("Display one or many resources.")
+        );
+        assert!(
+            option_named(
+                &get,
+                "--all-namespaces"
+            )
+            .arguments
+            .is_none()
+        );
+        assert_eq!(
+            value(
+                option_named(
+                    &get,
+                    "--chunk-size"
+                )
+            )
+            .default_value()
+            .as_deref(),
+            Some("500")
+        );
+        assert_eq!(
+            value(
+                option_named(
+                    &get, "--output"
+                )
+            )
+            .name,
+            "OUTPUT"
+        );
+        assert!(
+            option_named(
+                &get,
+                "--selector"
+            )
+            .description
+            .as_deref()
+            .is_some_and(|description| description.starts_with("Selector (label query)"))
+        );
+        assert!(
+            get.arguments
+                .is_empty()
+        );
+
+        let helm = parse_help(include_str!("fixtures/cobra-helm.txt"));
+        assert_eq!(
+            value(
+                option_named(
+                    &helm,
+                    "--kube-context"
+                )
+            )
+            .name,
+            "KUBE_CONTEXT"
+        );
+        assert_eq!(
+            value(
+                option_named(
+                    &helm,
+                    "--registry-config"
+                )
+            )
+            .default_value()
+            .as_deref(),
+            Some("~/.config/helm/registry/config.json")
+        );
+        assert!(
+            helm.arguments
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_import_descends_into_subcommands() {
+        let spec = import_with(
+            "helm",
+            "3.14.0",
+            2,
+            &|path| match path {
+                [] => Ok(include_str!("fixtures/cobra-helm.txt").to_string()),
+                [install] if install == "install" => {
+                    Ok(include_str!("fixtures/cobra-helm-install.txt").to_string())
+                }
+                _ => bail!(
+                    "no help for {:?}",
+                    path
+                ),
+            },
+        )
+        .unwrap();
+
+        let commands = spec
+            .commands
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            commands
+                .iter()
+                .map(
+                    |command| command
+                        .name
+                        .as_str()
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                "completion",
+                "install",
+                "list"
+            ]
+        );
+        let install = &commands[1];
+        assert_eq!(
+            install
+                .arguments
+                .iter()
+                .flatten()
+                .map(
+                    |argument| argument
+                        .name
+                        .as_str()
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                "NAME", "CHART"
+            ]
+        );
+        // Global flags belong to the root, which passes them down
+        assert!(
+            find_option(
+                &install.options,
+                "--namespace"
+            )
+            .is_none()
+        );
+        assert!(
+            find_option(
+                &install.options,
+                "--atomic"
+            )
+            .is_some()
+        );
+        assert!(
+            find_option(
+                &spec.options,
+                "--namespace"
+            )
+            .unwrap()
+            .recursive
+        );
+        assert!(
+            !find_option(
+                &spec.options,
+                "--registry-config"
+            )
+            .unwrap()
+            .recursive
+        );
+        assert_eq!(
+            spec.conventions
+                .and_then(|c| c.group_options),
+            Some(true)
+        );
+    }
+}
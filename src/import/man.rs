@@ -0,0 +1,1011 @@
+//! Build an OpenCLI spec from a roff man page
+//!
+//! Only the man(7) macros are understood. `NAME` gives the summary and `DESCRIPTION` the
+//! description. Options are the `.TP` and `.IP` entries whose tag starts with `-`, in the
+//! sections about options or, as GNU pages have them, in `DESCRIPTION`. Arguments are taken from
+//! the synopsis.
+
+use crate::import::help::{parse_option, usage_arguments};
+use crate::import::{conventions, merge_option, non_empty, spec};
+use crate::opencli::v0_1::{OptionElement, V0_1};
+use color_eyre::eyre::{WrapErr, eyre};
+use flate2::read::MultiGzDecoder;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Man directories searched when `$MANPATH` is not set
+const MAN_DIRS: [&str; 3] = [
+    "/usr/local/share/man",
+    "/usr/share/man",
+    "/usr/local/man",
+];
+
+/// Sections holding commands, user ones before administration ones
+const MAN_SECTIONS: [&str; 2] = [
+    "1", "8",
+];
+
+const GZIP_MAGIC: [u8; 2] = [
+    0x1f, 0x8b,
+];
+
+/// The content of a gzip file, every member of it
+fn gunzip(bytes: &[u8]) -> color_eyre::Result<Vec<u8>> {
+    let mut content = vec![];
+    MultiGzDecoder::new(bytes).read_to_end(&mut content)?;
+    Ok(content)
+}
+
+/// The roff source of the page at `path`, decompressed when gzipped
+pub fn read_page(path: &Path) -> color_eyre::Result<String> {
+    let bytes = fs::read(path).wrap_err_with(
+        || {
+            format!(
+                "Failed to read man page '{}'",
+                path.display()
+            )
+        },
+    )?;
+    let bytes = match bytes.starts_with(&GZIP_MAGIC) {
+        true => gunzip(&bytes).wrap_err_with(
+            || {
+                format!(
+                    "Failed to decompress man page '{}'",
+                    path.display()
+                )
+            },
+        )?,
+        false => bytes,
+    };
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// The installed page of `program` in section 1 or 8
+pub fn find_page(program: &str) -> Option<PathBuf> {
+    let roots: Vec<PathBuf> = match env::var_os("MANPATH") {
+        Some(manpath) => env::split_paths(&manpath)
+            .filter(
+                |root| {
+                    !root
+                        .as_os_str()
+                        .is_empty()
+                },
+            )
+            .collect(),
+        None => MAN_DIRS
+            .iter()
+            .map(PathBuf::from)
+            .collect(),
+    };
+
+    MAN_SECTIONS
+        .iter()
+        .flat_map(
+            |section| {
+                roots
+                    .iter()
+                    .flat_map(
+                        move |root| {
+                            let page = root
+                                .join(
+                                    format!(
+                                        "man{}",
+                                        section
+                                    ),
+                                )
+                                .join(
+                                    format!(
+                                        "{}.{}",
+                                        program, section
+                                    ),
+                                );
+                            [
+                                page.with_extension(
+                                    format!(
+                                        "{}.gz",
+                                        section
+                                    ),
+                                ),
+                                page,
+                            ]
+                        },
+                    )
+            },
+        )
+        .find(|candidate| candidate.is_file())
+}
+
+/// The name of a special character like `\(em` or `\[aq]`
+fn special(name: &str) -> &'static str {
+    match name {
+        "em" => "\u{2014}",
+        "en" => "\u{2013}",
+        "aq" | "oq" | "cq" => "'",
+        "dq" | "lq" | "rq" => "\"",
+        "hy" | "mi" | "-" => "-",
+        "bu" => "\u{2022}",
+        "co" => "\u{a9}",
+        "rg" => "\u{ae}",
+        "tm" => "\u{2122}",
+        "ga" => "`",
+        "ti" => "~",
+        "ha" => "^",
+        "rs" => "\\",
+        "lB" => "[",
+        "rB" => "]",
+        "lC" => "{",
+        "rC" => "}",
+        "la" => "<",
+        "ra" => ">",
+        _ => "",
+    }
+}
+
+/// The name after an escape: `x`, `(xx` or `[name]`
+fn escape_name(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    match chars.next() {
+        Some('(') => chars
+            .take(2)
+            .collect(),
+        Some('[') => chars
+            .take_while(|c| *c != ']')
+            .collect(),
+        Some(c) => c.to_string(),
+        None => String::new(),
+    }
+}
+
+/// Plain text of a roff line, with fonts dropped and escapes resolved
+fn text(line: &str) -> String {
+    let mut text = String::new();
+    let mut chars = line
+        .chars()
+        .peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            // A comment runs to the end of the line
+            Some('"') => break,
+            Some('-') => text.push('-'),
+            Some('e' | '\\') => text.push('\\'),
+            Some(' ' | '~' | '0') => text.push(' '),
+            Some(c @ ('\'' | '`' | '.')) => text.push(c),
+            Some('f' | 'n' | 'm' | 'M' | 'F') => {
+                escape_name(&mut chars);
+            }
+            Some('(') => {
+                let name: String = chars
+                    .by_ref()
+                    .take(2)
+                    .collect();
+                text.push_str(special(&name));
+            }
+            Some('[') => {
+                let name: String = chars
+                    .by_ref()
+                    .take_while(|c| *c != ']')
+                    .collect();
+                match name
+                    .strip_prefix('u')
+                    .and_then(
+                        |code| {
+                            u32::from_str_radix(
+                                code, 16,
+                            )
+                            .ok()
+                        },
+                    )
+                    .and_then(char::from_u32)
+                {
+                    Some(c) => text.push(c),
+                    None => text.push_str(special(&name)),
+                }
+            }
+            Some('*') => {
+                let name = escape_name(&mut chars);
+                text.push_str(
+                    match name.as_str() {
+                        "R" => "\u{ae}",
+                        "Tm" => "\u{2122}",
+                        "lq" | "rq" => "\"",
+                        _ => "",
+                    },
+                );
+            }
+            Some('s') => {
+                chars.next_if(|c| *c == '+' || *c == '-');
+                match chars.peek() {
+                    Some('(' | '[') => {
+                        escape_name(&mut chars);
+                    }
+                    _ => {
+                        chars.next_if(char::is_ascii_digit);
+                    }
+                }
+            }
+            // Motions and the like take a quoted argument
+            Some('h' | 'v' | 'w' | 'o' | 'b' | 'l' | 'L' | 'x' | 'D' | 'X' | 'Z' | 'N')
+                if chars
+                    .next_if_eq(&'\'')
+                    .is_some() =>
+            {
+                chars
+                    .by_ref()
+                    .take_while(|c| *c != '\'')
+                    .for_each(drop);
+            }
+            Some(c) if "&|^%,/:c)".contains(c) => {}
+            Some(c) => text.push(c),
+            None => {}
+        }
+    }
+
+    text
+}
+
+/// The arguments of a request, double quotes grouping words
+fn request_arguments(line: &str) -> Vec<String> {
+    let mut arguments = vec![];
+    let mut chars = line
+        .chars()
+        .peekable();
+
+    loop {
+        while chars
+            .next_if(|c| *c == ' ' || *c == '\t')
+            .is_some()
+        {}
+        let Some(first) = chars.next() else {
+            break;
+        };
+
+        let mut argument = String::new();
+        if first == '"' {
+            while let Some(c) = chars.next() {
+                match c {
+                    // `""` is a quote inside a quoted argument
+                    '"' if chars
+                        .next_if_eq(&'"')
+                        .is_some() =>
+                    {
+                        argument.push('"')
+                    }
+                    '"' => break,
+                    c => argument.push(c),
+                }
+            }
+        } else {
+            argument.push(first);
+            while let Some(c) = chars.next_if(|c| *c != ' ' && *c != '\t') {
+                argument.push(c);
+                // Keep an escaped space inside the argument
+                if c == '\\'
+                    && let Some(escaped) = chars.next()
+                {
+                    argument.push(escaped);
+                }
+            }
+        }
+        arguments.push(argument);
+    }
+
+    arguments
+}
+
+/// What a line of the page contributes
+#[derive(Debug, PartialEq)]
+enum Line {
+    Section(String),
+    Paragraph,
+    /// An indented paragraph continuing the entry it is in
+    Continue,
+    Tag(String),
+    /// Another tag for the entry started by the last one, from `.TQ`
+    ExtraTag(String),
+    Text(String),
+    Break,
+    Indent,
+    Outdent,
+}
+
+/// The page as lines of text and the structure around them
+fn lines(source: &str) -> Vec<Line> {
+    let mut lines = vec![];
+    // `.TP` and `.TQ` take the next line as the tag
+    let mut tag_next: Option<bool> = None;
+
+    for line in source.lines() {
+        let produced = match line
+            .strip_prefix('.')
+            .or_else(|| line.strip_prefix('\''))
+        {
+            Some(request) => {
+                let request = request.trim_start();
+                let (name, rest) = request
+                    .split_once(
+                        [
+                            ' ', '\t',
+                        ],
+                    )
+                    .unwrap_or(
+                        (
+                            request, "",
+                        ),
+                    );
+                let arguments = request_arguments(rest);
+                match name {
+                    "SH" => Some(Line::Section(text(&arguments.join(" ")))),
+                    "SS" | "PP" | "P" | "LP" | "HP" => Some(Line::Paragraph),
+                    "TP" => {
+                        tag_next = Some(false);
+                        None
+                    }
+                    "TQ" => {
+                        tag_next = Some(true);
+                        None
+                    }
+                    "IP" => Some(
+                        match arguments
+                            .first()
+                            .map(|tag| text(tag))
+                            .filter(
+                                |tag| {
+                                    !tag.trim()
+                                        .is_empty()
+                                },
+                            ) {
+                            Some(tag) => Line::Tag(tag),
+                            None => Line::Continue,
+                        },
+                    ),
+                    "RS" => Some(Line::Indent),
+                    "RE" => Some(Line::Outdent),
+                    "br" | "sp" => Some(Line::Break),
+                    "B" | "I" | "SM" | "SB" if !arguments.is_empty() => Some(
+                        Line::Text(
+                            arguments
+                                .iter()
+                                .map(|argument| text(argument))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        ),
+                    ),
+                    "BR" | "BI" | "IB" | "IR" | "RB" | "RI" => Some(
+                        Line::Text(
+                            arguments
+                                .iter()
+                                .map(|argument| text(argument))
+                                .collect(),
+                        ),
+                    ),
+                    _ => None,
+                }
+            }
+            None => Some(Line::Text(text(line))),
+        };
+
+        match (
+            produced, tag_next,
+        ) {
+            (Some(Line::Text(tag)), Some(extra)) => {
+                tag_next = None;
+                lines.push(
+                    match extra {
+                        true => Line::ExtraTag(tag),
+                        false => Line::Tag(tag),
+                    },
+                );
+            }
+            (Some(line), _) => lines.push(line),
+            (None, _) => {}
+        }
+    }
+
+    indented_tags(lines)
+}
+
+/// Turn `.PP` paragraphs of option names followed by an `.RS` body, as DocBook generates them,
+/// into tags
+fn indented_tags(lines: Vec<Line>) -> Vec<Line> {
+    let mut tagged = vec![];
+    let mut lines = lines
+        .into_iter()
+        .peekable();
+
+    while let Some(line) = lines.next() {
+        let is_paragraph = line == Line::Paragraph;
+        tagged.push(line);
+        if !is_paragraph {
+            continue;
+        }
+
+        let mut names: Vec<Line> = vec![];
+        while let Some(line) = lines.next_if(
+            |line| {
+                matches!(
+                    line,
+                    Line::Text(_) | Line::Break
+                )
+            },
+        ) {
+            names.push(line);
+        }
+        let is_tag = lines.peek() == Some(&Line::Indent)
+            && matches!(names.first(), Some(Line::Text(text)) if text.starts_with('-'));
+        match is_tag {
+            true => tagged.push(
+                Line::Tag(
+                    names
+                        .split(|line| *line == Line::Break)
+                        .map(
+                            |texts| {
+                                texts
+                                    .iter()
+                                    .filter_map(
+                                        |line| match line {
+                                            Line::Text(text) => Some(text.as_str()),
+                                            _ => None,
+                                        },
+                                    )
+                                    .collect::<Vec<_>>()
+                                    .join(" ")
+                            },
+                        )
+                        .filter(|names| !names.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            ),
+            false => tagged.extend(names),
+        }
+    }
+
+    tagged
+}
+
+/// A tagged paragraph being read
+struct Entry {
+    tag: String,
+    /// Whether it documents an option, the rest are read only to be skipped
+    is_option: bool,
+    indent: usize,
+    body: String,
+    /// Tags of the paragraphs nested in it, like the values an option takes
+    nested: Vec<String>,
+}
+
+impl Entry {
+    fn option(self) -> Option<OptionElement> {
+        if !self.is_option {
+            return None;
+        }
+        let body = self
+            .body
+            .lines()
+            .map(
+                |line| {
+                    line.split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                },
+            )
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut option = parse_option(
+            &self.tag,
+            Some(body).filter(|body| !body.is_empty()),
+        )?;
+
+        // Single word tags nested in the entry list the values
+        if let Some(value) = option
+            .arguments
+            .as_mut()
+            .and_then(|arguments| arguments.first_mut())
+            && value
+                .accepted_values
+                .is_none()
+            && self
+                .nested
+                .iter()
+                .all(|tag| !tag.starts_with('-') && !tag.contains(char::is_whitespace))
+        {
+            value.accepted_values = non_empty(self.nested);
+        }
+        Some(option)
+    }
+}
+
+/// Append `text` to a paragraph, on the same line
+fn push_text(paragraph: &mut String, text: &str) {
+    if !paragraph.is_empty() && !paragraph.ends_with('\n') {
+        paragraph.push(' ');
+    }
+    paragraph.push_str(text.trim());
+}
+
+/// Build the spec for `title` from the roff source of its man page
+pub fn parse_man(title: &str, source: &str) -> V0_1 {
+    let version = source
+        .lines()
+        .find_map(|line| line.strip_prefix(".TH "))
+        .map(request_arguments)
+        .and_then(
+            |arguments| {
+                text(arguments.get(3)?)
+                    .split_whitespace()
+                    .last()
+                    .filter(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+                    .map(String::from)
+            },
+        )
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut section = String::new();
+    let mut name = String::new();
+    let mut synopsis = String::new();
+    let mut paragraphs: Vec<String> = vec![];
+    let mut options: Vec<OptionElement> = vec![];
+    let mut entry: Option<Entry> = None;
+    let mut indent = 0;
+
+    let options_section = |section: &str| section.contains("OPTION") || section == "DESCRIPTION";
+    let mut finish = |entry: &mut Option<Entry>| {
+        if let Some(option) = entry
+            .take()
+            .and_then(Entry::option)
+        {
+            merge_option(
+                &mut options,
+                option,
+            );
+        }
+    };
+
+    for line in lines(source) {
+        match line {
+            Line::Section(title) => {
+                finish(&mut entry);
+                section = title.to_uppercase();
+                indent = 0;
+            }
+            Line::Tag(tag) => match &mut entry {
+                Some(current) if indent > current.indent => {
+                    current
+                        .body
+                        .push('\n');
+                    push_text(
+                        &mut current.body,
+                        &format!(
+                            "{}:",
+                            tag
+                        ),
+                    );
+                    current
+                        .nested
+                        .push(tag);
+                }
+                _ => {
+                    finish(&mut entry);
+                    entry = Some(
+                        Entry {
+                            is_option: options_section(&section) && tag.starts_with('-'),
+                            tag,
+                            indent,
+                            body: String::new(),
+                            nested: vec![],
+                        },
+                    );
+                }
+            },
+            Line::ExtraTag(tag) => {
+                if let Some(current) = &mut entry {
+                    current.tag = format!(
+                        "{}, {}",
+                        current.tag, tag
+                    );
+                }
+            }
+            Line::Text(text) => match &mut entry {
+                Some(current) => push_text(
+                    &mut current.body,
+                    &text,
+                ),
+                None => match section.as_str() {
+                    "NAME" => push_text(
+                        &mut name, &text,
+                    ),
+                    "SYNOPSIS" => push_text(
+                        &mut synopsis,
+                        &text,
+                    ),
+                    "DESCRIPTION" => {
+                        if paragraphs.is_empty() {
+                            paragraphs.push(String::new());
+                        }
+                        push_text(
+                            paragraphs
+                                .last_mut()
+                                .expect("a paragraph"),
+                            &text,
+                        );
+                    }
+                    _ => {}
+                },
+            },
+            Line::Paragraph => match &mut entry {
+                Some(current) if indent > current.indent => current
+                    .body
+                    .push('\n'),
+                _ => {
+                    finish(&mut entry);
+                    if paragraphs
+                        .last()
+                        .is_some_and(|paragraph| !paragraph.is_empty())
+                    {
+                        paragraphs.push(String::new());
+                    }
+                }
+            },
+            Line::Continue | Line::Break => {
+                if let Some(current) = &mut entry {
+                    current
+                        .body
+                        .push('\n');
+                } else if section == "SYNOPSIS" {
+                    synopsis.push('\n');
+                }
+            }
+            Line::Indent => indent += 1,
+            Line::Outdent => indent = indent.saturating_sub(1),
+        }
+    }
+    finish(&mut entry);
+
+    // Only the first form of the synopsis, without the program name
+    let usage: Vec<&str> = synopsis
+        .lines()
+        .find(
+            |line| {
+                !line
+                    .trim()
+                    .is_empty()
+            },
+        )
+        .unwrap_or_default()
+        .split_whitespace()
+        .skip(1)
+        .filter(
+            |word| {
+                !word
+                    .trim_start_matches('[')
+                    .starts_with('-')
+            },
+        )
+        .collect();
+
+    let mut spec = spec(
+        title, &version,
+    );
+    spec.info
+        .summary = name
+        .split_once(" - ")
+        .map(
+            |(_, summary)| {
+                summary
+                    .trim()
+                    .to_owned()
+            },
+        );
+    spec.info
+        .description = Some(
+        paragraphs
+            .iter()
+            .filter(|paragraph| !paragraph.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    )
+    .filter(|description| !description.is_empty());
+    spec.conventions = conventions(&options);
+    spec.options = non_empty(options);
+    spec.arguments = non_empty(usage_arguments(&usage));
+    spec
+}
+
+/// The program a page file documents, `ls.1.gz` gives `ls`
+fn page_title(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let name = name
+        .strip_suffix(".gz")
+        .unwrap_or(&name);
+    match name.rsplit_once('.') {
+        Some((title, _)) => title.to_owned(),
+        None => name.to_owned(),
+    }
+}
+
+/// Import the man page `page`, a path to the page source or the program it documents
+///
+/// A page that only includes another with `.so` is followed once.
+pub fn import_man(page: &str) -> color_eyre::Result<V0_1> {
+    let path = match Path::new(page).is_file() {
+        true => PathBuf::from(page),
+        false => find_page(page).ok_or_else(
+            || {
+                eyre!(
+                    "No man page found for '{}' in sections {}",
+                    page,
+                    MAN_SECTIONS.join(", ")
+                )
+            },
+        )?,
+    };
+
+    let mut source = read_page(&path)?;
+    if let Some(included) = source
+        .lines()
+        .find(|line| !line.starts_with(".\\\""))
+        .and_then(|line| line.strip_prefix(".so "))
+    {
+        // The include is relative to the man root, above the `manN` directory
+        let root = path
+            .parent()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("."));
+        let included = root.join(included.trim());
+        let mut gzipped = included
+            .clone()
+            .into_os_string();
+        gzipped.push(".gz");
+        source = match included.is_file() {
+            true => read_page(&included)?,
+            false => read_page(Path::new(&gzipped))?,
+        };
+    }
+
+    Ok(
+        parse_man(
+            &page_title(&path),
+            &source,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opencli::operations::find_option;
+    use crate::opencli::v0_1::ArgumentElement;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn sample() -> V0_1 {
+        parse_man(
+            "backup",
+            include_str!("fixtures/man-backup.8"),
+        )
+    }
+
+    fn value<'a>(spec: &'a V0_1, name: &str) -> &'a ArgumentElement {
+        &find_option(
+            &spec.options,
+            name,
+        )
+        .unwrap_or_else(
+            || {
+                panic!(
+                    "no option {}",
+                    name
+                )
+            },
+        )
+        .arguments
+        .as_ref()
+        .expect("option takes a value")[0]
+    }
+
+    #[test]
+    fn test_text_resolves_escapes() {
+        assert_eq!(
+            text(
+                r#"\fB\-\-output\fR=\fIDIR\fP \(em it\(aqs \[u00E9]t\(e2 \s-1ok\s0\&. \" comment"#
+            ),
+            "--output=DIR \u{2014} it's \u{e9}t ok. "
+        );
+        assert_eq!(
+            request_arguments(r#"\-h ", " "say ""hi""""#),
+            vec![
+                r"\-h",
+                ", ",
+                r#"say "hi""#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_info_and_arguments() {
+        let spec = sample();
+
+        assert_eq!(
+            spec.info
+                .version,
+            "2.4.1"
+        );
+        assert_eq!(
+            spec.info
+                .summary
+                .as_deref(),
+            Some("copy files to a backup location")
+        );
+        assert_eq!(
+            spec.info
+                .description
+                .as_deref(),
+            Some(
+                "backup copies the SOURCE files to a backup location, only sending what changed \
+                 since the last run.\n\nBackups are written below /var/backups, one directory per \
+                 run."
+            )
+        );
+        assert_eq!(
+            spec.arguments
+                .iter()
+                .flatten()
+                .map(
+                    |argument| (
+                        argument
+                            .name
+                            .as_str(),
+                        argument.min_values(),
+                        argument.max_values()
+                    )
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "SOURCE", 1, None
+                ),
+                (
+                    "DEST",
+                    0,
+                    Some(1)
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tagged_paragraphs_are_options() {
+        let spec = sample();
+
+        assert_eq!(
+            spec.options
+                .iter()
+                .flatten()
+                .map(
+                    |option| option
+                        .name
+                        .as_str()
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                "--help",
+                "--verbose",
+                "--mode",
+                "--output",
+                "-x",
+                "--quiet",
+                "--compress"
+            ]
+        );
+        assert_eq!(
+            find_option(
+                &spec.options,
+                "-q"
+            )
+            .and_then(
+                |quiet| quiet
+                    .description
+                    .as_deref()
+            ),
+            Some("print errors only")
+        );
+
+        // Values listed as nested tags
+        let mode = value(
+            &spec, "--mode",
+        );
+        assert_eq!(
+            mode.accepted_values,
+            Some(
+                vec![
+                    "full".to_string(),
+                    "incremental".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            value(
+                &spec, "-o"
+            )
+            .default_value()
+            .as_deref(),
+            Some("/var/backups")
+        );
+        assert_eq!(
+            value(
+                &spec, "-x"
+            )
+            .name,
+            "PATTERN"
+        );
+        assert_eq!(
+            value(
+                &spec,
+                "--compress"
+            )
+            .min_values(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_docbook_paragraphs_are_tags() {
+        let spec = parse_man(
+            "tool",
+            ".SH OPTIONS\n.PP\n\\fB\\-n\\fR, \\fB\\-\\-lines=\\fR\\fIN\\fR\n.RS 4\nShow N lines\\&.\n\
+             .RE\n.PP\nNot an option\\&.\n",
+        );
+
+        let lines = find_option(
+            &spec.options,
+            "--lines",
+        )
+        .unwrap();
+        assert_eq!(
+            lines.aliases,
+            Some(vec!["-n".to_string()])
+        );
+        assert_eq!(
+            lines
+                .description
+                .as_deref(),
+            Some("Show N lines.")
+        );
+        assert_eq!(
+            spec.options
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_gunzip() -> color_eyre::Result<()> {
+        let page = include_str!("fixtures/man-backup.8");
+        let mut encoder = GzEncoder::new(
+            vec![],
+            Compression::default(),
+        );
+        encoder.write_all(page.as_bytes())?;
+        let gzipped = encoder.finish()?;
+
+        assert_eq!(
+            gunzip(&gzipped)?,
+            page.as_bytes()
+        );
+        assert!(gunzip(page.as_bytes()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+//! Generating OpenCLI specs for programs that ship none
+
+pub mod completion;
+pub mod fish;
+pub mod help;
+pub mod man;
+pub mod zsh;
+
+use crate::opencli::completion::COMPLETION_METADATUM;
+use crate::opencli::operations::DEFAULT_METADATUM;
+use crate::opencli::registry::SpecRegistry;
+use crate::opencli::v0_1::{
+    ArgumentElement, Arity, CommandElement, Conventions, Info, MetadatumElement, OptionElement,
+    V0_1,
+};
+use color_eyre::eyre::{WrapErr, bail};
+use directories::ProjectDirs;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An empty spec for `title`
+pub(crate) fn spec(title: &str, version: &str) -> V0_1 {
+    V0_1 {
+        arguments: None,
+        commands: None,
+        conventions: None,
+        examples: None,
+        exit_codes: None,
+        info: Info {
+            contact: None,
+            description: None,
+            license: None,
+            summary: None,
+            title: title.to_owned(),
+            version: version.to_owned(),
+        },
+        interactive: false,
+        metadata: None,
+        opencli: "0.1".to_string(),
+        options: None,
+    }
+}
+
+pub(crate) fn command(name: &str) -> CommandElement {
+    CommandElement {
+        aliases: None,
+        arguments: None,
+        commands: None,
+        description: None,
+        examples: None,
+        exit_codes: None,
+        hidden: false,
+        interactive: false,
+        metadata: None,
+        name: name.to_owned(),
+        options: None,
+    }
+}
+
+pub(crate) fn option(name: &str) -> OptionElement {
+    OptionElement {
+        aliases: None,
+        arguments: None,
+        description: None,
+        group: None,
+        hidden: false,
+        metadata: None,
+        name: name.to_owned(),
+        recursive: false,
+        required: false,
+    }
+}
+
+pub(crate) fn argument(name: &str) -> ArgumentElement {
+    ArgumentElement {
+        accepted_values: None,
+        arity: None,
+        description: None,
+        group: None,
+        hidden: false,
+        metadata: None,
+        name: name.to_owned(),
+        required: false,
+    }
+}
+
+/// The arity of a variadic argument, `None` leaves the default of one value
+pub(crate) fn arity(optional: bool, variadic: bool) -> Option<Arity> {
+    variadic.then_some(
+        Arity {
+            minimum: Some(!optional as i64),
+            maximum: None,
+        },
+    )
+}
+
+/// Metadatum documenting `value` as the argument's default
+pub(crate) fn default_metadatum(value: &str) -> MetadatumElement {
+    MetadatumElement {
+        name: DEFAULT_METADATUM.to_string(),
+        value: Some(Value::String(value.to_owned())),
+    }
+}
+
+/// Metadatum completing the argument with paths matching `glob`
+pub(crate) fn glob_metadatum(glob: &str) -> MetadatumElement {
+    MetadatumElement {
+        name: COMPLETION_METADATUM.to_string(),
+        value: Some(json!({ "glob": glob })),
+    }
+}
+
+/// An option's value named after the option, `--kube-context` gives `KUBE_CONTEXT`
+pub(crate) fn value_name(option: &str) -> String {
+    option
+        .trim_start_matches(
+            [
+                '-', '+',
+            ],
+        )
+        .replace(
+            '-', "_",
+        )
+        .to_uppercase()
+}
+
+/// `None` for an empty list, as the spec leaves out what a command does not have
+pub(crate) fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+    (!items.is_empty()).then_some(items)
+}
+
+/// The command called `name`, added at the end when missing
+pub(crate) fn command_mut<'a>(
+    commands: &'a mut Vec<CommandElement>,
+    name: &str,
+) -> &'a mut CommandElement {
+    let index = match commands
+        .iter()
+        .position(|command| command.name == name)
+    {
+        Some(index) => index,
+        None => {
+            commands.push(command(name));
+            commands.len() - 1
+        }
+    };
+    &mut commands[index]
+}
+
+/// Add `option`, or fill in what an option declared earlier under the same name lacks
+///
+/// Completion files may declare an option several times, each adding values.
+pub(crate) fn merge_option(options: &mut Vec<OptionElement>, option: OptionElement) {
+    let Some(existing) = options
+        .iter_mut()
+        .find(|existing| existing.name == option.name)
+    else {
+        options.push(option);
+        return;
+    };
+
+    existing.description = existing
+        .description
+        .take()
+        .or(option.description);
+    match (
+        &mut existing.arguments,
+        option.arguments,
+    ) {
+        (None, arguments) => existing.arguments = arguments,
+        (Some(existing), Some(arguments)) => {
+            if let (Some(values), Some(more)) = (
+                &mut existing[0].accepted_values,
+                arguments
+                    .into_iter()
+                    .next()
+                    .and_then(|argument| argument.accepted_values),
+            ) {
+                values.extend(
+                    more.into_iter()
+                        .filter(|value| !values.contains(value))
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+        (Some(_), None) => {}
+    }
+}
+
+/// Short options like `-a` can be grouped, as the common option parsers allow
+pub(crate) fn conventions(options: &[OptionElement]) -> Option<Conventions> {
+    options
+        .iter()
+        .any(
+            |option| {
+                option
+                    .short_flag()
+                    .is_some()
+            },
+        )
+        .then_some(
+            Conventions {
+                group_options: Some(true),
+                option_separator: None,
+            },
+        )
+}
+
+/// Remove options a subcommand repeats from its ancestors, marking the ancestor's recursive
+///
+/// Help pages and completions list inherited options again for every subcommand.
+pub(crate) fn drop_inherited(options: &mut [OptionElement], commands: &mut [CommandElement]) {
+    for command in commands {
+        if let Some(own) = &mut command.options {
+            own.retain(
+                |option| match options
+                    .iter_mut()
+                    .find(|inherited| inherited.name == option.name)
+                {
+                    Some(inherited) => {
+                        inherited.recursive = true;
+                        false
+                    }
+                    None => true,
+                },
+            );
+            if own.is_empty() {
+                command.options = None;
+            }
+        }
+
+        if let Some(children) = &mut command.commands {
+            drop_inherited(
+                options, children,
+            );
+            if let Some(own) = &mut command.options {
+                drop_inherited(
+                    own, children,
+                );
+            }
+        }
+    }
+}
+
+/// Drop nulls, empty lists and `false` flags, they are the defaults when loading
+fn prune(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.values_mut()
+                .for_each(prune);
+            map.retain(
+                |_, value| {
+                    !matches!(
+                        value,
+                        Value::Null | Value::Bool(false)
+                    ) && value
+                        .as_array()
+                        .is_none_or(|array| !array.is_empty())
+                },
+            );
+        }
+        Value::Array(array) => array
+            .iter_mut()
+            .for_each(prune),
+        _ => {}
+    }
+}
+
+/// The spec as YAML without the fields left at their defaults
+pub fn to_yaml(spec: &V0_1) -> color_eyre::Result<String> {
+    let mut value = serde_json::to_value(spec)?;
+    prune(&mut value);
+    Ok(serde_yml::to_string(&value)?)
+}
+
+/// Write `spec` to `output`, or where the registry looks for user specs
+///
+/// An existing file is only replaced with `force`. Returns the path written.
+pub fn write_spec(
+    spec: &V0_1,
+    output: Option<&Path>,
+    directories: &ProjectDirs,
+    force: bool,
+) -> color_eyre::Result<PathBuf> {
+    let path = match output {
+        Some(path) => path.to_path_buf(),
+        None => SpecRegistry::user_spec_dir(directories).join(
+            format!(
+                "{}.yaml",
+                spec.info
+                    .title
+            ),
+        ),
+    };
+    if path.exists() && !force {
+        bail!(
+            "'{}' already exists, pass --force to replace it",
+            path.display()
+        );
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(
+        &path,
+        to_yaml(spec)?,
+    )
+    .wrap_err_with(
+        || {
+            format!(
+                "Failed to write spec '{}'",
+                path.display()
+            )
+        },
+    )?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yaml_leaves_out_defaults() {
+        let mut spec = spec(
+            "tool", "1.0",
+        );
+        spec.options = Some(vec![option("--all")]);
+
+        let yaml = to_yaml(&spec).unwrap();
+        assert!(!yaml.contains("null"));
+        assert!(!yaml.contains("hidden"));
+        assert_eq!(
+            serde_yml::from_str::<V0_1>(&yaml).unwrap(),
+            spec
+        );
+    }
+}
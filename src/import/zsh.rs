@@ -0,0 +1,1330 @@
+//! Build an OpenCLI spec from a zsh completion function
+//!
+//! The script is tokenized, not run. Options and positionals come from the `_arguments` specs,
+//! subcommands from the `name:description` arrays handed to `_describe`. An `_arguments` call
+//! belongs to the subcommand named by the `case` branch or `_program_subcommand` function it sits
+//! in; the largest call left over describes the program itself.
+
+use crate::import::{
+    argument, arity, command_mut, conventions, drop_inherited, glob_metadatum, merge_option,
+    non_empty, option, spec, value_name,
+};
+use crate::opencli::v0_1::{ArgumentElement, Arity, CommandElement, OptionElement, V0_1};
+use std::collections::HashMap;
+
+/// `_arguments` flags that come before the specs
+const ARGUMENTS_FLAGS: [&str; 8] = [
+    "-s", "-S", "-C", "-w", "-W", "-n", "-0", "-R",
+];
+
+/// `_arguments` flags that take the word after them
+const ARGUMENTS_VALUE_FLAGS: [&str; 3] = [
+    "-A", "-O", "-M",
+];
+
+/// Exclusion list entries that stand for the positionals rather than options
+const NOT_OPTIONS: [&str; 3] = [
+    "-", "*", ":",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A word, several when brace expansion like `{-v,--verbose}` applies
+    Word(Vec<String>),
+    Open,
+    Close,
+    Separator,
+}
+
+impl Token {
+    fn word(&self) -> Option<&str> {
+        match self {
+            Token::Word(words) => words
+                .first()
+                .map(String::as_str),
+            _ => None,
+        }
+    }
+}
+
+/// The text up to the `end` matching the opener before `start`, escapes and nesting included
+fn enclosed(chars: &[char], start: usize, open: char, end: char) -> usize {
+    let mut depth = 1;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            c if c == open => depth += 1,
+            c if c == end => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Split a script into words and the separators and parentheses between them
+///
+/// Quotes are removed and escapes resolved, expansions like `${name}` and `$(command)` are kept
+/// as they are. Separators inside parentheses are dropped so arrays can span lines.
+fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<char> = text
+        .chars()
+        .collect();
+    let mut tokens = vec![];
+    let mut word: Option<Vec<String>> = None;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    fn append(word: &mut Option<Vec<String>>, text: &str) {
+        word.get_or_insert_with(|| vec![String::new()])
+            .iter_mut()
+            .for_each(|word| word.push_str(text));
+    }
+    fn flush(tokens: &mut Vec<Token>, word: &mut Option<Vec<String>>) {
+        if let Some(words) = word.take() {
+            tokens.push(Token::Word(words));
+        }
+    }
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        match c {
+            '\\' => {
+                match chars.get(i) {
+                    // A line continuation
+                    Some('\n') | None => {}
+                    Some(escaped) => append(
+                        &mut word,
+                        &escaped.to_string(),
+                    ),
+                }
+                i += 1;
+            }
+            '#' if word.is_none() => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\n' | ';' => {
+                flush(
+                    &mut tokens,
+                    &mut word,
+                );
+                if depth == 0 && tokens.last() != Some(&Token::Separator) {
+                    tokens.push(Token::Separator);
+                }
+            }
+            // Attached to a word they are patterns or redirections like `2>&1`
+            '&' | '|' if word.is_none() => {
+                if depth == 0 && tokens.last() != Some(&Token::Separator) {
+                    tokens.push(Token::Separator);
+                }
+            }
+            c if c.is_whitespace() => flush(
+                &mut tokens,
+                &mut word,
+            ),
+            '\'' => {
+                let end = (i..chars.len())
+                    .find(|&end| chars[end] == '\'')
+                    .unwrap_or(chars.len());
+                append(
+                    &mut word,
+                    &chars[i..end]
+                        .iter()
+                        .collect::<String>(),
+                );
+                i = end + 1;
+            }
+            '"' => {
+                let mut quoted = String::new();
+                while i < chars.len() && chars[i] != '"' {
+                    match chars[i] {
+                        '\\' if i + 1 < chars.len() => {
+                            i += 1;
+                            match chars[i] {
+                                '\n' => {}
+                                escaped @ ('"' | '\\' | '$' | '`') => quoted.push(escaped),
+                                escaped => {
+                                    quoted.push('\\');
+                                    quoted.push(escaped);
+                                }
+                            }
+                        }
+                        c => quoted.push(c),
+                    }
+                    i += 1;
+                }
+                append(
+                    &mut word, &quoted,
+                );
+                i += 1;
+            }
+            '$' if matches!(
+                chars.get(i),
+                Some('{' | '(')
+            ) =>
+            {
+                let (open, close) = match chars[i] {
+                    '{' => (
+                        '{', '}',
+                    ),
+                    _ => (
+                        '(', ')',
+                    ),
+                };
+                let end = enclosed(
+                    &chars,
+                    i + 1,
+                    open,
+                    close,
+                );
+                append(
+                    &mut word,
+                    &chars[i - 1..(end + 1).min(chars.len())]
+                        .iter()
+                        .collect::<String>(),
+                );
+                i = end + 1;
+            }
+            '(' => {
+                flush(
+                    &mut tokens,
+                    &mut word,
+                );
+                tokens.push(Token::Open);
+                depth += 1;
+            }
+            ')' => {
+                flush(
+                    &mut tokens,
+                    &mut word,
+                );
+                tokens.push(Token::Close);
+                depth = depth.saturating_sub(1);
+            }
+            '{' => {
+                let end = (i..chars.len()).find(|&end| "}{'\" \t\n".contains(chars[end]));
+                match end.filter(|&end| chars[end] == '}' && chars[i..end].contains(&',')) {
+                    Some(end) => {
+                        let alternatives: String = chars[i..end]
+                            .iter()
+                            .collect();
+                        let words = word
+                            .take()
+                            .unwrap_or_else(|| vec![String::new()]);
+                        word = Some(
+                            words
+                                .iter()
+                                .flat_map(
+                                    |word| {
+                                        alternatives
+                                            .split(',')
+                                            .map(
+                                                move |alternative| {
+                                                    format!(
+                                                        "{}{}",
+                                                        word, alternative
+                                                    )
+                                                },
+                                            )
+                                    },
+                                )
+                                .collect(),
+                        );
+                        i = end + 1;
+                    }
+                    None => append(
+                        &mut word, "{",
+                    ),
+                }
+            }
+            c => append(
+                &mut word,
+                &c.to_string(),
+            ),
+        }
+    }
+    flush(
+        &mut tokens,
+        &mut word,
+    );
+
+    tokens
+}
+
+/// One `_arguments` call with the places it sits in, innermost first
+#[derive(Debug)]
+struct ArgumentsCall {
+    labels: Vec<String>,
+    specs: Vec<Vec<String>>,
+}
+
+/// What a completion function declares
+#[derive(Debug, Default)]
+struct Script {
+    /// `name:description` entries of the arrays handed to `_describe` for commands
+    commands: Vec<String>,
+    calls: Vec<ArgumentsCall>,
+}
+
+/// The name of the array a word like `$opts`, `${opts}` or `${opts[@]}` expands
+fn array_reference(word: &str) -> Option<&str> {
+    let name = word.strip_prefix('$')?;
+    let name = name
+        .strip_prefix('{')
+        .and_then(|name| name.strip_suffix('}'))
+        .unwrap_or(name);
+    let name = name
+        .strip_suffix("[@]")
+        .or_else(|| name.strip_suffix("[*]"))
+        .unwrap_or(name);
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        .then_some(name)
+}
+
+fn read_script(text: &str) -> Script {
+    let tokens = tokenize(text);
+    let mut script = Script::default();
+    let mut arrays: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+    // The function being defined and the brace depth its body closes at
+    let mut function: Option<(
+        String,
+        usize,
+    )> = None;
+    let mut braces = 0usize;
+    let mut cases: Vec<Option<String>> = vec![];
+
+    for command in tokens.split(|token| *token == Token::Separator) {
+        let mut rest = command;
+
+        // `name() {` starts a function
+        if let [
+            Token::Word(name),
+            Token::Open,
+            Token::Close,
+            tail @ ..,
+        ] = rest
+        {
+            function = Some(
+                (
+                    name[0].clone(),
+                    braces,
+                ),
+            );
+            rest = tail;
+        } else if let [
+            Token::Word(keyword),
+            Token::Word(name),
+            tail @ ..,
+        ] = rest
+            && keyword[0] == "function"
+        {
+            function = Some(
+                (
+                    name[0].clone(),
+                    braces,
+                ),
+            );
+            rest = tail;
+        }
+
+        // `add)` or `(add|a)` starts a case branch
+        if !cases.is_empty() {
+            let pattern = match rest {
+                [
+                    Token::Open,
+                    Token::Word(pattern),
+                    Token::Close,
+                    tail @ ..,
+                ]
+                | [
+                    Token::Word(pattern),
+                    Token::Close,
+                    tail @ ..,
+                ] => Some(
+                    (
+                        pattern, tail,
+                    ),
+                ),
+                _ => None,
+            };
+            if let Some((pattern, tail)) = pattern {
+                *cases
+                    .last_mut()
+                    .expect("inside a case") = Some(pattern[0].clone());
+                rest = tail;
+            }
+        }
+
+        match rest
+            .first()
+            .and_then(Token::word)
+        {
+            Some("case") => cases.push(None),
+            Some("esac") => {
+                cases.pop();
+            }
+            _ => {}
+        }
+
+        for (i, token) in rest
+            .iter()
+            .enumerate()
+        {
+            let Some(word) = token.word() else {
+                continue;
+            };
+            match word {
+                "{" => braces += 1,
+                "}" => {
+                    braces = braces.saturating_sub(1);
+                    if function
+                        .as_ref()
+                        .is_some_and(|(_, depth)| *depth == braces)
+                    {
+                        function = None;
+                    }
+                }
+                _ => {}
+            }
+
+            // `name=(` or `name+=(` assigns an array
+            if let Some(name) = word
+                .strip_suffix("+=")
+                .or_else(|| word.strip_suffix('='))
+                && rest.get(i + 1) == Some(&Token::Open)
+            {
+                let elements = rest[i + 2..]
+                    .iter()
+                    .take_while(|token| **token != Token::Close)
+                    .filter_map(
+                        |token| match token {
+                            Token::Word(words) => Some(words.clone()),
+                            _ => None,
+                        },
+                    );
+                let array = arrays
+                    .entry(name.to_owned())
+                    .or_default();
+                if !word.ends_with("+=") {
+                    array.clear();
+                }
+                array.extend(elements);
+            }
+
+            let words = || {
+                rest[i + 1..]
+                    .iter()
+                    .filter_map(
+                        |token| match token {
+                            Token::Word(words) => Some(words),
+                            _ => None,
+                        },
+                    )
+            };
+            match word {
+                "_describe" => {
+                    let mut words = words().map(|words| words[0].as_str());
+                    let mut description = None;
+                    let mut names = vec![];
+                    while let Some(word) = words.next() {
+                        match word {
+                            "-t" | "-V" | "-J" | "-x" | "-X" => {
+                                words.next();
+                            }
+                            "-o" | "-O" | "-1" | "-2" | "--" => {}
+                            word if description.is_none() => description = Some(word),
+                            word => names.push(word),
+                        }
+                    }
+                    let is_command = |text: &str| text.contains("command");
+                    if description.is_some_and(is_command)
+                        || names
+                            .iter()
+                            .any(|name| is_command(name))
+                    {
+                        for name in names {
+                            script
+                                .commands
+                                .extend(
+                                    arrays
+                                        .get(name)
+                                        .into_iter()
+                                        .flatten()
+                                        .flatten()
+                                        .cloned(),
+                                );
+                        }
+                    }
+                }
+                "_arguments" => {
+                    let specs = words()
+                        .flat_map(
+                            |words| match array_reference(&words[0]) {
+                                Some(name) => arrays
+                                    .get(name)
+                                    .cloned()
+                                    .unwrap_or_default(),
+                                None => vec![words.clone()],
+                            },
+                        )
+                        .collect();
+                    script
+                        .calls
+                        .push(
+                            ArgumentsCall {
+                                labels: cases
+                                    .iter()
+                                    .rev()
+                                    .flatten()
+                                    .chain(
+                                        function
+                                            .iter()
+                                            .map(|(name, _)| name),
+                                    )
+                                    .cloned()
+                                    .collect(),
+                                specs,
+                            },
+                        );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    script
+}
+
+/// Split at `:` outside of escapes, quotes and parentheses
+fn fields(text: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        let field = fields
+            .last_mut()
+            .expect("one field");
+        match c {
+            '\\' => {
+                field.push(c);
+                field.extend(chars.next());
+            }
+            c if Some(c) == quote => {
+                quote = None;
+                field.push(c);
+            }
+            '\'' | '"' if quote.is_none() => {
+                quote = Some(c);
+                field.push(c);
+            }
+            '(' if quote.is_none() => {
+                depth += 1;
+                field.push(c);
+            }
+            ')' if quote.is_none() => {
+                depth = depth.saturating_sub(1);
+                field.push(c);
+            }
+            ':' if quote.is_none() && depth == 0 => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+
+    fields
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+/// The words of an action like `(a b)` or `((a\:desc b\:desc))`, unquoted
+fn action_words(text: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => word
+                .get_or_insert_with(String::new)
+                .extend(chars.next()),
+            c if Some(c) == quote => quote = None,
+            '\'' | '"' if quote.is_none() => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && quote.is_none() => words.extend(word.take()),
+            c => word
+                .get_or_insert_with(String::new)
+                .push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// An argument named after the spec's message, with what its action completes
+fn value(message: &str, action: &str, fallback: &str) -> ArgumentElement {
+    let name: String = unescape(message)
+        .trim()
+        .trim_start_matches('<')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || " _-".contains(*c))
+        .collect();
+    let name = name
+        .trim()
+        .replace(
+            [
+                ' ', '-',
+            ],
+            "_",
+        )
+        .to_uppercase();
+    let mut argument = argument(
+        match name.is_empty() {
+            true => fallback,
+            false => &name,
+        },
+    );
+
+    let action = action.trim();
+    if let Some(described) = action
+        .strip_prefix("((")
+        .and_then(|action| action.strip_suffix("))"))
+    {
+        // Escaped colons come back as text, so split the words before unescaping
+        argument.accepted_values = non_empty(
+            action_words(
+                &described.replace(
+                    "\\:", "\u{1f}",
+                ),
+            )
+            .into_iter()
+            .map(
+                |word| {
+                    word.split('\u{1f}')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned()
+                },
+            )
+            .collect(),
+        );
+    } else if let Some(values) = action
+        .strip_prefix('(')
+        .and_then(|action| action.strip_suffix(')'))
+    {
+        argument.accepted_values = non_empty(action_words(values));
+    } else {
+        let words = action_words(action);
+        let glob = match words
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [
+                "_directories",
+                ..,
+            ]
+            | [
+                "_files" | "_path_files",
+                "-/",
+                ..,
+            ] => Some("*/".to_owned()),
+            [
+                "_files" | "_path_files",
+                "-g",
+                pattern,
+                ..,
+            ] => Some(pattern.to_string()),
+            [
+                "_files" | "_path_files",
+                ..,
+            ] => Some("*".to_owned()),
+            _ => None,
+        };
+        argument.metadata = glob.map(|glob| vec![glob_metadatum(&glob)]);
+    }
+
+    argument
+}
+
+/// The `:message:action` pairs after an option's name, each optional with `::`
+fn values(text: &str, option: &str) -> Vec<ArgumentElement> {
+    let fields = fields(text);
+    let mut values = vec![];
+    let mut fields = fields
+        .iter()
+        .skip(1)
+        .peekable();
+
+    while let Some(mut message) = fields.next() {
+        let optional = message.is_empty()
+            && fields
+                .peek()
+                .is_some();
+        if optional {
+            message = fields
+                .next()
+                .expect("peeked");
+        }
+        let action = fields
+            .next()
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        let mut value = value(
+            message,
+            action,
+            &value_name(option),
+        );
+        value.required = !optional;
+        if optional {
+            value.arity = Some(
+                Arity {
+                    minimum: Some(0),
+                    maximum: Some(1),
+                },
+            );
+        }
+        values.push(value);
+    }
+
+    values
+}
+
+/// A parsed `-x[description]:message:action` spec
+struct OptionSpec {
+    exclusions: Vec<String>,
+    names: Vec<String>,
+    description: Option<String>,
+    arguments: Vec<ArgumentElement>,
+}
+
+/// An option spec from its brace expanded alternatives
+fn option_spec(alternatives: &[String]) -> Option<OptionSpec> {
+    let mut spec: Option<OptionSpec> = None;
+
+    for alternative in alternatives {
+        let mut text = alternative.as_str();
+        let mut exclusions = vec![];
+        if text.starts_with('(') {
+            let end = text.find(')')?;
+            exclusions = text[1..end]
+                .split_whitespace()
+                .map(String::from)
+                .collect();
+            text = &text[end + 1..];
+        }
+        text = text.trim_start_matches('*');
+        if !text.starts_with(
+            [
+                '-', '+',
+            ],
+        ) {
+            return None;
+        }
+
+        let end = text
+            .find(
+                [
+                    '[', ':',
+                ],
+            )
+            .unwrap_or(text.len());
+        let (name, rest) = text.split_at(end);
+        let name = name
+            .strip_suffix("=-")
+            .or_else(
+                || {
+                    name.strip_suffix(
+                        [
+                            '=', '+',
+                        ],
+                    )
+                },
+            )
+            .or_else(
+                || {
+                    (name.len() > 2)
+                        .then(|| name.strip_suffix('-'))
+                        .flatten()
+                },
+            )
+            .unwrap_or(name);
+
+        let (description, rest) = match rest.strip_prefix('[') {
+            Some(rest) => {
+                let chars: Vec<char> = rest
+                    .chars()
+                    .collect();
+                let end = enclosed(
+                    &chars, 0, '[', ']',
+                );
+                (
+                    Some(
+                        unescape(
+                            &chars[..end]
+                                .iter()
+                                .collect::<String>(),
+                        ),
+                    ),
+                    chars[(end + 1).min(chars.len())..]
+                        .iter()
+                        .collect::<String>(),
+                )
+            }
+            None => (
+                None,
+                rest.to_owned(),
+            ),
+        };
+
+        match &mut spec {
+            Some(spec) => spec
+                .names
+                .push(name.to_owned()),
+            None => {
+                spec = Some(
+                    OptionSpec {
+                        exclusions,
+                        names: vec![name.to_owned()],
+                        description,
+                        arguments: match rest.is_empty() {
+                            true => vec![],
+                            false => values(
+                                &rest, name,
+                            ),
+                        },
+                    },
+                )
+            }
+        }
+    }
+
+    spec
+}
+
+/// A positional spec like `1:message:action`, `:message:action` or `*:message:action`
+///
+/// Returns the position, `None` for the next free one, and the argument.
+fn positional_spec(
+    text: &str,
+    index: usize,
+) -> Option<(
+    Option<usize>,
+    ArgumentElement,
+)> {
+    let (position, rest) = match text.find(':') {
+        Some(end) => text.split_at(end),
+        None => return None,
+    };
+    let variadic = position == "*";
+    let position = match position {
+        "" | "*" => None,
+        number => Some(
+            number
+                .parse::<usize>()
+                .ok()?,
+        ),
+    };
+
+    let optional = rest.starts_with("::");
+    let fields = fields(rest);
+    let fields = &fields[1 + optional as usize..];
+    if variadic && optional {
+        // `*::` hands the remaining words to a subcommand
+        return None;
+    }
+    let message = fields.first()?;
+    let action = fields
+        .get(1)
+        .map(String::as_str)
+        .unwrap_or_default();
+    if action.starts_with("->") {
+        // A state the script completes itself, usually the subcommands
+        return None;
+    }
+
+    let mut argument = value(
+        message,
+        action,
+        &format!(
+            "ARG{}",
+            index + 1
+        ),
+    );
+    argument.required = !optional && !variadic;
+    argument.arity = arity(
+        true, variadic,
+    );
+    Some(
+        (
+            position, argument,
+        ),
+    )
+}
+
+/// Options and positionals of one `_arguments` call
+fn parse_specs(
+    specs: &[Vec<String>],
+) -> (
+    Vec<OptionElement>,
+    Vec<ArgumentElement>,
+) {
+    let mut specs = specs.iter();
+    let mut rest: Vec<&Vec<String>> = vec![];
+    // Leading flags configure `_arguments` itself
+    while let Some(spec) = specs.next() {
+        if ARGUMENTS_VALUE_FLAGS.contains(&spec[0].as_str()) {
+            specs.next();
+        } else if !ARGUMENTS_FLAGS.contains(&spec[0].as_str()) {
+            rest.push(spec);
+            break;
+        }
+    }
+    rest.extend(specs);
+
+    let mut options: Vec<OptionSpec> = vec![];
+    let mut positionals: Vec<(
+        Option<usize>,
+        ArgumentElement,
+    )> = vec![];
+    for spec in rest {
+        let first = spec[0].as_str();
+        let marker = first
+            .strip_prefix(
+                [
+                    '-', '+',
+                ],
+            )
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '));
+        if first.starts_with('!') || marker {
+            // Hidden specs, and `- set` or `+ group` markers
+            continue;
+        }
+        if let Some(option) = option_spec(spec) {
+            options.push(option);
+        } else if let Some(positional) = positional_spec(
+            first,
+            positionals.len(),
+        ) {
+            positionals.push(positional);
+        }
+    }
+
+    positionals.sort_by_key(|(position, _)| position.unwrap_or(usize::MAX));
+    let mut arguments: Vec<ArgumentElement> = vec![];
+    for (_, mut argument) in positionals {
+        if arguments
+            .iter()
+            .any(|other| other.name == argument.name)
+        {
+            argument.name = format!(
+                "{}{}",
+                argument.name,
+                arguments.len() + 1
+            );
+        }
+        arguments.push(argument);
+    }
+
+    (
+        exclusive_groups(options),
+        arguments,
+    )
+}
+
+/// Options from their specs, those excluding each other alike sharing a group
+///
+/// `'(-v --verbose -q --quiet)'{-v,--verbose}` and the same list on `-q` put `--verbose` and
+/// `--quiet` in the group `--verbose | --quiet`.
+fn exclusive_groups(specs: Vec<OptionSpec>) -> Vec<OptionElement> {
+    let primary = |names: &[String]| {
+        names
+            .iter()
+            .find(|name| name.starts_with("--"))
+            .unwrap_or(&names[0])
+            .clone()
+    };
+    fn exclusive(spec: &OptionSpec) -> Option<Vec<&String>> {
+        let mut exclusions: Vec<&String> = spec
+            .exclusions
+            .iter()
+            .filter(|name| !NOT_OPTIONS.contains(&name.as_str()))
+            .collect();
+        let excludes_others = exclusions
+            .iter()
+            .any(
+                |name| {
+                    !spec
+                        .names
+                        .contains(name)
+                },
+            );
+        exclusions.sort();
+        excludes_others.then_some(exclusions)
+    }
+
+    specs
+        .iter()
+        .map(
+            |spec| {
+                let name = primary(&spec.names);
+                let mut option = option(&name);
+                option.aliases = non_empty(
+                    spec.names
+                        .iter()
+                        .filter(|alias| **alias != name)
+                        .cloned()
+                        .collect(),
+                );
+                option.description = spec
+                    .description
+                    .clone();
+                option.arguments = non_empty(
+                    spec.arguments
+                        .clone(),
+                );
+
+                if let Some(exclusions) = exclusive(spec) {
+                    let members: Vec<String> = specs
+                        .iter()
+                        .filter(|other| exclusive(other).as_ref() == Some(&exclusions))
+                        .map(|other| primary(&other.names))
+                        .collect();
+                    if members.len() > 1 {
+                        option.group = Some(members.join(" | "));
+                    }
+                }
+                option
+            },
+        )
+        .collect()
+}
+
+/// Whether an `_arguments` call found under `label` describes subcommand `name`
+fn labels_command(label: &str, name: &str) -> bool {
+    label
+        .split('|')
+        .any(|pattern| pattern == name)
+        || label.ends_with(
+            &format!(
+                "_{}",
+                name
+            ),
+        )
+        || label.ends_with(
+            &format!(
+                "-{}",
+                name
+            ),
+        )
+}
+
+/// The program a `#compdef` line completes
+pub fn compdef_program(text: &str) -> Option<&str> {
+    text.lines()
+        .next()?
+        .strip_prefix("#compdef")?
+        .split_whitespace()
+        .find(|word| !word.starts_with('-'))
+}
+
+/// Build the spec for `program` from a zsh completion function
+pub fn parse_zsh(program: &str, text: &str) -> V0_1 {
+    let script = read_script(text);
+
+    let mut commands: Vec<CommandElement> = vec![];
+    for entry in &script.commands {
+        let fields = fields(entry);
+        let name = unescape(&fields[0]);
+        if name.is_empty() || name == program {
+            continue;
+        }
+        let description = fields[1..]
+            .iter()
+            .map(|field| unescape(field))
+            .collect::<Vec<_>>()
+            .join(":");
+        let command = command_mut(
+            &mut commands,
+            &name,
+        );
+        command.description = command
+            .description
+            .take()
+            .or(Some(description).filter(|description| !description.is_empty()));
+    }
+
+    // Calls not placed in a subcommand describe the program, the largest one wins
+    let mut own: Option<&ArgumentsCall> = None;
+    for call in &script.calls {
+        let subcommand = call
+            .labels
+            .iter()
+            .find_map(
+                |label| {
+                    commands
+                        .iter()
+                        .position(
+                            |command| {
+                                labels_command(
+                                    label,
+                                    &command.name,
+                                )
+                            },
+                        )
+                },
+            );
+        match subcommand {
+            Some(index) => {
+                let (options, arguments) = parse_specs(&call.specs);
+                let command = &mut commands[index];
+                for option in options {
+                    merge_option(
+                        command
+                            .options
+                            .get_or_insert_with(Vec::new),
+                        option,
+                    );
+                }
+                if command
+                    .arguments
+                    .is_none()
+                {
+                    command.arguments = non_empty(arguments);
+                }
+            }
+            None => {
+                if own.is_none_or(
+                    |own| {
+                        call.specs
+                            .len()
+                            > own
+                                .specs
+                                .len()
+                    },
+                ) {
+                    own = Some(call);
+                }
+            }
+        }
+    }
+
+    let (mut options, arguments) = own
+        .map(|call| parse_specs(&call.specs))
+        .unwrap_or_default();
+    drop_inherited(
+        &mut options,
+        &mut commands,
+    );
+
+    let mut spec = spec(
+        program, "unknown",
+    );
+    spec.conventions = conventions(&options);
+    spec.options = non_empty(options);
+    spec.arguments = non_empty(arguments);
+    spec.commands = non_empty(commands);
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opencli::operations::find_option;
+
+    fn sample() -> V0_1 {
+        let text = include_str!("fixtures/zsh-_backup");
+        parse_zsh(
+            compdef_program(text).unwrap(),
+            text,
+        )
+    }
+
+    #[test]
+    fn test_tokenize_expands_braces() {
+        assert_eq!(
+            tokenize("_arguments '(-v)'{-v,--verbose}'[be loud]' && x=(a b)"),
+            vec![
+                Token::Word(vec!["_arguments".to_string()]),
+                Token::Word(
+                    vec![
+                        "(-v)-v[be loud]".to_string(),
+                        "(-v)--verbose[be loud]".to_string()
+                    ]
+                ),
+                Token::Separator,
+                Token::Word(vec!["x=".to_string()]),
+                Token::Open,
+                Token::Word(vec!["a".to_string()]),
+                Token::Word(vec!["b".to_string()]),
+                Token::Close,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_options_values_and_groups() {
+        let spec = sample();
+
+        let verbose = find_option(
+            &spec.options,
+            "-v",
+        )
+        .unwrap();
+        assert_eq!(
+            verbose.name,
+            "--verbose"
+        );
+        assert_eq!(
+            verbose
+                .group
+                .as_deref(),
+            Some("--verbose | --quiet")
+        );
+        assert!(
+            find_option(
+                &spec.options,
+                "--output"
+            )
+            .is_some_and(
+                |output| output
+                    .group
+                    .is_none()
+            )
+        );
+
+        let compress = find_option(
+            &spec.options,
+            "--compress",
+        )
+        .unwrap();
+        assert_eq!(
+            compress
+                .description
+                .as_deref(),
+            Some("compression level [default: fast]")
+        );
+        let level = &compress
+            .arguments
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(
+            level.accepted_values,
+            Some(
+                vec![
+                    "fast".to_string(),
+                    "best".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            level.min_values(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_subcommands_from_describe_and_case_branches() {
+        let spec = sample();
+        let commands = spec
+            .commands
+            .unwrap();
+
+        assert_eq!(
+            commands
+                .iter()
+                .map(
+                    |command| command
+                        .name
+                        .as_str()
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                "restore", "list"
+            ]
+        );
+
+        // From the `_backup_restore` function, without the inherited --help
+        let restore = &commands[0];
+        assert!(
+            find_option(
+                &restore.options,
+                "--force"
+            )
+            .is_some()
+        );
+        assert!(
+            find_option(
+                &restore.options,
+                "--help"
+            )
+            .is_none()
+        );
+        let arguments = restore
+            .arguments
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            arguments
+                .iter()
+                .map(
+                    |argument| (
+                        argument
+                            .name
+                            .as_str(),
+                        argument.min_values(),
+                        argument.max_values()
+                    )
+                )
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "ARCHIVE",
+                    1,
+                    Some(1)
+                ),
+                (
+                    "PATHS", 0, None
+                )
+            ]
+        );
+
+        // From the `list)` case branch
+        assert!(
+            find_option(
+                &commands[1].options,
+                "--format"
+            )
+            .is_some()
+        );
+        assert!(
+            spec.arguments
+                .is_none()
+        );
+    }
+}
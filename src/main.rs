@@ -1,13 +1,16 @@
 use crate::app::App;
-use crate::config::{Cli, load};
-use crate::opencli::v0_1::V0_1;
+use crate::config::{Cli, CliCommand, SpecCommand, load};
+use crate::opencli::registry::SpecRegistry;
 use crate::screens::builder_screen::model::BuilderScreen;
+use crate::shell::Shell;
 use clap::Parser;
+use color_eyre::eyre::WrapErr;
 use directories::ProjectDirs;
 use ratatui::crossterm::execute;
 use ratatui::crossterm::terminal::LeaveAlternateScreen;
 use std::io::stdout;
 use std::panic::{set_hook, take_hook};
+use std::path::Path;
 
 #[macro_use]
 extern crate rust_i18n;
@@ -21,8 +24,10 @@ mod app;
 mod builder;
 mod config;
 pub mod event;
+mod import;
 mod opencli;
 mod screens;
+mod shell;
 mod theme;
 mod util;
 
@@ -33,7 +38,119 @@ use log4rs::append::file::FileAppender;
 use log4rs::config::{Appender, Root};
 use log4rs::encode::pattern::PatternEncoder;
 
-const DEMO_YAML: &str = include_str!("../src/opencli/demo-kubectl.yaml");
+/// Run a `cmdi spec` subcommand
+fn spec_command(command: &SpecCommand, directories: &ProjectDirs) -> color_eyre::Result<()> {
+    match command {
+        SpecCommand::ImportHelp {
+            program,
+            depth,
+            output,
+            force,
+        } => {
+            let spec = import::help::import_help(
+                program, *depth,
+            )?;
+            let path = import::write_spec(
+                &spec,
+                output.as_deref(),
+                directories,
+                *force,
+            )?;
+            println!(
+                "Wrote {}",
+                path.display()
+            );
+        }
+        SpecCommand::ImportMan {
+            page,
+            output,
+            force,
+        } => {
+            let spec = import::man::import_man(page)?;
+            let path = import::write_spec(
+                &spec,
+                output.as_deref(),
+                directories,
+                *force,
+            )?;
+            println!(
+                "Wrote {}",
+                path.display()
+            );
+        }
+        SpecCommand::ImportCompletion {
+            program,
+            output,
+            force,
+        } => {
+            let file = Path::new(program);
+            let spec = match file.is_file() {
+                true => import::completion::import_file(file)?,
+                false => import::completion::import_installed(program)?,
+            };
+            let path = import::write_spec(
+                &spec,
+                output.as_deref(),
+                directories,
+                *force,
+            )?;
+            println!(
+                "Wrote {}",
+                path.display()
+            );
+        }
+        SpecCommand::Completions {
+            program,
+            shell,
+            output,
+        } => {
+            let spec = SpecRegistry::new(directories).load(program)?;
+            let script = opencli::completion_script::completion_script(
+                &spec,
+                shell.unwrap_or_else(Shell::detect),
+            )?;
+            match output {
+                Some(path) => std::fs::write(
+                    path, script,
+                )?,
+                None => print!(
+                    "{}",
+                    script
+                ),
+            }
+        }
+        SpecCommand::Docs {
+            program,
+            format,
+            output,
+        } => {
+            let spec = SpecRegistry::new(directories).load(program)?;
+            std::fs::create_dir_all(output)?;
+            for page in opencli::docs::pages(
+                &spec, *format,
+            )? {
+                let path = output.join(&page.file_name);
+                std::fs::write(
+                    &path, page.text,
+                )
+                .wrap_err_with(
+                    || {
+                        format!(
+                            "Failed to write page '{}'",
+                            path.display()
+                        )
+                    },
+                )?;
+                println!(
+                    "Wrote {}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
 
 fn main() -> color_eyre::Result<()> {
     let logfile = FileAppender::builder()
@@ -66,17 +183,49 @@ fn main() -> color_eyre::Result<()> {
     )
     .expect("Failed to get project directories");
     let cli = Cli::parse();
-    let _settings = load(
+    match &cli.command {
+        Some(CliCommand::Init {
+            shell,
+        }) => {
+            print!(
+                "{}",
+                shell.init_script()?
+            );
+            return Ok(());
+        }
+        Some(CliCommand::Spec {
+            command,
+        }) => {
+            return spec_command(
+                command,
+                &directories,
+            );
+        }
+        None => {}
+    }
+
+    let (cmd, args) = cli.command_line()?;
+    let registry = SpecRegistry::new(&directories);
+    let settings = load(
         &cli,
         directories,
     )?;
 
     info!(
         "Build for {}",
-        cli.cmd
+        cmd
     );
 
-    let parsed: V0_1 = serde_yml::from_str(DEMO_YAML)?;
+    let spec = registry.load(&cmd)?;
+    let initial_screen = if cli.demo {
+        BuilderScreen::demo(
+            spec, settings,
+        )
+    } else {
+        BuilderScreen::new(
+            spec, &args, settings,
+        )
+    };
 
     // TODO trap SIGTERM see signal-hook
     // Add panic hook
@@ -91,10 +240,7 @@ fn main() -> color_eyre::Result<()> {
     );
 
     let terminal = ratatui::init();
-    let result = App::new(BuilderScreen::demo(parsed))
-        .run(
-        terminal,
-    );
+    let result = App::new(initial_screen).run(terminal);
 
     execute!(
         stdout(),
@@ -103,10 +249,16 @@ fn main() -> color_eyre::Result<()> {
     ratatui::restore();
 
     match result {
-        Ok(result_str) => println!(
-            "{}",
-            result_str
-        ),
+        // The shell widgets read the command back from a file as the TUI owns stdout
+        Ok(result_str) => match &cli.output {
+            Some(path) => std::fs::write(
+                path, result_str,
+            )?,
+            None => println!(
+                "{}",
+                result_str
+            ),
+        },
         Err(msg) => println!(
             "Exiting - {}",
             msg
@@ -0,0 +1,457 @@
+//! Dynamic value completion declared in spec metadata
+//!
+//! An argument or option opts in with a `cmdi.completion` metadatum whose value is one of
+//!
+//! ```yaml
+//! metadata:
+//!   - name: cmdi.completion
+//!     value: { command: "kubectl get namespaces -o name", timeout: 2000 }
+//!   # or { values: [json, yaml] }
+//!   # or { glob: "*.yaml" }, a trailing `/` lists directories only
+//! ```
+//!
+//! Candidates are computed once per session and cached.
+
+use crate::opencli::v0_1::{ArgumentElement, MetadatumElement, OptionElement};
+use crate::util::process::output_with_timeout;
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// Name of the metadatum declaring a completion provider
+pub const COMPLETION_METADATUM: &str = "cmdi.completion";
+
+/// Milliseconds a provider command may run before it is killed
+const DEFAULT_TIMEOUT: u64 = 2000;
+
+/// Where candidate values for an argument come from
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(untagged)]
+pub enum CompletionProvider {
+    /// A shell command printing one candidate per line
+    Command {
+        command: String,
+        /// Milliseconds before the command is given up on
+        #[serde(default = "default_timeout")]
+        timeout: u64,
+    },
+    /// A fixed list
+    Values {
+        values: Vec<String>,
+    },
+    /// Paths matching a `*`/`?` pattern
+    Glob {
+        glob: String,
+    },
+}
+
+fn default_timeout() -> u64 {
+    DEFAULT_TIMEOUT
+}
+
+fn cache() -> &'static Mutex<HashMap<CompletionProvider, Vec<String>>> {
+    static CACHE: OnceLock<Mutex<HashMap<CompletionProvider, Vec<String>>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl CompletionProvider {
+    /// Find and parse the provider among `metadata`
+    pub fn from_metadata(metadata: &Option<Vec<MetadatumElement>>) -> Option<Self> {
+        let value = metadata
+            .iter()
+            .flatten()
+            .find(|metadatum| metadatum.name == COMPLETION_METADATUM)?
+            .value
+            .clone()?;
+
+        serde_json::from_value(value)
+            .inspect_err(
+                |e| {
+                    log::warn!(
+                        "Ignoring malformed {}: {}",
+                        COMPLETION_METADATUM,
+                        e
+                    )
+                },
+            )
+            .ok()
+    }
+
+    /// Candidate values, from the session cache when the provider already ran
+    ///
+    /// Failures are not cached so a provider that timed out is tried again next time.
+    pub fn candidates(&self) -> color_eyre::Result<Vec<String>> {
+        if let Some(cached) = cache()
+            .lock()
+            .map_err(|_| eyre!("Completion cache poisoned"))?
+            .get(self)
+        {
+            return Ok(cached.clone());
+        }
+
+        let candidates = match self {
+            CompletionProvider::Command {
+                command,
+                timeout,
+            } => run_provider(
+                command,
+                Duration::from_millis(*timeout),
+            )?,
+            CompletionProvider::Values {
+                values,
+            } => values.clone(),
+            CompletionProvider::Glob {
+                glob,
+            } => glob_paths(glob)?,
+        };
+
+        cache()
+            .lock()
+            .map_err(|_| eyre!("Completion cache poisoned"))?
+            .insert(
+                self.clone(),
+                candidates.clone(),
+            );
+        Ok(candidates)
+    }
+}
+
+impl ArgumentElement {
+    /// The argument's completion provider, if its metadata declares one
+    pub fn completion(&self) -> Option<CompletionProvider> {
+        CompletionProvider::from_metadata(&self.metadata)
+    }
+}
+
+impl OptionElement {
+    /// The option's completion provider, declared on the option or its first argument
+    pub fn completion(&self) -> Option<CompletionProvider> {
+        CompletionProvider::from_metadata(&self.metadata).or_else(
+            || {
+                self.arguments
+                    .iter()
+                    .flatten()
+                    .next()
+                    .and_then(ArgumentElement::completion)
+            },
+        )
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(
+            [
+                "/C", command,
+            ],
+        );
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(
+            [
+                "-c", command,
+            ],
+        );
+        cmd
+    }
+}
+
+/// Run `command` and collect its non-empty output lines, killing it after `timeout`
+fn run_provider(command: &str, timeout: Duration) -> color_eyre::Result<Vec<String>> {
+    let output = output_with_timeout(
+        shell_command(command),
+        timeout,
+    )
+    .wrap_err_with(
+        || {
+            format!(
+                "Completion provider `{}` did not finish",
+                command
+            )
+        },
+    )?;
+    if !output
+        .status
+        .success()
+    {
+        bail!(
+            "Completion provider `{}` failed with {}",
+            command,
+            output.status
+        );
+    }
+
+    Ok(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Paths matching `pattern`, wildcards are only allowed in the last component
+fn glob_paths(pattern: &str) -> color_eyre::Result<Vec<String>> {
+    let (pattern, dirs_only) = match pattern.strip_suffix('/') {
+        Some(pattern) => (
+            pattern, true,
+        ),
+        None => (
+            pattern, false,
+        ),
+    };
+    let (dir, name_pattern) = match pattern.rfind('/') {
+        Some(i) => (
+            &pattern[..=i],
+            &pattern[i + 1..],
+        ),
+        None => (
+            "", pattern,
+        ),
+    };
+
+    let search = if dir.is_empty() {
+        Path::new(".")
+    } else {
+        Path::new(dir)
+    };
+
+    let mut paths: Vec<String> = std::fs::read_dir(search)?
+        .filter_map(Result::ok)
+        .filter_map(
+            |entry| {
+                let name = entry
+                    .file_name()
+                    .to_string_lossy()
+                    .into_owned();
+                let is_dir = entry
+                    .file_type()
+                    .is_ok_and(|file_type| file_type.is_dir());
+                let hidden = name.starts_with('.') && !name_pattern.starts_with('.');
+
+                (!hidden
+                    && (is_dir || !dirs_only)
+                    && wildcard_match(
+                        name_pattern,
+                        &name,
+                    ))
+                .then(
+                    || {
+                        format!(
+                            "{}{}{}",
+                            dir,
+                            name,
+                            if is_dir {
+                                "/"
+                            } else {
+                                ""
+                            }
+                        )
+                    },
+                )
+            },
+        )
+        .collect();
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Shell style `*` and `?` matching over whole names
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .collect();
+    let name: Vec<char> = name
+        .chars()
+        .collect();
+
+    // matches[j]: pattern so far matches name[..j]
+    let mut matches = vec![false; name.len() + 1];
+    matches[0] = true;
+    for p in pattern {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '*' => matches[j] || (j > 0 && next[j - 1]),
+                '?' => j > 0 && matches[j - 1],
+                c => j > 0 && matches[j - 1] && name[j - 1] == c,
+            };
+        }
+        matches = next;
+    }
+
+    matches[name.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs;
+    use std::time::Instant;
+
+    fn provider(yaml: &str) -> Option<CompletionProvider> {
+        let argument: ArgumentElement = serde_yml::from_str(yaml).unwrap();
+        argument.completion()
+    }
+
+    #[test]
+    fn test_provider_kinds_from_metadata() {
+        assert_eq!(
+            provider(
+                r#"
+name: namespace
+metadata:
+  - name: cmdi.completion
+    value:
+      command: kubectl get namespaces -o name
+"#
+            ),
+            Some(
+                CompletionProvider::Command {
+                    command: "kubectl get namespaces -o name".to_string(),
+                    timeout: DEFAULT_TIMEOUT,
+                }
+            )
+        );
+        assert_eq!(
+            provider(
+                r#"
+name: format
+metadata:
+  - name: cmdi.completion
+    value:
+      values: [json, yaml]
+"#
+            ),
+            Some(
+                CompletionProvider::Values {
+                    values: vec![
+                        "json".to_string(),
+                        "yaml".to_string()
+                    ],
+                }
+            )
+        );
+        assert_eq!(
+            provider("name: plain"),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_output_and_timeout() {
+        assert_eq!(
+            run_provider(
+                "printf 'default\\n\\nkube-system\\n'",
+                Duration::from_secs(5)
+            )
+            .unwrap(),
+            vec![
+                "default",
+                "kube-system"
+            ]
+        );
+
+        let started = Instant::now();
+        assert!(
+            run_provider(
+                "sleep 5",
+                Duration::from_millis(100)
+            )
+            .is_err()
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        // A program left in the background keeps the output open past the provider's exit
+        let started = Instant::now();
+        assert!(
+            run_provider(
+                "sleep 5 & echo default",
+                Duration::from_millis(300)
+            )
+            .is_err()
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_glob_matches_names() {
+        let dir = temp_dir().join(
+            format!(
+                "cmdi-completion-{}",
+                std::process::id()
+            ),
+        );
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("charts")).unwrap();
+        fs::write(
+            dir.join("deploy.yaml"),
+            "",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("notes.txt"),
+            "",
+        )
+        .unwrap();
+        let base = format!(
+            "{}/",
+            dir.display()
+        );
+
+        assert_eq!(
+            glob_paths(
+                &format!(
+                    "{}*.yaml",
+                    base
+                )
+            )
+            .unwrap(),
+            vec![
+                format!(
+                    "{}deploy.yaml",
+                    base
+                )
+            ]
+        );
+        assert_eq!(
+            glob_paths(
+                &format!(
+                    "{}*/",
+                    base
+                )
+            )
+            .unwrap(),
+            vec![
+                format!(
+                    "{}charts/",
+                    base
+                )
+            ]
+        );
+        assert!(
+            wildcard_match(
+                "de?loy.*",
+                "deploy.yaml"
+            )
+        );
+        assert!(
+            !wildcard_match(
+                "*.yml",
+                "deploy.yaml"
+            )
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,1126 @@
+//! Shell completion scripts generated from a spec
+//!
+//! The scripts are static: they follow the command path with the names and aliases in the spec,
+//! and only run the programs of `cmdi.completion` commands at completion time.
+
+use crate::opencli::completion::CompletionProvider;
+use crate::opencli::operations::CommandScope;
+use crate::opencli::v0_1::{ArgumentElement, CommandElement, OptionElement, V0_1};
+use crate::shell::{FishQuoter, PosixQuoter, Quoter, Shell};
+use color_eyre::eyre::bail;
+use std::fmt::Write;
+
+/// What to offer for an argument's value
+#[derive(Debug, PartialEq)]
+enum Values {
+    Words(Vec<String>),
+    /// Paths, only those matching the glob when given
+    Files(Option<String>),
+    Directories,
+    /// A shell command printing one value per line
+    Command(String),
+}
+
+impl Values {
+    fn of_argument(argument: &ArgumentElement) -> Option<Self> {
+        match &argument.accepted_values {
+            Some(values) => Some(Values::Words(values.clone())),
+            None => argument
+                .completion()
+                .map(Values::from),
+        }
+    }
+
+    fn of_option(option: &OptionElement) -> Option<Self> {
+        option
+            .arguments
+            .iter()
+            .flatten()
+            .next()
+            .and_then(
+                |argument| {
+                    argument
+                        .accepted_values
+                        .clone()
+                },
+            )
+            .map(Values::Words)
+            .or_else(
+                || {
+                    option
+                        .completion()
+                        .map(Values::from)
+                },
+            )
+    }
+}
+
+impl From<CompletionProvider> for Values {
+    fn from(provider: CompletionProvider) -> Self {
+        match provider {
+            CompletionProvider::Command {
+                command,
+                ..
+            } => Values::Command(command),
+            CompletionProvider::Values {
+                values,
+            } => Values::Words(values),
+            CompletionProvider::Glob {
+                glob,
+            } if glob.ends_with('/') => Values::Directories,
+            CompletionProvider::Glob {
+                glob,
+            } if glob == "*" => Values::Files(None),
+            CompletionProvider::Glob {
+                glob,
+            } => Values::Files(Some(glob)),
+        }
+    }
+}
+
+fn names<'a>(name: &'a str, aliases: &'a Option<Vec<String>>) -> Vec<&'a str> {
+    std::iter::once(name)
+        .chain(
+            aliases
+                .iter()
+                .flatten()
+                .map(String::as_str),
+        )
+        .collect()
+}
+
+fn option_names(option: &OptionElement) -> Vec<&str> {
+    names(
+        &option.name,
+        &option.aliases,
+    )
+}
+
+fn command_names(command: &CommandElement) -> Vec<&str> {
+    names(
+        &command.name,
+        &command.aliases,
+    )
+}
+
+fn takes_value(option: &OptionElement) -> bool {
+    option
+        .arguments
+        .as_ref()
+        .is_some_and(|arguments| !arguments.is_empty())
+}
+
+/// The first line of a description, as completion menus show one line per candidate
+fn summary(description: &Option<String>) -> Option<&str> {
+    description
+        .as_deref()?
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+}
+
+/// A name usable in shell function names
+fn identifier(name: &str) -> String {
+    name.chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+/// The completion script for `spec` in `shell`'s language
+pub fn completion_script(spec: &V0_1, shell: Shell) -> color_eyre::Result<String> {
+    let scopes = spec.visible_scopes();
+    match shell {
+        Shell::Bash => Ok(
+            bash(
+                spec, &scopes,
+            ),
+        ),
+        Shell::Zsh => Ok(
+            zsh(
+                spec, &scopes,
+            ),
+        ),
+        Shell::Fish => Ok(
+            fish(
+                spec, &scopes,
+            ),
+        ),
+        Shell::PowerShell => bail!(
+            "No completion script for {} yet",
+            shell
+        ),
+    }
+}
+
+/// `case` patterns matching `path:name` for each name
+fn bash_patterns(path: &str, names: &[&str]) -> String {
+    names
+        .iter()
+        .map(
+            |name| {
+                PosixQuoter.quote(
+                    &format!(
+                        "{}:{}",
+                        path, name
+                    ),
+                )
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn bash_reply(values: &Values) -> String {
+    match values {
+        Values::Words(words) => format!(
+            "COMPREPLY=($(compgen -W {} -- \"$cur\"))",
+            PosixQuoter.quote(&words.join(" "))
+        ),
+        Values::Files(None) => {
+            "compopt -o filenames 2>/dev/null; COMPREPLY=($(compgen -f -- \"$cur\"))".to_string()
+        }
+        Values::Files(Some(glob)) => format!(
+            "compopt -o filenames 2>/dev/null; COMPREPLY=($(compgen -f -X {} -- \"$cur\") $(compgen -d -- \"$cur\"))",
+            PosixQuoter.quote(
+                &format!(
+                    "!{}",
+                    glob
+                )
+            )
+        ),
+        Values::Directories => {
+            "compopt -o filenames 2>/dev/null; COMPREPLY=($(compgen -d -- \"$cur\"))".to_string()
+        }
+        Values::Command(command) => format!(
+            "COMPREPLY=($(compgen -W \"$({} 2>/dev/null)\" -- \"$cur\"))",
+            command
+        ),
+    }
+}
+
+/// A bash function walking the words before the cursor to find the command and position
+fn bash(spec: &V0_1, scopes: &[CommandScope]) -> String {
+    let program = &spec
+        .info
+        .title;
+    let function = format!(
+        "_{}_completion",
+        identifier(program)
+    );
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "# bash completion for {}, generated by cmdi from its OpenCLI spec\n",
+        program
+    );
+    let _ = writeln!(
+        out,
+        "{}() {{",
+        function
+    );
+    out.push_str("    local cur=${COMP_WORDS[COMP_CWORD]} prev=${COMP_WORDS[COMP_CWORD-1]}\n");
+    let _ = writeln!(
+        out,
+        "    local path={} position=0 word i\n",
+        PosixQuoter.quote(program)
+    );
+
+    // The command the words lead to and how many positional values they hold
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        word=${COMP_WORDS[i]}\n");
+    out.push_str("        case \"$path:$word\" in\n");
+    for scope in scopes {
+        let path = scope.path();
+        for command in &scope.commands {
+            let _ = writeln!(
+                out,
+                "            {})\n                path={}\n                position=0\n                ;;",
+                bash_patterns(
+                    &path,
+                    &command_names(command)
+                ),
+                PosixQuoter.quote(
+                    &format!(
+                        "{} {}",
+                        path, command.name
+                    )
+                )
+            );
+        }
+        let valued: Vec<&str> = scope
+            .usable_options()
+            .filter(|option| takes_value(option))
+            .flat_map(|option| option_names(option))
+            .collect();
+        if !valued.is_empty() {
+            let _ = writeln!(
+                out,
+                "            {})\n                ((i++))\n                ;;",
+                bash_patterns(
+                    &path, &valued
+                )
+            );
+        }
+    }
+    out.push_str("            *:-*) ;;\n");
+    out.push_str("            *) ((position++)) ;;\n");
+    out.push_str("        esac\n");
+    out.push_str("    done\n\n");
+
+    // The value of the option before the cursor
+    out.push_str("    case \"$path:$prev\" in\n");
+    for scope in scopes {
+        let path = scope.path();
+        for option in scope
+            .usable_options()
+            .filter(|option| takes_value(option))
+        {
+            let reply = Values::of_option(option)
+                .map(|values| bash_reply(&values))
+                .unwrap_or("COMPREPLY=()".to_string());
+            let _ = writeln!(
+                out,
+                "        {})\n            {}\n            return\n            ;;",
+                bash_patterns(
+                    &path,
+                    &option_names(option)
+                ),
+                reply
+            );
+        }
+    }
+    out.push_str("    esac\n\n");
+
+    out.push_str("    if [[ $cur == -* ]]; then\n");
+    out.push_str("        case \"$path\" in\n");
+    for scope in scopes {
+        let options: Vec<String> = scope
+            .usable_options()
+            .flat_map(|option| option_names(option))
+            .map(str::to_string)
+            .collect();
+        if !options.is_empty() {
+            let _ = writeln!(
+                out,
+                "            {}) {} ;;",
+                PosixQuoter.quote(&scope.path()),
+                bash_reply(&Values::Words(options))
+            );
+        }
+    }
+    out.push_str("        esac\n");
+    out.push_str("        return\n");
+    out.push_str("    fi\n\n");
+
+    // Subcommands, or the values of positional arguments
+    out.push_str("    case \"$path:$position\" in\n");
+    for scope in scopes {
+        let path = scope.path();
+        if !scope
+            .commands
+            .is_empty()
+        {
+            let commands: Vec<String> = scope
+                .commands
+                .iter()
+                .flat_map(|command| command_names(command))
+                .map(str::to_string)
+                .collect();
+            let _ = writeln!(
+                out,
+                "        {}) {} ;;",
+                PosixQuoter.quote(
+                    &format!(
+                        "{}:0",
+                        path
+                    )
+                ),
+                bash_reply(&Values::Words(commands))
+            );
+            continue;
+        }
+
+        let mut position = 0;
+        for argument in &scope.arguments {
+            let values = Values::of_argument(argument);
+            let Some(count) = argument.max_values() else {
+                // Takes every remaining position
+                if let Some(values) = values {
+                    let _ = writeln!(
+                        out,
+                        "        {}*) {} ;;",
+                        PosixQuoter.quote(
+                            &format!(
+                                "{}:",
+                                path
+                            )
+                        ),
+                        bash_reply(&values)
+                    );
+                }
+                break;
+            };
+            if let Some(values) = values {
+                let patterns: Vec<String> = (position..position + count)
+                    .map(|position| position.to_string())
+                    .collect();
+                let _ = writeln!(
+                    out,
+                    "        {}) {} ;;",
+                    bash_patterns(
+                        &path,
+                        &patterns
+                            .iter()
+                            .map(String::as_str)
+                            .collect::<Vec<_>>()
+                    ),
+                    bash_reply(&values)
+                );
+            }
+            position += count;
+        }
+    }
+    out.push_str("    esac\n");
+    out.push_str("}\n\n");
+
+    let _ = writeln!(
+        out,
+        "complete -o bashdefault -o default -F {} {}",
+        function,
+        PosixQuoter.quote(program)
+    );
+    out
+}
+
+/// Escape the characters `_arguments` and `_describe` give a meaning to
+fn zsh_escape(text: &str, special: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if c == '\\' || special.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn zsh_action(values: Option<Values>) -> String {
+    match values {
+        None => String::new(),
+        Some(Values::Words(words)) => format!(
+            "({})",
+            words
+                .iter()
+                .map(
+                    |word| zsh_escape(
+                        word, " ():"
+                    )
+                )
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        Some(Values::Files(None)) => "_files".to_string(),
+        Some(Values::Files(Some(glob))) => format!(
+            "_files -g \"{}\"",
+            glob
+        ),
+        Some(Values::Directories) => "_files -/".to_string(),
+        Some(Values::Command(command)) => format!(
+            "{{local -a values; values=(${{(f)\"$({} 2>/dev/null)\"}}); compadd -a values}}",
+            command
+        ),
+    }
+}
+
+/// `:message:action` for each of the option's values
+///
+/// The values are never optional, as the word after the option is always taken as its value.
+fn zsh_option_values(option: &OptionElement) -> String {
+    let mut specs = String::new();
+    for (index, argument) in option
+        .arguments
+        .iter()
+        .flatten()
+        .enumerate()
+    {
+        let values = match index {
+            0 => Values::of_option(option),
+            _ => Values::of_argument(argument),
+        };
+        let _ = write!(
+            specs,
+            ":{}:{}",
+            zsh_escape(
+                &argument.name,
+                ":"
+            ),
+            zsh_action(values)
+        );
+    }
+    specs
+}
+
+/// One `_arguments` spec per name, each excluding the option's other names
+fn zsh_option_specs(option: &OptionElement) -> Vec<String> {
+    let names = option_names(option);
+    let exclusions = match names.len() {
+        1 => String::new(),
+        _ => format!(
+            "({})",
+            names.join(" ")
+        ),
+    };
+    let description = summary(&option.description)
+        .map(
+            |description| {
+                format!(
+                    "[{}]",
+                    zsh_escape(
+                        description,
+                        "[]"
+                    )
+                )
+            },
+        )
+        .unwrap_or_default();
+    let values = zsh_option_values(option);
+
+    names
+        .iter()
+        .map(
+            |name| {
+                format!(
+                    "{}{}{}{}",
+                    exclusions, name, description, values
+                )
+            },
+        )
+        .collect()
+}
+
+fn zsh_argument_specs(arguments: &[&ArgumentElement]) -> Vec<String> {
+    let mut specs = vec![];
+    let mut position = 1;
+    for argument in arguments {
+        let message = zsh_escape(
+            &argument.name,
+            ":",
+        );
+        let action = zsh_action(Values::of_argument(argument));
+        let Some(count) = argument.max_values() else {
+            specs.push(
+                format!(
+                    "*:{}:{}",
+                    message, action
+                ),
+            );
+            break;
+        };
+        for offset in 0..count {
+            let optional = match offset < argument.min_values() {
+                true => "",
+                false => ":",
+            };
+            specs.push(
+                format!(
+                    "{}:{}{}:{}",
+                    position + offset,
+                    optional,
+                    message,
+                    action
+                ),
+            );
+        }
+        position += count;
+    }
+    specs
+}
+
+/// A zsh function per command, each handing the words after a subcommand to that command's
+fn zsh(spec: &V0_1, scopes: &[CommandScope]) -> String {
+    let program = &spec
+        .info
+        .title;
+    let function = |path: &[&str]| {
+        format!(
+            "_{}",
+            path.iter()
+                .map(|name| identifier(name))
+                .collect::<Vec<_>>()
+                .join("_")
+        )
+    };
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "#compdef {}\n# zsh completion for {}, generated by cmdi from its OpenCLI spec",
+        program, program
+    );
+    for scope in scopes {
+        let mut specs: Vec<String> = scope
+            .usable_options()
+            .flat_map(zsh_option_specs)
+            .collect();
+        let has_commands = !scope
+            .commands
+            .is_empty();
+        match has_commands {
+            true => specs.extend(
+                [
+                    "1: :->command".to_string(),
+                    "*:: :->args".to_string(),
+                ],
+            ),
+            false => specs.extend(zsh_argument_specs(&scope.arguments)),
+        }
+
+        let _ = writeln!(
+            out,
+            "\n{}() {{",
+            function(&scope.path)
+        );
+        if has_commands {
+            out.push_str("    local curcontext=\"$curcontext\" state line\n");
+        }
+        let _ = write!(
+            out,
+            "    _arguments{}{}",
+            match spec.groups_short_options() {
+                true => " -s",
+                false => "",
+            },
+            match has_commands {
+                true => " -C",
+                false => "",
+            }
+        );
+        for spec in &specs {
+            let _ = write!(
+                out,
+                " \\\n        {}",
+                PosixQuoter.quote(spec)
+            );
+        }
+        out.push('\n');
+
+        if has_commands {
+            out.push_str("    case $state in\n");
+            out.push_str("        command)\n");
+            out.push_str("            local -a commands=(\n");
+            for command in &scope.commands {
+                let description = summary(&command.description).unwrap_or_default();
+                for name in command_names(command) {
+                    let entry = match description.is_empty() {
+                        true => zsh_escape(
+                            name, ":",
+                        ),
+                        false => format!(
+                            "{}:{}",
+                            zsh_escape(
+                                name, ":"
+                            ),
+                            description
+                        ),
+                    };
+                    let _ = writeln!(
+                        out,
+                        "                {}",
+                        PosixQuoter.quote(&entry)
+                    );
+                }
+            }
+            out.push_str("            )\n");
+            let _ = writeln!(
+                out,
+                "            _describe -t commands {} commands",
+                PosixQuoter.quote(
+                    &format!(
+                        "{} command",
+                        scope.path()
+                    )
+                )
+            );
+            out.push_str("            ;;\n");
+            out.push_str("        args)\n");
+            out.push_str("            case $line[1] in\n");
+            for command in &scope.commands {
+                let mut path = scope
+                    .path
+                    .clone();
+                path.push(&command.name);
+                let _ = writeln!(
+                    out,
+                    "                {}) {} ;;",
+                    command_names(command)
+                        .iter()
+                        .map(|name| PosixQuoter.quote(name))
+                        .collect::<Vec<_>>()
+                        .join("|"),
+                    function(&path)
+                );
+            }
+            out.push_str("            esac\n");
+            out.push_str("            ;;\n");
+            out.push_str("    esac\n");
+        }
+        out.push_str("}\n");
+    }
+
+    let _ = writeln!(
+        out,
+        "\n{} \"$@\"",
+        function(&scopes[0].path)
+    );
+    out
+}
+
+/// `complete` flags offering `values`, files being offered only for paths
+fn fish_values(values: &Values) -> String {
+    match values {
+        Values::Words(words) => format!(
+            " -f -a {}",
+            FishQuoter.quote(&words.join(" "))
+        ),
+        Values::Files(None) => " -F".to_string(),
+        // `*.yaml` and the like, other patterns offer every file
+        Values::Files(Some(glob))
+            if glob
+                .strip_prefix('*')
+                .is_some_and(
+                    |suffix| {
+                        !suffix.contains(
+                            [
+                                '*', '?', '[',
+                            ],
+                        )
+                    },
+                ) =>
+        {
+            format!(
+                " -f -a {}",
+                FishQuoter.quote(
+                    &format!(
+                        "(__fish_complete_suffix {})",
+                        &glob[1..]
+                    )
+                )
+            )
+        }
+        Values::Files(Some(_)) => " -F".to_string(),
+        Values::Directories => " -f -a '(__fish_complete_directories)'".to_string(),
+        Values::Command(command) => format!(
+            " -f -a {}",
+            FishQuoter.quote(
+                &format!(
+                    "({} 2>/dev/null)",
+                    command
+                )
+            )
+        ),
+    }
+}
+
+/// `complete` flags for the option's names, `-l`, `-s` or `-o`
+fn fish_option_names(option: &OptionElement) -> String {
+    let mut flags = String::new();
+    for name in option_names(option) {
+        let (flag, name) = match (
+            name.strip_prefix("--"),
+            name.strip_prefix('-'),
+        ) {
+            (Some(long), _) => (
+                "-l", long,
+            ),
+            (None, Some(short))
+                if short
+                    .chars()
+                    .count()
+                    == 1 =>
+            {
+                (
+                    "-s", short,
+                )
+            }
+            (None, Some(old)) => (
+                "-o", old,
+            ),
+            // fish only completes dash options
+            (None, None) => continue,
+        };
+        let _ = write!(
+            flags,
+            " {} {}",
+            flag,
+            FishQuoter.quote(name)
+        );
+    }
+    flags
+}
+
+fn fish_description(description: &Option<String>) -> String {
+    summary(description)
+        .map(
+            |description| {
+                format!(
+                    " -d {}",
+                    FishQuoter.quote(description)
+                )
+            },
+        )
+        .unwrap_or_default()
+}
+
+/// A fish function telling the command the words lead to, and `complete` lines guarded by it
+fn fish(spec: &V0_1, scopes: &[CommandScope]) -> String {
+    let program = &spec
+        .info
+        .title;
+    let complete = format!(
+        "complete -c {}",
+        FishQuoter.quote(program)
+    );
+    let function = format!(
+        "__cmdi_{}_at",
+        identifier(program)
+    );
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        "# fish completion for {}, generated by cmdi from its OpenCLI spec\n",
+        program
+    );
+    let _ = writeln!(
+        out,
+        "# Whether the words before the cursor lead to the command given as arguments\nfunction {}",
+        function
+    );
+    let _ = writeln!(
+        out,
+        "    set -l path {}",
+        FishQuoter.quote(program)
+    );
+    out.push_str("    set -l skip 0\n");
+    out.push_str("    for word in (commandline -opc)[2..-1]\n");
+    out.push_str("        if test $skip = 1\n");
+    out.push_str("            set skip 0\n");
+    out.push_str("            continue\n");
+    out.push_str("        end\n");
+    out.push_str("        switch \"$path:$word\"\n");
+    let patterns = |path: &str, names: Vec<&str>| {
+        names
+            .iter()
+            .map(
+                |name| {
+                    FishQuoter.quote(
+                        &format!(
+                            "{}:{}",
+                            path, name
+                        ),
+                    )
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    for scope in scopes {
+        let path = scope.path();
+        for command in &scope.commands {
+            let _ = writeln!(
+                out,
+                "            case {}\n                set path {}",
+                patterns(
+                    &path,
+                    command_names(command)
+                ),
+                FishQuoter.quote(
+                    &format!(
+                        "{} {}",
+                        path, command.name
+                    )
+                )
+            );
+        }
+        let valued: Vec<&str> = scope
+            .usable_options()
+            .filter(|option| takes_value(option))
+            .flat_map(|option| option_names(option))
+            .collect();
+        if !valued.is_empty() {
+            let _ = writeln!(
+                out,
+                "            case {}\n                set skip 1",
+                patterns(
+                    &path, valued
+                )
+            );
+        }
+    }
+    out.push_str("        end\n");
+    out.push_str("    end\n");
+    out.push_str("    test \"$path\" = \"$argv\"\n");
+    out.push_str("end\n\n");
+
+    let _ = writeln!(
+        out,
+        "{} -f",
+        complete
+    );
+    for scope in scopes {
+        let condition = format!(
+            " -n {}",
+            FishQuoter.quote(
+                &format!(
+                    "{} {}",
+                    function,
+                    scope.path()
+                )
+            )
+        );
+        for command in &scope.commands {
+            for name in command_names(command) {
+                let _ = writeln!(
+                    out,
+                    "{}{} -a {}{}",
+                    complete,
+                    condition,
+                    FishQuoter.quote(name),
+                    fish_description(&command.description)
+                );
+            }
+        }
+        for option in scope.usable_options() {
+            let names = fish_option_names(option);
+            if names.is_empty() {
+                continue;
+            }
+            let values = match takes_value(option) {
+                true => Values::of_option(option)
+                    .map(
+                        |values| {
+                            format!(
+                                " -r{}",
+                                fish_values(&values)
+                            )
+                        },
+                    )
+                    .unwrap_or(" -x".to_string()),
+                false => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "{}{}{}{}{}",
+                complete,
+                condition,
+                names,
+                fish_description(&option.description),
+                values
+            );
+        }
+        if scope
+            .commands
+            .is_empty()
+        {
+            for argument in &scope.arguments {
+                if let Some(values) = Values::of_argument(argument) {
+                    let _ = writeln!(
+                        out,
+                        "{}{}{}",
+                        complete,
+                        condition,
+                        fish_values(&values)
+                    );
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+opencli: "0.1"
+info:
+  title: kube
+  version: "1.0"
+conventions:
+  groupOptions: true
+options:
+  - name: --kubeconfig
+    description: Path to the kubeconfig file
+    recursive: true
+    arguments:
+      - name: FILE
+        metadata:
+          - name: cmdi.completion
+            value: { glob: "*.yaml" }
+  - name: --debug
+    hidden: true
+commands:
+  - name: get
+    aliases: [g]
+    description: Display resources
+    arguments:
+      - name: resource
+        required: true
+        acceptedValues: [pods, services]
+      - name: name
+    options:
+      - name: --output
+        aliases: [-o]
+        description: Output format
+        arguments:
+          - name: FORMAT
+            acceptedValues: [json, yaml]
+      - name: --namespace
+        arguments:
+          - name: NAMESPACE
+            metadata:
+              - name: cmdi.completion
+                value: { command: "kube namespaces" }
+  - name: internal
+    hidden: true
+"#;
+
+    fn spec() -> V0_1 {
+        serde_yml::from_str(SPEC).unwrap()
+    }
+
+    #[test]
+    fn test_scopes_inherit_recursive_options_and_skip_hidden() {
+        let spec = spec();
+        let scopes = spec.visible_scopes();
+
+        let paths: Vec<String> = scopes
+            .iter()
+            .map(CommandScope::path)
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "kube", "kube get"
+            ]
+        );
+        let options: Vec<&str> = scopes[1]
+            .usable_options()
+            .map(
+                |option| {
+                    option
+                        .name
+                        .as_str()
+                },
+            )
+            .collect();
+        assert_eq!(
+            options,
+            [
+                "--output",
+                "--namespace",
+                "--kubeconfig"
+            ]
+        );
+        assert_eq!(
+            scopes[0]
+                .options
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_bash_script() {
+        let script = completion_script(
+            &spec(),
+            Shell::Bash,
+        )
+        .unwrap();
+
+        assert!(script.contains("kube:get|kube:g)\n                path='kube get'\n"));
+        assert!(script.contains(
+            "'kube get:--output'|'kube get:-o')\n            COMPREPLY=($(compgen -W 'json yaml' -- \"$cur\"))\n"
+        ));
+        assert!(
+            script.contains(
+                "COMPREPLY=($(compgen -W \"$(kube namespaces 2>/dev/null)\" -- \"$cur\"))"
+            )
+        );
+        assert!(script.contains("compgen -f -X '!*.yaml' -- \"$cur\""));
+        assert!(
+            script
+                .contains("'kube get:0') COMPREPLY=($(compgen -W 'pods services' -- \"$cur\")) ;;")
+        );
+        assert!(!script.contains("--debug"));
+        assert!(!script.contains("internal"));
+        assert!(script.ends_with("complete -o bashdefault -o default -F _kube_completion kube\n"));
+    }
+
+    #[test]
+    fn test_zsh_script() {
+        let script = completion_script(
+            &spec(),
+            Shell::Zsh,
+        )
+        .unwrap();
+
+        assert!(script.starts_with("#compdef kube\n"));
+        assert!(script.contains("'(--output -o)-o[Output format]:FORMAT:(json yaml)'"));
+        assert!(
+            script
+                .contains("'--kubeconfig[Path to the kubeconfig file]:FILE:_files -g \"*.yaml\"'")
+        );
+        assert!(
+            script.contains("'get:Display resources'\n                'g:Display resources'\n")
+        );
+        assert!(script.contains("get|g) _kube_get ;;"));
+        assert!(script.contains("'1:resource:(pods services)' \\\n        2::name:\n"));
+        assert!(!script.contains("internal"));
+        assert!(script.ends_with("\n_kube \"$@\"\n"));
+    }
+
+    #[test]
+    fn test_fish_script() {
+        let script = completion_script(
+            &spec(),
+            Shell::Fish,
+        )
+        .unwrap();
+
+        assert!(script.contains("case kube:get kube:g\n                set path 'kube get'\n"));
+        assert!(
+            script.contains(
+                "complete -c kube -n '__cmdi_kube_at kube' -a g -d 'Display resources'\n"
+            )
+        );
+        assert!(script.contains(
+            "complete -c kube -n '__cmdi_kube_at kube get' -l output -s o -d 'Output format' -r -f -a 'json yaml'\n"
+        ));
+        assert!(script.contains("complete -c kube -n '__cmdi_kube_at kube get' -l kubeconfig"));
+        assert!(
+            script
+                .contains("complete -c kube -n '__cmdi_kube_at kube get' -f -a 'pods services'\n")
+        );
+        assert!(script.contains("-l kubeconfig -d 'Path to the kubeconfig file' -r -f -a '(__fish_complete_suffix .yaml)'"));
+        assert!(!script.contains("debug"));
+    }
+
+    #[test]
+    fn test_powershell_is_not_supported() {
+        assert!(
+            completion_script(
+                &spec(),
+                Shell::PowerShell
+            )
+            .is_err()
+        );
+    }
+}
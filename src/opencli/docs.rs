@@ -0,0 +1,707 @@
+//! Reference documentation generated from a spec, a page per command
+//!
+//! Pages are named after the command path like git's, `kubectl-get` for `kubectl get`.
+
+use crate::opencli::operations::CommandScope;
+use crate::opencli::usage::{MarkdownWriter, RoffWriter, UsageWriter};
+use crate::opencli::v0_1::{ArgumentElement, ExitCodeElement, OptionElement, V0_1};
+use clap::ValueEnum;
+use color_eyre::eyre::bail;
+use std::collections::HashMap;
+
+/// Formats `cmdi spec docs` writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DocsFormat {
+    /// Man pages in section 1
+    Man,
+    /// Markdown pages, one per command
+    Markdown,
+}
+
+impl DocsFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocsFormat::Man => "1",
+            DocsFormat::Markdown => "md",
+        }
+    }
+}
+
+/// A rendered page and the file it goes in
+pub struct Page {
+    pub file_name: String,
+    pub text: String,
+}
+
+fn page_name(path: &[&str]) -> String {
+    path.join("-")
+}
+
+/// `<NAME>` for a value that must be given, `[NAME]` otherwise, and `...` when it repeats
+fn placeholder(argument: &ArgumentElement, optional: bool) -> String {
+    let name = argument
+        .name
+        .to_uppercase();
+    let repeats = match argument.max_values() != Some(1) {
+        true => "...",
+        false => "",
+    };
+    match optional {
+        true => format!(
+            "[{}]{}",
+            name, repeats
+        ),
+        false => format!(
+            "<{}>{}",
+            name, repeats
+        ),
+    }
+}
+
+/// How many values an argument takes, in words
+fn arity(argument: &ArgumentElement) -> String {
+    let minimum = argument.min_values();
+    match argument.max_values() {
+        Some(maximum) if maximum == minimum => minimum.to_string(),
+        Some(1) => "0 or 1".to_string(),
+        Some(maximum) => format!(
+            "{} to {}",
+            minimum, maximum
+        ),
+        None => format!(
+            "{} or more",
+            minimum
+        ),
+    }
+}
+
+/// The description followed by what the spec says about the accepted values
+fn describe(description: &Option<String>, argument: Option<&ArgumentElement>) -> String {
+    let mut sentences: Vec<String> = description
+        .iter()
+        .map(
+            |description| {
+                description
+                    .trim()
+                    .to_string()
+            },
+        )
+        .filter(|description| !description.is_empty())
+        .collect();
+    // Notes follow as sentences of their own
+    let ended = |sentences: &mut Vec<String>| {
+        if let Some(last) = sentences.last_mut()
+            && !last.ends_with(
+                [
+                    '.', '!', '?',
+                ],
+            )
+        {
+            last.push('.');
+        }
+    };
+    if let Some(argument) = argument {
+        if let Some(values) = &argument.accepted_values {
+            ended(&mut sentences);
+            sentences.push(
+                format!(
+                    "Accepted values: {}.",
+                    values.join(", ")
+                ),
+            );
+        }
+        if let Some(default) = argument.default_value() {
+            ended(&mut sentences);
+            sentences.push(
+                format!(
+                    "Default: {}.",
+                    default
+                ),
+            );
+        }
+    }
+    sentences.join(" ")
+}
+
+fn option_rows(options: &[&OptionElement]) -> Vec<Vec<String>> {
+    options
+        .iter()
+        .map(
+            |option| {
+                let arguments: Vec<&ArgumentElement> = option
+                    .arguments
+                    .iter()
+                    .flatten()
+                    .collect();
+                // The word after an option is its value unless the arity allows none
+                let value = arguments
+                    .iter()
+                    .map(
+                        |argument| {
+                            placeholder(
+                                argument,
+                                argument
+                                    .arity
+                                    .as_ref()
+                                    .is_some_and(|arity| arity.minimum == Some(0)),
+                            )
+                        },
+                    )
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut description = describe(
+                    &option.description,
+                    arguments
+                        .first()
+                        .copied(),
+                );
+                if option.required {
+                    description = format!(
+                        "Required. {}",
+                        description
+                    );
+                }
+
+                vec![
+                    std::iter::once(&option.name)
+                        .chain(
+                            option
+                                .aliases
+                                .iter()
+                                .flatten(),
+                        )
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    value,
+                    description,
+                ]
+            },
+        )
+        .collect()
+}
+
+fn exit_code_rows(exit_codes: &[ExitCodeElement]) -> Vec<Vec<String>> {
+    exit_codes
+        .iter()
+        .map(
+            |exit_code| {
+                vec![
+                    exit_code
+                        .code
+                        .to_string(),
+                    exit_code
+                        .description
+                        .clone()
+                        .unwrap_or_default(),
+                ]
+            },
+        )
+        .collect()
+}
+
+/// How the command is typed, `git commit [OPTIONS] [PATHSPEC]...`
+fn synopsis(scope: &CommandScope) -> String {
+    let mut words = vec![scope.path()];
+    if scope
+        .usable_options()
+        .next()
+        .is_some()
+    {
+        words.push("[OPTIONS]".to_string());
+    }
+    match scope
+        .commands
+        .is_empty()
+    {
+        false => words.push("<COMMAND>".to_string()),
+        true => words.extend(
+            scope
+                .arguments
+                .iter()
+                .map(
+                    |argument| {
+                        placeholder(
+                            argument,
+                            argument.min_values() == 0,
+                        )
+                    },
+                ),
+        ),
+    }
+    words.join(" ")
+}
+
+/// Write the page documenting the command of `scope`
+pub fn write_page<W: UsageWriter>(spec: &V0_1, scope: &CommandScope, out: &mut W) {
+    let info = &spec.info;
+    let (summary, description, examples, exit_codes) = match scope.command {
+        None => (
+            info.summary
+                .as_deref(),
+            &info.description,
+            &spec.examples,
+            &spec.exit_codes,
+        ),
+        Some(command) => (
+            command
+                .description
+                .as_deref()
+                .and_then(
+                    |description| {
+                        description
+                            .lines()
+                            .next()
+                    },
+                ),
+            &command.description,
+            &command.examples,
+            &command.exit_codes,
+        ),
+    };
+
+    out.page(
+        &page_name(&scope.path),
+        summary,
+        &format!(
+            "{} {}",
+            info.title, info.version
+        ),
+    );
+    out.heading("Synopsis");
+    out.code(&[synopsis(scope)]);
+
+    let aliases = scope
+        .command
+        .and_then(
+            |command| {
+                command
+                    .aliases
+                    .as_ref()
+            },
+        );
+    // A one line description is already the summary
+    let description = description
+        .as_deref()
+        .filter(|description| Some(description.trim()) != summary);
+    if description.is_some() || aliases.is_some() {
+        out.heading("Description");
+        if let Some(description) = description {
+            out.paragraph(description);
+        }
+        if let Some(aliases) = aliases {
+            out.paragraph(
+                &format!(
+                    "Aliases: {}",
+                    aliases.join(", ")
+                ),
+            );
+        }
+    }
+
+    if !scope
+        .commands
+        .is_empty()
+    {
+        out.heading("Commands");
+        out.table(
+            &[
+                "Command",
+                "Description",
+            ],
+            &scope
+                .commands
+                .iter()
+                .map(
+                    |command| {
+                        vec![
+                            command
+                                .name
+                                .clone(),
+                            describe(
+                                &command.description,
+                                None,
+                            ),
+                        ]
+                    },
+                )
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    for (heading, options) in [
+        (
+            "Options",
+            &scope.options,
+        ),
+        (
+            "Inherited options",
+            &scope.inherited,
+        ),
+    ] {
+        if !options.is_empty() {
+            out.heading(heading);
+            out.table(
+                &[
+                    "Option",
+                    "Value",
+                    "Description",
+                ],
+                &option_rows(options),
+            );
+        }
+    }
+
+    if !scope
+        .arguments
+        .is_empty()
+    {
+        out.heading("Arguments");
+        out.table(
+            &[
+                "Argument",
+                "Values",
+                "Description",
+            ],
+            &scope
+                .arguments
+                .iter()
+                .map(
+                    |argument| {
+                        vec![
+                            argument
+                                .name
+                                .to_uppercase(),
+                            arity(argument),
+                            describe(
+                                &argument.description,
+                                Some(argument),
+                            ),
+                        ]
+                    },
+                )
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    if let Some(examples) = examples {
+        out.heading("Examples");
+        out.code(examples);
+    }
+
+    if let Some(exit_codes) = exit_codes {
+        out.heading("Exit status");
+        out.table(
+            &[
+                "Code",
+                "Description",
+            ],
+            &exit_code_rows(exit_codes),
+        );
+    }
+
+    if scope
+        .command
+        .is_none()
+    {
+        if let Some(contact) = &info.contact {
+            let email = contact
+                .email
+                .as_ref()
+                .map(
+                    |email| {
+                        format!(
+                            "<{}>",
+                            email
+                        )
+                    },
+                );
+            let parts: Vec<&str> = [
+                &contact.name,
+                &email,
+                &contact.url,
+            ]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+            if !parts.is_empty() {
+                out.heading("Contact");
+                out.paragraph(&parts.join(" "));
+            }
+        }
+        if let Some(license) = &info.license {
+            let text = match (
+                &license.name,
+                &license.identifier,
+            ) {
+                (Some(name), Some(identifier)) => Some(
+                    format!(
+                        "{} ({})",
+                        name, identifier
+                    ),
+                ),
+                (name, identifier) => name
+                    .clone()
+                    .or(identifier.clone()),
+            };
+            if let Some(text) = text {
+                out.heading("License");
+                out.paragraph(&text);
+            }
+        }
+    }
+
+    // The parent page, then those of the subcommands
+    let parent = scope
+        .path
+        .split_last()
+        .map(|(_, parent)| parent)
+        .filter(|parent| !parent.is_empty())
+        .map(page_name);
+    let related: Vec<String> = parent
+        .into_iter()
+        .chain(
+            scope
+                .commands
+                .iter()
+                .map(
+                    |command| {
+                        let mut path = scope
+                            .path
+                            .clone();
+                        path.push(&command.name);
+                        page_name(&path)
+                    },
+                ),
+        )
+        .collect();
+    if !related.is_empty() {
+        out.see_also(&related);
+    }
+}
+
+/// A page for the program and one for each of its visible commands
+///
+/// Fails when a name can't be put in a file name, as it would leave the output directory or
+/// hide the page, and when two commands would write the same file.
+pub fn pages(spec: &V0_1, format: DocsFormat) -> color_eyre::Result<Vec<Page>> {
+    let mut pages = vec![];
+    // File names taken so far, and the command each documents
+    let mut taken: HashMap<String, String> = HashMap::new();
+    for scope in spec.visible_scopes() {
+        let command = scope
+            .path
+            .join(" ");
+        if scope
+            .path
+            .iter()
+            .any(
+                |name| {
+                    name.is_empty()
+                        || name.starts_with('.')
+                        || name.contains(
+                            [
+                                '/', '\\', '\0',
+                            ],
+                        )
+                },
+            )
+        {
+            bail!(
+                "No page for '{}', its name can't be used in a file name",
+                command
+            );
+        }
+        let file_name = format!(
+            "{}.{}",
+            page_name(&scope.path),
+            format.extension()
+        );
+        if let Some(other) = taken.insert(
+            file_name.clone(),
+            command.clone(),
+        ) {
+            bail!(
+                "The pages of '{}' and '{}' would both be '{}'",
+                other,
+                command,
+                file_name
+            );
+        }
+
+        let text = match format {
+            DocsFormat::Man => {
+                let mut out = RoffWriter::new();
+                write_page(
+                    spec, &scope, &mut out,
+                );
+                out.into_string()
+            }
+            DocsFormat::Markdown => {
+                let mut out = MarkdownWriter::new();
+                write_page(
+                    spec, &scope, &mut out,
+                );
+                out.into_string()
+            }
+        };
+        pages.push(
+            Page {
+                file_name,
+                text,
+            },
+        );
+    }
+
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+opencli: "0.1"
+info:
+  title: tool
+  version: "1.0"
+  summary: Does things
+  license:
+    identifier: MIT
+options:
+  - name: --verbose
+    recursive: true
+commands:
+  - name: run
+    aliases: [r]
+    description: Run the things
+    arguments:
+      - name: file
+        arity: { minimum: 1 }
+    options:
+      - name: --mode
+        arguments:
+          - name: MODE
+            acceptedValues: [fast, slow]
+            metadata:
+              - name: cmdi.default
+                value: fast
+  - name: secret
+    hidden: true
+"#;
+
+    fn spec() -> V0_1 {
+        serde_yml::from_str(SPEC).unwrap()
+    }
+
+    #[test]
+    fn test_a_page_per_visible_command() -> color_eyre::Result<()> {
+        let names: Vec<String> = pages(
+            &spec(),
+            DocsFormat::Markdown,
+        )?
+        .into_iter()
+        .map(|page| page.file_name)
+        .collect();
+
+        assert_eq!(
+            names,
+            [
+                "tool.md",
+                "tool-run.md"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_names_that_are_not_file_names() {
+        for name in [
+            "../etc", "a/b", ".hidden",
+        ] {
+            let mut spec = spec();
+            spec.commands
+                .as_mut()
+                .unwrap()[0]
+                .name = name.to_string();
+
+            assert!(
+                pages(
+                    &spec,
+                    DocsFormat::Man
+                )
+                .is_err(),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_pages_sharing_a_file_name() {
+        let spec: V0_1 = serde_yml::from_str(
+            r#"
+opencli: "0.1"
+info:
+  title: tool
+  version: "1.0"
+commands:
+  - name: a-b
+  - name: a
+    commands:
+      - name: b
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pages(
+                &spec,
+                DocsFormat::Man
+            )
+            .err()
+            .map(|e| e.to_string()),
+            Some("The pages of 'tool a-b' and 'tool a b' would both be 'tool-a-b.1'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_command_page_documents_options_and_arity() {
+        let spec = spec();
+        let scopes = spec.visible_scopes();
+        let mut out = MarkdownWriter::new();
+        write_page(
+            &spec, &scopes[1], &mut out,
+        );
+        let page = out.into_string();
+
+        assert!(page.starts_with("# tool-run\n\nRun the things\n"));
+        assert!(page.contains("```\ntool run [OPTIONS] <FILE>...\n```"));
+        assert!(page.contains("Aliases: r"));
+        assert!(
+            page.contains("| `--mode` | `<MODE>` | Accepted values: fast, slow. Default: fast. |")
+        );
+        assert!(page.contains("## Inherited options\n\n| Option | Value | Description |\n|---|---|---|\n| `--verbose` |  |  |"));
+        assert!(page.contains("| `FILE` | `1 or more` |  |"));
+        assert!(page.ends_with("## See also\n\n[tool](tool.md)\n"));
+    }
+
+    #[test]
+    fn test_program_man_page() {
+        let spec = spec();
+        let scopes = spec.visible_scopes();
+        let mut out = RoffWriter::new();
+        write_page(
+            &spec, &scopes[0], &mut out,
+        );
+        let page = out.into_string();
+
+        assert!(
+            page.starts_with(".TH \"TOOL\" 1 \"\" \"tool 1.0\"\n.SH NAME\ntool \\- Does things\n")
+        );
+        assert!(page.contains(".SH \"LICENSE\"\n.PP\nMIT\n"));
+        assert!(page.contains(".SH \"SEE ALSO\"\n\\fBtool\\-run\\fR(1)\n"));
+        assert!(!page.contains("secret"));
+    }
+}
@@ -1,4 +1,8 @@
 // use serde::{Deserialize, Serialize};
+pub mod completion;
+pub mod completion_script;
+pub mod docs;
 pub mod operations;
+pub mod registry;
 pub mod usage;
 pub mod v0_1;
@@ -1,18 +1,314 @@
-// use crate::opencli::v0_1::{OptionElement};
-
-// pub fn find_option_spec<'a>(
-//     options: &'a Vec<OptionElement>,
-//     option_flag: &str,
-// ) -> Option<&'a OptionElement> {
-//     options
-//     .iter()
-//         .find(|o| o.name == option_flag)
-// }
-
-// pub fn find_options(spec: &V0_1, cmd_ctx: Option<&CommandElement>) -> Vec<String> {
-//     cmd_ctx
-//         .and_then(|cmd| cmd.options.as_ref())
-//         .or_else(|| spec.options.as_ref())
-//         .map(|elems| elems.iter().map(|s| s.name.clone()).collect())
-//         .unwrap_or_default()
-// }
+use crate::opencli::v0_1::{ArgumentElement, CommandElement, OptionElement, V0_1};
+use std::iter;
+
+/// Name of the metadatum documenting the value used when an argument is not given
+///
+/// OpenCLI 0.1 has no field for it.
+pub const DEFAULT_METADATUM: &str = "cmdi.default";
+
+/// A visible command of a spec with the visible options, arguments and subcommands it has
+pub struct CommandScope<'a> {
+    /// The names leading to the command, the program's title first
+    pub path: Vec<&'a str>,
+    /// `None` for the program itself
+    pub command: Option<&'a CommandElement>,
+    pub options: Vec<&'a OptionElement>,
+    /// Recursive options of ancestors that no option closer to the command hides
+    pub inherited: Vec<&'a OptionElement>,
+    pub arguments: Vec<&'a ArgumentElement>,
+    pub commands: Vec<&'a CommandElement>,
+}
+
+impl CommandScope<'_> {
+    /// The names leading to the command separated by spaces, as typed
+    pub fn path(&self) -> String {
+        self.path
+            .join(" ")
+    }
+
+    /// The command's own options followed by the inherited ones
+    pub fn usable_options(&self) -> impl Iterator<Item = &OptionElement> {
+        self.options
+            .iter()
+            .chain(&self.inherited)
+            .copied()
+    }
+}
+
+fn visible<T>(items: &Option<Vec<T>>, hidden: impl Fn(&T) -> bool) -> Vec<&T> {
+    items
+        .iter()
+        .flatten()
+        .filter(|item| !hidden(item))
+        .collect()
+}
+
+/// Add the scope of `command` then those of its visible subcommands, depth first
+fn walk<'a>(
+    path: Vec<&'a str>,
+    command: Option<&'a CommandElement>,
+    inherited: &[&'a OptionElement],
+    options: &'a Option<Vec<OptionElement>>,
+    arguments: &'a Option<Vec<ArgumentElement>>,
+    commands: &'a Option<Vec<CommandElement>>,
+    scopes: &mut Vec<CommandScope<'a>>,
+) {
+    let options = visible(
+        options,
+        |option| option.hidden,
+    );
+    let inherited: Vec<&OptionElement> = inherited
+        .iter()
+        .copied()
+        .filter(
+            |inherited| {
+                !options
+                    .iter()
+                    .any(|option| option.name == inherited.name)
+            },
+        )
+        .collect();
+    let passed: Vec<&OptionElement> = inherited
+        .iter()
+        .copied()
+        .chain(
+            options
+                .iter()
+                .copied()
+                .filter(|option| option.recursive),
+        )
+        .collect();
+    let commands = visible(
+        commands,
+        |command| command.hidden,
+    );
+
+    scopes.push(
+        CommandScope {
+            path: path.clone(),
+            command,
+            options,
+            inherited,
+            arguments: visible(
+                arguments,
+                |argument| argument.hidden,
+            ),
+            commands: commands.clone(),
+        },
+    );
+
+    for command in commands {
+        walk(
+            path.iter()
+                .copied()
+                .chain(
+                    iter::once(
+                        command
+                            .name
+                            .as_str(),
+                    ),
+                )
+                .collect(),
+            Some(command),
+            &passed,
+            &command.options,
+            &command.arguments,
+            &command.commands,
+            scopes,
+        );
+    }
+}
+
+impl V0_1 {
+    /// The program and every command not hidden, depth first
+    pub fn visible_scopes(&self) -> Vec<CommandScope<'_>> {
+        let mut scopes = vec![];
+        walk(
+            vec![
+                &self
+                    .info
+                    .title,
+            ],
+            None,
+            &[],
+            &self.options,
+            &self.arguments,
+            &self.commands,
+            &mut scopes,
+        );
+        scopes
+    }
+
+    /// Whether the program accepts short options grouped into one word, like `-alh`
+    pub fn groups_short_options(&self) -> bool {
+        self.conventions
+            .as_ref()
+            .and_then(|conventions| conventions.group_options)
+            .unwrap_or(false)
+    }
+}
+
+impl OptionElement {
+    /// Whether `word` is the option's name or one of its aliases
+    pub fn matches(&self, word: &str) -> bool {
+        self.name == word
+            || self
+                .aliases
+                .as_ref()
+                .is_some_and(
+                    |aliases| {
+                        aliases
+                            .iter()
+                            .any(|alias| alias == word)
+                    },
+                )
+    }
+
+    /// The letter of the option's single dash form (`-l`), from its name or an alias
+    pub fn short_flag(&self) -> Option<char> {
+        std::iter::once(&self.name)
+            .chain(
+                self.aliases
+                    .iter()
+                    .flatten(),
+            )
+            .find_map(
+                |name| {
+                    let mut chars = name
+                        .strip_prefix('-')?
+                        .chars();
+                    match (
+                        chars.next(),
+                        chars.next(),
+                    ) {
+                        (Some(flag), None) if flag != '-' => Some(flag),
+                        _ => None,
+                    }
+                },
+            )
+    }
+
+    /// Whether the option can take more than one value, over one or several arguments
+    pub fn takes_several_values(&self) -> bool {
+        let arguments = self
+            .arguments
+            .as_deref()
+            .unwrap_or_default();
+
+        arguments.len() > 1
+            || arguments
+                .iter()
+                .any(|argument| argument.max_values() != Some(1))
+    }
+
+    /// The values belonging to each argument in order, and those left over
+    ///
+    /// Each argument takes as many values as its arity allows, like the parser assigns them.
+    pub fn split_values<'a>(
+        &self,
+        values: &'a [String],
+    ) -> (
+        Vec<&'a [String]>,
+        &'a [String],
+    ) {
+        let mut remaining = values;
+        let assigned = self
+            .arguments
+            .iter()
+            .flatten()
+            .map(
+                |argument| {
+                    let take = argument
+                        .max_values()
+                        .unwrap_or(remaining.len())
+                        .min(remaining.len());
+                    let (mine, rest) = remaining.split_at(take);
+                    remaining = rest;
+                    mine
+                },
+            )
+            .collect();
+
+        (
+            assigned, remaining,
+        )
+    }
+}
+
+impl CommandElement {
+    /// Whether `word` is the command's name or one of its aliases
+    pub fn matches(&self, word: &str) -> bool {
+        self.name == word
+            || self
+                .aliases
+                .as_ref()
+                .is_some_and(
+                    |aliases| {
+                        aliases
+                            .iter()
+                            .any(|alias| alias == word)
+                    },
+                )
+    }
+}
+
+impl ArgumentElement {
+    /// The fewest values the argument accepts
+    ///
+    /// Without an arity a required argument needs exactly one value.
+    pub fn min_values(&self) -> usize {
+        self.arity
+            .as_ref()
+            .and_then(|arity| arity.minimum)
+            .map(|minimum| minimum.max(0) as usize)
+            .unwrap_or(self.required as usize)
+    }
+
+    /// The documented default value, strings as they are and anything else as JSON
+    pub fn default_value(&self) -> Option<String> {
+        self.metadata
+            .iter()
+            .flatten()
+            .find(|metadatum| metadatum.name == DEFAULT_METADATUM)?
+            .value
+            .as_ref()
+            .map(
+                |value| match value {
+                    serde_json::Value::String(string) => string.clone(),
+                    value => value.to_string(),
+                },
+            )
+    }
+
+    /// The most values the argument accepts, `None` when unbounded
+    ///
+    /// Without an arity an argument takes a single value.
+    pub fn max_values(&self) -> Option<usize> {
+        match &self.arity {
+            None => Some(1),
+            Some(arity) => arity
+                .maximum
+                .map(|maximum| maximum.max(0) as usize),
+        }
+    }
+}
+
+pub fn find_option<'a>(
+    options: &'a Option<Vec<OptionElement>>,
+    word: &str,
+) -> Option<&'a OptionElement> {
+    options
+        .as_ref()?
+        .iter()
+        .find(|option| option.matches(word))
+}
+
+pub fn find_command<'a>(
+    commands: &'a Option<Vec<CommandElement>>,
+    word: &str,
+) -> Option<&'a CommandElement> {
+    commands
+        .as_ref()?
+        .iter()
+        .find(|command| command.matches(word))
+}
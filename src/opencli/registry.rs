@@ -0,0 +1,275 @@
+use crate::opencli::v0_1::V0_1;
+use color_eyre::eyre::{WrapErr, bail, eyre};
+use directories::ProjectDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File extensions a spec may be stored with, in lookup order
+const SPEC_EXTENSIONS: [&str; 3] = [
+    "yaml", "yml", "json",
+];
+
+/// Spec directory relative to the current working directory
+const PROJECT_SPEC_DIR: &str = ".cmdi/specs";
+
+/// System wide spec directories
+const SYSTEM_SPEC_DIRS: [&str; 3] = [
+    "/usr/local/share/cmdi/specs",
+    "/usr/share/cmdi/specs",
+    "/etc/cmdi/specs",
+];
+
+/// Resolves a program name to an OpenCLI spec on disk
+///
+/// Directories are searched in order: project (`./.cmdi/specs`), user (config and data dirs) and
+/// then system. The first `<program>.yaml`, `<program>.yml` or `<program>.json` found wins.
+pub struct SpecRegistry {
+    search_paths: Vec<PathBuf>,
+}
+
+impl SpecRegistry {
+    pub fn new(directories: &ProjectDirs) -> Self {
+        let mut search_paths = vec![PathBuf::from(PROJECT_SPEC_DIR)];
+        search_paths.push(
+            directories
+                .config_dir()
+                .join("specs"),
+        );
+        search_paths.push(Self::user_spec_dir(directories));
+        search_paths.extend(
+            SYSTEM_SPEC_DIRS
+                .iter()
+                .map(PathBuf::from),
+        );
+
+        Self::with_paths(search_paths)
+    }
+
+    /// Where specs generated for the user are written
+    pub fn user_spec_dir(directories: &ProjectDirs) -> PathBuf {
+        directories
+            .data_dir()
+            .join("specs")
+    }
+
+    pub fn with_paths(search_paths: Vec<PathBuf>) -> Self {
+        let mut unique: Vec<PathBuf> = vec![];
+        for path in search_paths {
+            if !unique.contains(&path) {
+                unique.push(path);
+            }
+        }
+
+        Self {
+            search_paths: unique,
+        }
+    }
+
+    /// Find the spec file for `program` without loading it
+    pub fn find(&self, program: &str) -> Option<PathBuf> {
+        self.search_paths
+            .iter()
+            .flat_map(
+                |dir| {
+                    SPEC_EXTENSIONS
+                        .iter()
+                        .map(
+                            move |ext| {
+                                dir.join(
+                                    format!(
+                                        "{}.{}",
+                                        program, ext
+                                    ),
+                                )
+                            },
+                        )
+                },
+            )
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Load the spec for `program`
+    ///
+    /// `program` may also be a path to a spec file, which is loaded directly.
+    pub fn load(&self, program: &str) -> color_eyre::Result<V0_1> {
+        let direct = Path::new(program);
+        if is_spec_file(direct) {
+            return load_spec_file(direct);
+        }
+
+        match self.find(program) {
+            Some(path) => load_spec_file(&path),
+            None => Err(
+                eyre!(
+                    "No OpenCLI spec found for '{}'. Searched:\n{}",
+                    program,
+                    self.searched_candidates(program)
+                ),
+            ),
+        }
+    }
+
+    fn searched_candidates(&self, program: &str) -> String {
+        self.search_paths
+            .iter()
+            .map(
+                |dir| {
+                    format!(
+                        "  {}/{}.{{{}}}",
+                        dir.display(),
+                        program,
+                        SPEC_EXTENSIONS.join(",")
+                    )
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn is_spec_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| SPEC_EXTENSIONS.contains(&ext))
+}
+
+/// Deserialize a spec file, choosing the format from its extension
+pub fn load_spec_file(path: &Path) -> color_eyre::Result<V0_1> {
+    let content = fs::read_to_string(path).wrap_err_with(
+        || {
+            format!(
+                "Failed to read spec '{}'",
+                path.display()
+            )
+        },
+    )?;
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    match extension {
+        "json" => serde_json::from_str(&content).wrap_err_with(
+            || {
+                format!(
+                    "Malformed JSON spec '{}'",
+                    path.display()
+                )
+            },
+        ),
+        "yaml" | "yml" => serde_yml::from_str(&content).wrap_err_with(
+            || {
+                format!(
+                    "Malformed YAML spec '{}'",
+                    path.display()
+                )
+            },
+        ),
+        other => bail!(
+            "Unsupported spec format '{}' for '{}'",
+            other,
+            path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = temp_dir().join(
+            format!(
+                "cmdi-registry-{}-{}",
+                name,
+                std::process::id()
+            ),
+        );
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_finds_yaml_and_json_specs() {
+        let first = scratch_dir("first");
+        let second = scratch_dir("second");
+        fs::write(
+            first.join("kubectl.yaml"),
+            include_str!("demo-kubectl.yaml"),
+        )
+        .unwrap();
+        fs::write(
+            second.join("dotnet.json"),
+            include_str!("example-dotnet.json"),
+        )
+        .unwrap();
+
+        let registry = SpecRegistry::with_paths(
+            vec![
+                first.clone(),
+                second.clone(),
+            ],
+        );
+
+        assert_eq!(
+            registry
+                .load("kubectl")
+                .unwrap()
+                .info
+                .title,
+            "kubectl"
+        );
+        assert_eq!(
+            registry
+                .load("dotnet")
+                .unwrap()
+                .info
+                .title,
+            "dotnet"
+        );
+    }
+
+    #[test]
+    fn test_missing_spec_names_searched_paths() {
+        let dir = scratch_dir("missing");
+        let registry = SpecRegistry::with_paths(vec![dir.clone()]);
+
+        let err = registry
+            .load("nope")
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("nope"));
+        assert!(
+            err.contains(
+                dir.to_str()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_malformed_spec_is_an_error() {
+        let dir = scratch_dir("malformed");
+        fs::write(
+            dir.join("broken.yaml"),
+            "opencli: [",
+        )
+        .unwrap();
+        let registry = SpecRegistry::with_paths(vec![dir]);
+
+        let err = registry
+            .load("broken")
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("Malformed YAML spec")
+        );
+    }
+}
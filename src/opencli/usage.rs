@@ -1,7 +1,7 @@
-use crate::builder::Token;
 use crate::builder::Token::{
-    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken,
+    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken, UnknownToken,
 };
+use crate::builder::{ScopedOption, Token};
 use crate::opencli::usage::UsageStyleClass::*;
 use crate::opencli::v0_1::{
     ArgumentElement, Arity, CommandElement, ExitCodeElement, OptionElement, V0_1,
@@ -35,6 +35,105 @@ pub trait UsageWriter {
         }
     }
     fn newline(&mut self, indent: usize);
+
+    /// Start the page documenting `title`, `footer` naming the program and its version
+    fn page(&mut self, title: &str, summary: Option<&str>, _footer: &str) {
+        self.write(
+            title,
+            Some(Name),
+        );
+        if let Some(summary) = summary {
+            self.write(
+                " -- ", None,
+            );
+            self.write(
+                summary,
+                Some(Description),
+            );
+        }
+        self.newline(0);
+    }
+
+    /// Start a section of a page
+    fn heading(&mut self, title: &str) {
+        self.newline(0);
+        self.write(
+            title.to_uppercase(),
+            Some(Header),
+        );
+        self.write(
+            ":",
+            Some(Header),
+        );
+        self.newline(0);
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        self.write(
+            text,
+            Some(Description),
+        );
+        self.newline(0);
+    }
+
+    /// Rows of names separated by `, `, details like a value's syntax, and a description
+    ///
+    /// The first cell of a row holds the names and the last the description.
+    fn table(&mut self, _columns: &[&str], rows: &[Vec<String>]) {
+        for row in rows {
+            self.write(
+                "  ", None,
+            );
+            for (index, cell) in row
+                .iter()
+                .enumerate()
+            {
+                match index {
+                    0 => self.write(
+                        cell,
+                        Some(Name),
+                    ),
+                    index if index + 1 == row.len() => {
+                        self.write(
+                            " -- ", None,
+                        );
+                        self.write(
+                            cell,
+                            Some(Description),
+                        );
+                    }
+                    _ => self.write(
+                        format!(
+                            " {}",
+                            cell
+                        ),
+                        Some(Details),
+                    ),
+                }
+            }
+            self.newline(0);
+        }
+    }
+
+    /// Lines shown as typed, like a synopsis or examples
+    fn code(&mut self, lines: &[String]) {
+        for line in lines {
+            self.write(
+                format!(
+                    "  {}",
+                    line
+                ),
+                None,
+            );
+            self.newline(0);
+        }
+    }
+
+    /// Point to the pages documenting related commands
+    fn see_also(&mut self, pages: &[String]) {
+        self.heading("See also");
+        self.paragraph(&pages.join(", "));
+    }
 }
 
 pub struct WidgetWriter<'a> {
@@ -112,6 +211,63 @@ impl<'a> WidgetWriter<'a> {
         out.into_text()
     }
 
+    /// Usage of `token`, followed by the recursive options its command inherits
+    pub fn token_in_scope_to_usage_text(
+        token: &Token,
+        inherited: &[ScopedOption],
+        style: &UsageStyle,
+    ) -> Text<'a> {
+        let mut out = Self::new(style);
+        match token {
+            CommandToken {
+                ..
+            }
+            | SubCommandToken {
+                ..
+            } if !inherited.is_empty() => {
+                foreach(
+                    &mut out,
+                    &Some(inherited.to_vec()),
+                    "INHERITED OPTIONS".to_string(),
+                    0,
+                );
+            }
+            OptionToken {
+                ctx,
+                spec,
+                ..
+            } => {
+                if let Some(option) = inherited
+                    .iter()
+                    .find(
+                        |option| {
+                            option
+                                .spec
+                                .name
+                                == spec.name
+                                && option.ctx == *ctx
+                        },
+                    )
+                {
+                    out.newline(0);
+                    out.write(
+                        t!(
+                            "usage.inherited_from",
+                            origin = option.origin
+                        ),
+                        Some(Details),
+                    );
+                }
+            }
+            _ => {}
+        }
+
+        Self::token_to_usage_text(
+            token, style,
+        )
+        .add(out.into_text())
+    }
+
     pub fn token_to_usage_text(token: &Token, style: &UsageStyle) -> Text<'a> {
         match token {
             PlaceholderToken => Text::from(""),
@@ -139,6 +295,9 @@ impl<'a> WidgetWriter<'a> {
             } => Self::spec_to_text(
                 spec, style,
             ),
+            UnknownToken {
+                ..
+            } => Text::from(t!("messages.unknown_token").to_string()),
         }
     }
 }
@@ -155,6 +314,14 @@ impl StringWriter {
             style: style.clone(),
         }
     }
+    pub fn spec_to_usage<T: Usage>(spec: &T, style: &UsageStyle) -> String {
+        let mut out = StringWriter::new(style);
+        spec.usage(
+            &mut out, 0,
+        );
+        out.lines
+    }
+
     pub fn spec_to_usage_line<T: Usage>(spec: &T, style: &UsageStyle) -> String {
         let mut out = StringWriter::new(style);
         spec.usage_line(
@@ -205,6 +372,462 @@ impl UsageWriter for StringWriter {
     }
 }
 
+/// Text for `man`, in the roff language of the man macros
+pub struct RoffWriter {
+    out: String,
+}
+
+impl RoffWriter {
+    pub fn new() -> Self {
+        Self {
+            out: String::new(),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        self.out
+    }
+
+    /// Make sure the next text starts a line, as requests have to
+    fn start_line(&mut self) {
+        if !self
+            .out
+            .is_empty()
+            && !self
+                .out
+                .ends_with('\n')
+        {
+            self.out
+                .push('\n');
+        }
+    }
+
+    /// `text` with roff escapes, protecting lines that would read as requests
+    fn escape(&self, text: &str) -> String {
+        let mut escaped = String::new();
+        let mut line_start = self
+            .out
+            .is_empty()
+            || self
+                .out
+                .ends_with('\n');
+        for c in text.chars() {
+            if line_start
+                && matches!(
+                    c,
+                    '.' | '\''
+                )
+            {
+                escaped.push_str(r"\&");
+            }
+            match c {
+                '\\' => escaped.push_str(r"\e"),
+                '-' => escaped.push_str(r"\-"),
+                c => escaped.push(c),
+            }
+            line_start = c == '\n';
+        }
+        escaped
+    }
+
+    /// A request argument, quoted as it may hold spaces
+    fn argument(text: &str) -> String {
+        format!(
+            "\"{}\"",
+            text.replace(
+                '\\', r"\e"
+            )
+            .replace(
+                '"', r"\(dq"
+            )
+            .replace(
+                '-', r"\-"
+            )
+        )
+    }
+}
+
+impl Default for RoffWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageWriter for RoffWriter {
+    fn write<T: AsRef<str>>(&mut self, text: T, style: Option<UsageStyleClass>) {
+        let escaped = self.escape(text.as_ref());
+        match style {
+            Some(Name) | Some(Header) => self
+                .out
+                .push_str(
+                    &format!(
+                        r"\fB{}\fR",
+                        escaped
+                    ),
+                ),
+            Some(Details) => self
+                .out
+                .push_str(
+                    &format!(
+                        r"\fI{}\fR",
+                        escaped
+                    ),
+                ),
+            Some(Description) | None => self
+                .out
+                .push_str(&escaped),
+        }
+    }
+
+    fn newline(&mut self, _indent: usize) {
+        self.start_line();
+        self.out
+            .push_str(".br\n");
+    }
+
+    fn page(&mut self, title: &str, summary: Option<&str>, footer: &str) {
+        self.start_line();
+        self.out
+            .push_str(
+                &format!(
+                    ".TH {} 1 \"\" {}\n",
+                    Self::argument(&title.to_uppercase()),
+                    Self::argument(footer)
+                ),
+            );
+        self.out
+            .push_str(".SH NAME\n");
+        let name = self.escape(title);
+        self.out
+            .push_str(&name);
+        if let Some(summary) = summary {
+            self.out
+                .push_str(r" \- ");
+            let summary = self.escape(summary);
+            self.out
+                .push_str(&summary);
+        }
+        self.out
+            .push('\n');
+    }
+
+    fn heading(&mut self, title: &str) {
+        self.start_line();
+        self.out
+            .push_str(
+                &format!(
+                    ".SH {}\n",
+                    Self::argument(&title.to_uppercase())
+                ),
+            );
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        for paragraph in text
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+        {
+            self.start_line();
+            self.out
+                .push_str(".PP\n");
+            self.write(
+                paragraph, None,
+            );
+        }
+    }
+
+    fn table(&mut self, _columns: &[&str], rows: &[Vec<String>]) {
+        for row in rows {
+            self.start_line();
+            self.out
+                .push_str(".TP\n");
+            let Some((description, cells)) = row.split_last() else {
+                continue;
+            };
+            for (index, cell) in cells
+                .iter()
+                .enumerate()
+            {
+                match index {
+                    0 => {
+                        for (index, name) in cell
+                            .split(", ")
+                            .enumerate()
+                        {
+                            if index > 0 {
+                                self.write(
+                                    ", ", None,
+                                );
+                            }
+                            self.write(
+                                name,
+                                Some(Name),
+                            );
+                        }
+                    }
+                    _ if cell.is_empty() => {}
+                    _ => {
+                        self.write(
+                            " ", None,
+                        );
+                        self.write(
+                            cell,
+                            Some(Details),
+                        );
+                    }
+                }
+            }
+            self.out
+                .push('\n');
+            self.write(
+                description.trim(),
+                None,
+            );
+        }
+    }
+
+    fn code(&mut self, lines: &[String]) {
+        self.start_line();
+        self.out
+            .push_str(".PP\n.RS 4\n.nf\n");
+        for line in lines {
+            self.write(
+                line, None,
+            );
+            self.out
+                .push('\n');
+        }
+        self.out
+            .push_str(".fi\n.RE\n");
+    }
+
+    fn see_also(&mut self, pages: &[String]) {
+        self.heading("See also");
+        let references: Vec<String> = pages
+            .iter()
+            .map(
+                |page| {
+                    format!(
+                        r"\fB{}\fR(1)",
+                        self.escape(page)
+                    )
+                },
+            )
+            .collect();
+        self.out
+            .push_str(&references.join(",\n"));
+        self.out
+            .push('\n');
+    }
+}
+
+/// CommonMark, pages linking to each other as `name.md`
+pub struct MarkdownWriter {
+    out: String,
+}
+
+impl MarkdownWriter {
+    pub fn new() -> Self {
+        Self {
+            out: String::new(),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        self.out
+    }
+
+    /// Leave a blank line before the next block
+    fn start_block(&mut self) {
+        if self
+            .out
+            .is_empty()
+        {
+            return;
+        }
+        while !self
+            .out
+            .ends_with("\n\n")
+        {
+            self.out
+                .push('\n');
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        let mut escaped = String::new();
+        for c in text.chars() {
+            if "\\`*_[]<>|".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    /// `text` as inline code, or escaped when it holds backquotes
+    fn code_span(text: &str) -> String {
+        match text.contains('`') {
+            true => Self::escape(text),
+            false => format!(
+                "`{}`",
+                text
+            ),
+        }
+    }
+}
+
+impl Default for MarkdownWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UsageWriter for MarkdownWriter {
+    fn write<T: AsRef<str>>(&mut self, text: T, style: Option<UsageStyleClass>) {
+        let text = text.as_ref();
+        if text.is_empty() {
+            return;
+        }
+        let formatted = match style {
+            Some(Name) => Self::code_span(text),
+            Some(Header) => format!(
+                "**{}**",
+                Self::escape(text)
+            ),
+            Some(Details) => format!(
+                "*{}*",
+                Self::escape(text)
+            ),
+            Some(Description) | None => Self::escape(text),
+        };
+        self.out
+            .push_str(&formatted);
+    }
+
+    fn newline(&mut self, _indent: usize) {
+        self.start_block();
+    }
+
+    fn page(&mut self, title: &str, summary: Option<&str>, _footer: &str) {
+        self.start_block();
+        self.out
+            .push_str(
+                &format!(
+                    "# {}\n",
+                    Self::escape(title)
+                ),
+            );
+        if let Some(summary) = summary {
+            self.start_block();
+            self.write(
+                summary, None,
+            );
+            self.out
+                .push('\n');
+        }
+    }
+
+    fn heading(&mut self, title: &str) {
+        self.start_block();
+        self.out
+            .push_str(
+                &format!(
+                    "## {}\n",
+                    Self::escape(title)
+                ),
+            );
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        self.start_block();
+        self.write(
+            text.trim(),
+            None,
+        );
+        self.out
+            .push('\n');
+    }
+
+    fn table(&mut self, columns: &[&str], rows: &[Vec<String>]) {
+        self.start_block();
+        self.out
+            .push_str(
+                &format!(
+                    "| {} |\n|{}\n",
+                    columns.join(" | "),
+                    "---|".repeat(columns.len())
+                ),
+            );
+        for row in rows {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(
+                    |(index, cell)| match index {
+                        _ if cell.is_empty() => String::new(),
+                        0 => cell
+                            .split(", ")
+                            .map(Self::code_span)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        index if index + 1 == row.len() => Self::escape(
+                            &cell
+                                .split_whitespace()
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        ),
+                        _ => Self::code_span(cell),
+                    },
+                )
+                .collect();
+            self.out
+                .push_str(
+                    &format!(
+                        "| {} |\n",
+                        cells.join(" | ")
+                    ),
+                );
+        }
+    }
+
+    fn code(&mut self, lines: &[String]) {
+        self.start_block();
+        self.out
+            .push_str("```\n");
+        for line in lines {
+            self.out
+                .push_str(line);
+            self.out
+                .push('\n');
+        }
+        self.out
+            .push_str("```\n");
+    }
+
+    fn see_also(&mut self, pages: &[String]) {
+        self.heading("See also");
+        self.start_block();
+        let links: Vec<String> = pages
+            .iter()
+            .map(
+                |page| {
+                    format!(
+                        "[{}]({}.md)",
+                        Self::escape(page),
+                        page
+                    )
+                },
+            )
+            .collect();
+        self.out
+            .push_str(&links.join(", "));
+        self.out
+            .push('\n');
+    }
+}
+
 fn foreach<W: UsageWriter, U: Usage>(
     out: &mut W,
     list: &Option<Vec<U>>,
@@ -388,6 +1011,43 @@ impl Usage for OptionElement {
     }
 }
 
+impl Usage for ScopedOption {
+    fn usage<W: UsageWriter>(&self, out: &mut W, indent: usize) {
+        self.usage_line(
+            out, indent,
+        );
+        out.newline(indent);
+
+        foreach(
+            out,
+            &self
+                .spec
+                .arguments,
+            "ARGUMENTS".to_string(),
+            indent,
+        );
+    }
+
+    fn usage_line<W: UsageWriter>(&self, out: &mut W, indent: usize) {
+        self.spec
+            .usage_line(
+                out, indent,
+            );
+        if self.inherited {
+            out.write(
+                format!(
+                    "  [{}]",
+                    t!(
+                        "usage.inherited_from",
+                        origin = self.origin
+                    )
+                ),
+                Some(Details),
+            );
+        }
+    }
+}
+
 impl Usage for ArgumentElement {
     fn usage<W: UsageWriter>(&self, out: &mut W, indent: usize) {
         self.usage_line(
@@ -435,6 +1095,18 @@ impl Usage for ArgumentElement {
             &self.description,
             Some(Description),
         );
+
+        if let Some(accepted) = &self.accepted_values {
+            out.newline(indent);
+            out.write(
+                "Accepted values: ",
+                Some(Header),
+            );
+            out.write(
+                accepted.join(", "),
+                Some(Details),
+            );
+        }
     }
 }
 
@@ -494,3 +1166,100 @@ impl Display for Arity {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::style::ContentStyle as Style;
+
+    fn plain() -> UsageStyle {
+        UsageStyle {
+            default: Style::default(),
+            header: Style::default(),
+            name: Style::default(),
+            description: Style::default(),
+            details: Style::default(),
+        }
+    }
+
+    #[test]
+    fn test_option_usage_lists_arguments_and_accepted_values() {
+        let option: OptionElement = serde_yml::from_str(
+            r#"
+name: "--output"
+aliases: ["-o"]
+description: Output format
+arguments:
+  - name: format
+    required: true
+    arity:
+      minimum: 1
+      maximum: 1
+    acceptedValues: [json, yaml, wide]
+"#,
+        )
+        .unwrap();
+        let usage = StringWriter::spec_to_usage(
+            &option,
+            &plain(),
+        );
+
+        assert!(usage.contains("-o, --output"));
+        assert!(usage.contains("Output format"));
+        assert!(usage.contains("ARGUMENTS:"));
+        assert!(usage.contains("<FORMAT>"));
+        assert!(usage.contains("Accepted values: json, yaml, wide"));
+    }
+
+    #[test]
+    fn test_roff_writer_escapes_text() {
+        let mut out = RoffWriter::new();
+        out.heading("Options");
+        out.table(
+            &[
+                "Option",
+                "Value",
+                "Description",
+            ],
+            &[
+                vec![
+                    "--output, -o".to_string(),
+                    "<FORMAT>".to_string(),
+                    ".json or C:\\path".to_string(),
+                ],
+            ],
+        );
+
+        assert_eq!(
+            out.into_string(),
+            ".SH \"OPTIONS\"\n.TP\n\\fB\\-\\-output\\fR, \\fB\\-o\\fR \\fI<FORMAT>\\fR\n\\&.json or C:\\epath"
+        );
+    }
+
+    #[test]
+    fn test_markdown_writer_tables_and_escapes() {
+        let mut out = MarkdownWriter::new();
+        out.page(
+            "tool",
+            Some("Does *things*"),
+            "tool 1.0",
+        );
+        out.table(
+            &[
+                "Option",
+                "Description",
+            ],
+            &[
+                vec![
+                    "--all, -a".to_string(),
+                    "Show all\nentries | even hidden".to_string(),
+                ],
+            ],
+        );
+
+        assert_eq!(
+            out.into_string(),
+            "# tool\n\nDoes \\*things\\*\n\n| Option | Description |\n|---|---|\n| `--all`, `-a` | Show all entries \\| even hidden |\n"
+        );
+    }
+}
@@ -1,99 +1,359 @@
-use crate::builder::{Action, Token};
+use crate::builder::{Action, Token, ValueSource};
+use crate::config::Selector;
 use crate::event::Event;
+use crate::opencli::completion::CompletionProvider;
+use crate::opencli::v0_1::ArgumentElement;
 use crate::screens::input_screen::input::InputScreen;
 use crate::screens::input_screen::noop::NoopScreen;
+use crate::screens::input_screen::select::{SelectItem, select_screen};
 use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use log::warn;
 use ratatui::Frame;
+use ratatui::crossterm::event::Event::Key;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::{Block, BorderType, Clear, Paragraph};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
 
+/// Where value entry is at
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    Start,
+    /// Waiting for the completion provider, which runs on its own thread
+    Loading,
+    /// Choosing from the accepted values or the completion provider's candidates
+    Picking,
+    /// Typing the value, directly or after backing out of the picker
+    Entering,
+    Done,
+}
+
+/// Enter the value of an option, argument or unknown word
+///
+/// Accepted values are picked from instead of typed, several at once when the arity allows it,
+/// keeping no more than its maximum. Only options take several: an argument token holds one
+/// word, so each value of a variadic positional is added as its own token. Otherwise a declared
+/// completion provider's candidates are offered first and cancelling the picker falls back to
+/// free text. A provider running a command is waited for without blocking the screen, and Esc
+/// stops waiting.
 pub struct AddArgumentsScreen {
     token: Token,
     action: Action,
-    complete: bool,
+    selector: Selector,
+    candidates: Vec<String>,
+    /// Candidates are the accepted values, free text is not offered
+    strict: bool,
+    multi: bool,
+    stage: Stage,
+    cancelled: bool,
+    /// The candidates of the completion provider, once it has run
+    loading: Option<Receiver<Vec<String>>>,
 }
 impl AddArgumentsScreen {
-    pub fn new(token: &Token, action: &Action) -> Box<dyn Screen> {
+    pub fn new(token: &Token, action: &Action, selector: Selector) -> Box<dyn Screen> {
         match token {
             Token::OptionToken {
                 ..
+            }
+            | Token::ArgumentToken {
+                ..
+            }
+            | Token::UnknownToken {
+                ..
             } => Box::new(
                 Self {
                     token: token.clone(),
                     action: action.clone(),
-                    complete: false,
+                    selector,
+                    candidates: vec![],
+                    strict: false,
+                    multi: false,
+                    stage: Stage::Start,
+                    cancelled: false,
+                    loading: None,
                 },
             ),
-            // Token::ArgumentToken { .. } => {}
             _ => NoopScreen::new(),
         }
     }
+
+    /// The spec of the value being entered, an option's first argument
+    fn value_spec(&self) -> Option<&ArgumentElement> {
+        match &self.token {
+            Token::OptionToken {
+                spec,
+                ..
+            } => spec
+                .arguments
+                .iter()
+                .flatten()
+                .next(),
+            Token::ArgumentToken {
+                spec,
+                ..
+            } => Some(spec),
+            _ => None,
+        }
+    }
+
+    /// Fill `candidates` from the accepted values, or else start the completion provider
+    fn load_candidates(&mut self) {
+        let accepted = self
+            .value_spec()
+            .and_then(
+                |spec| {
+                    spec.accepted_values
+                        .clone()
+                        .filter(|accepted| !accepted.is_empty())
+                        .map(
+                            |accepted| {
+                                (
+                                    accepted,
+                                    spec.max_values() != Some(1),
+                                )
+                            },
+                        )
+                },
+            );
+
+        match accepted {
+            Some((accepted, several)) => {
+                self.candidates = accepted;
+                self.strict = true;
+                // Positional arguments hold a single word per token
+                self.multi = several
+                    && matches!(
+                        self.token,
+                        Token::OptionToken { .. }
+                    );
+            }
+            None => {
+                if let Some(provider) = self.completion_provider() {
+                    let (sender, receiver) = mpsc::channel();
+                    thread::spawn(
+                        move || {
+                            let _ = sender.send(completion_candidates(&provider));
+                        },
+                    );
+                    self.loading = Some(receiver);
+                }
+            }
+        }
+    }
+
+    fn completion_provider(&self) -> Option<CompletionProvider> {
+        match &self.token {
+            Token::OptionToken {
+                spec,
+                ..
+            } => spec.completion(),
+            Token::ArgumentToken {
+                spec,
+                ..
+            } => spec.completion(),
+            _ => None,
+        }
+    }
+
+    /// Pick from the candidates, or type the value when there are none
+    fn offer_candidates(&mut self) -> Transition {
+        if self
+            .candidates
+            .is_empty()
+        {
+            return self.input_screen();
+        }
+
+        self.stage = Stage::Picking;
+        Transition::Push(
+            select_screen(
+                self.selector,
+                SelectItem::strings(&self.candidates),
+                self.multi,
+            ),
+        )
+    }
+
+    fn input_screen(&mut self) -> Transition {
+        self.stage = Stage::Entering;
+        Transition::Push(
+            InputScreen::new(
+                &self
+                    .token
+                    .args()
+                    .join(""),
+            ),
+        )
+    }
+
+    fn set_value(&mut self, input: String) {
+        self.set_values(vec![input]);
+    }
+
+    fn set_values(&mut self, mut values: Vec<String>) {
+        match &mut self.token {
+            Token::PlaceholderToken => {}
+            Token::CommandToken {
+                ..
+            } => {}
+            Token::OptionToken {
+                arg,
+                ..
+            } => *arg = values,
+            Token::SubCommandToken {
+                ..
+            } => {}
+            Token::ArgumentToken {
+                arg,
+                ..
+            }
+            | Token::UnknownToken {
+                arg,
+                ..
+            } => {
+                if !values.is_empty() {
+                    *arg = values.swap_remove(0)
+                }
+            }
+        }
+    }
 }
 impl Screen for AddArgumentsScreen {
     fn update(&mut self, event: Event) -> color_eyre::Result<Transition> {
         match event {
             Event::Exit => Ok(Transition::Exit("Exit Event".to_string())),
-            _ => {
-                if !self.complete {
-                    self.complete = true;
-                    Ok(
-                        Transition::Push(
-                            InputScreen::new(
-                                &self
-                                    .token
-                                    .args()
-                                    .join(""),
-                            ),
-                        ),
-                    )
-                } else {
-                    Ok(
-                        Transition::Complete(
-                            Return::TokenAction(
-                                self.token
-                                    .clone(),
-                                self.action
-                                    .clone(),
-                            ),
-                        ),
-                    )
+            _ => match self.stage {
+                Stage::Start => {
+                    self.load_candidates();
+                    match self.loading {
+                        Some(_) => {
+                            self.stage = Stage::Loading;
+                            Ok(Transition::Continue)
+                        }
+                        None => Ok(self.offer_candidates()),
+                    }
                 }
-            }
+                Stage::Loading => match event {
+                    Event::Crossterm(Key(KeyEvent {
+                        code: KeyCode::Esc,
+                        ..
+                    })) => {
+                        self.loading = None;
+                        Ok(self.input_screen())
+                    }
+                    _ => match self
+                        .loading
+                        .as_ref()
+                        .map(Receiver::try_recv)
+                    {
+                        Some(Err(TryRecvError::Empty)) => Ok(Transition::Continue),
+                        loaded => {
+                            if let Some(Ok(candidates)) = loaded {
+                                self.candidates = candidates;
+                            }
+                            self.loading = None;
+                            Ok(self.offer_candidates())
+                        }
+                    },
+                },
+                // The picker was dismissed without a choice
+                Stage::Picking if self.strict => Ok(Transition::Complete(Return::Noop)),
+                Stage::Picking => Ok(self.input_screen()),
+                Stage::Entering => Ok(Transition::Continue),
+                Stage::Done if self.cancelled => Ok(Transition::Complete(Return::Noop)),
+                Stage::Done => Ok(
+                    Transition::Complete(
+                        Return::TokenAction(
+                            self.token
+                                .clone(),
+                            self.action
+                                .clone(),
+                        ),
+                    ),
+                ),
+            },
         }
     }
 
-    fn process(&mut self, return_value: Return) -> color_eyre::Result<()> {
-        match return_value {
-            Return::Noop => Ok(()),
-            Return::InputString(input) => {
-                match &mut self.token {
-                    Token::PlaceholderToken => {}
-                    Token::CommandToken {
-                        ..
-                    } => {}
-                    Token::OptionToken {
-                        arg,
-                        ..
-                    } => {
-                        arg.clear();
-                        arg.push(input);
-                    }
-                    Token::SubCommandToken {
-                        ..
-                    } => {}
-                    Token::ArgumentToken {
-                        ..
-                    } => {}
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
+        match (
+            self.stage,
+            return_value,
+        ) {
+            (Stage::Picking, Return::Selection(selections)) => {
+                // The picker can't stop at the arity, so the first picks are kept
+                let maximum = self
+                    .value_spec()
+                    .and_then(|spec| spec.max_values())
+                    .unwrap_or(usize::MAX);
+                let values: Vec<String> = selections
+                    .iter()
+                    .take(maximum)
+                    .filter_map(
+                        |s| {
+                            self.candidates
+                                .get(*s)
+                        },
+                    )
+                    .cloned()
+                    .collect();
+                if !values.is_empty() {
+                    self.set_values(values);
+                    self.token
+                        .set_source(ValueSource::Completed);
+                    self.stage = Stage::Done;
                 }
-                Ok(())
             }
-            _ => Ok(()),
+            (Stage::Entering, Return::Noop) => {
+                self.cancelled = true;
+                self.stage = Stage::Done;
+            }
+            (Stage::Entering, Return::InputString(input)) => {
+                self.set_value(input);
+                self.token
+                    .set_source(ValueSource::Typed);
+                self.stage = Stage::Done;
+            }
+            _ => {}
         }
+        Ok(Transition::Continue)
     }
 
     fn render_frame(
         &self,
-        _frame: &mut Frame,
-        _ctx: &RenderContext
+        frame: &mut Frame,
+        ctx: &RenderContext,
     ) -> color_eyre::Result<Rendering> {
+        if self.stage == Stage::Loading {
+            let [
+                _header_area,
+                area,
+            ] = Layout::vertical(
+                [
+                    Constraint::Length(1),
+                    Constraint::Length(3),
+                ],
+            )
+            .areas(frame.area());
+            let styles = ctx
+                .theme
+                .screen_styles();
+
+            frame.render_widget(
+                Clear, area,
+            );
+            frame.render_widget(
+                Paragraph::new(t!("values.loading").to_string())
+                    .style(styles.default)
+                    .block(
+                        Block::bordered()
+                            .border_type(BorderType::Rounded)
+                            .border_style(styles.boarder),
+                    ),
+                area,
+            );
+        }
         Ok(Rendering::Complete)
     }
 
@@ -108,3 +368,222 @@ impl Screen for AddArgumentsScreen {
         Ok(())
     }
 }
+
+/// The provider's candidates, none when it fails
+fn completion_candidates(provider: &CompletionProvider) -> Vec<String> {
+    provider
+        .candidates()
+        .unwrap_or_else(
+            |e| {
+                warn!(
+                    "Completion provider failed: {}",
+                    e
+                );
+                vec![]
+            },
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opencli::v0_1::OptionElement;
+
+    fn option_token(yaml: &str) -> Token {
+        let spec: OptionElement = serde_yml::from_str(yaml).unwrap();
+        Token::OptionToken {
+            ctx: None,
+            spec,
+            arg: vec![],
+            details: vec![],
+        }
+    }
+
+    fn finish(
+        screen: &mut Box<dyn Screen>,
+        selection: Return,
+    ) -> color_eyre::Result<Option<Token>> {
+        assert!(
+            matches!(
+                screen.update(Event::Tick)?,
+                Transition::Push(_)
+            )
+        );
+        screen.process(selection)?;
+
+        Ok(
+            match screen.update(Event::Tick)? {
+                Transition::Complete(Return::TokenAction(token, _)) => Some(token),
+                _ => None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_accepted_values_are_picked() -> color_eyre::Result<()> {
+        let token = option_token(
+            r#"
+name: "--output"
+arguments:
+  - name: FORMAT
+    acceptedValues: [json, yaml]
+"#,
+        );
+        let mut screen = AddArgumentsScreen::new(
+            &token,
+            &Action::InsertArgument,
+            Selector::Native,
+        );
+
+        assert_eq!(
+            finish(
+                &mut screen,
+                Return::Selection(vec![1])
+            )?
+            .map(|token| token.args()),
+            Some(vec!["yaml".to_string()])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_several_accepted_values_within_arity() -> color_eyre::Result<()> {
+        let token = option_token(
+            r#"
+name: "--columns"
+arguments:
+  - name: COLUMN
+    arity:
+      minimum: 1
+    acceptedValues: [name, status, age]
+"#,
+        );
+        let mut screen = AddArgumentsScreen::new(
+            &token,
+            &Action::InsertArgument,
+            Selector::Native,
+        );
+
+        assert_eq!(
+            finish(
+                &mut screen,
+                Return::Selection(
+                    vec![
+                        0, 2
+                    ]
+                )
+            )?
+            .map(|token| token.args()),
+            Some(
+                vec![
+                    "name".to_string(),
+                    "age".to_string()
+                ]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dismissing_accepted_values_cancels() -> color_eyre::Result<()> {
+        let token = option_token(
+            r#"
+name: "--output"
+arguments:
+  - name: FORMAT
+    acceptedValues: [json, yaml]
+"#,
+        );
+        let mut screen = AddArgumentsScreen::new(
+            &token,
+            &Action::InsertArgument,
+            Selector::Native,
+        );
+        screen.update(Event::Tick)?;
+        screen.process(Return::Noop)?;
+
+        assert!(
+            matches!(
+                screen.update(Event::Tick)?,
+                Transition::Complete(Return::Noop)
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_picks_beyond_the_maximum_are_dropped() -> color_eyre::Result<()> {
+        let token = option_token(
+            r#"
+name: "--columns"
+arguments:
+  - name: COLUMN
+    arity:
+      minimum: 1
+      maximum: 2
+    acceptedValues: [name, status, age]
+"#,
+        );
+        let mut screen = AddArgumentsScreen::new(
+            &token,
+            &Action::InsertArgument,
+            Selector::Native,
+        );
+
+        assert_eq!(
+            finish(
+                &mut screen,
+                Return::Selection(
+                    vec![
+                        0, 1, 2
+                    ]
+                )
+            )?
+            .map(|token| token.args()),
+            Some(
+                vec![
+                    "name".to_string(),
+                    "status".to_string()
+                ]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_completion_candidates_load_off_the_ui_thread() -> color_eyre::Result<()> {
+        let token = option_token(
+            r#"
+name: "--namespace"
+arguments:
+  - name: NAMESPACE
+    metadata:
+      - name: cmdi.completion
+        value: { values: [default, kube-system] }
+"#,
+        );
+        let mut screen = AddArgumentsScreen::new(
+            &token,
+            &Action::InsertArgument,
+            Selector::Native,
+        );
+
+        assert!(
+            matches!(
+                screen.update(Event::Tick)?,
+                Transition::Continue
+            )
+        );
+        // Ticks poll the provider until its candidates are offered
+        let mut offered = false;
+        for _ in 0..100 {
+            if let Transition::Push(_) = screen.update(Event::Tick)? {
+                offered = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(offered);
+        Ok(())
+    }
+}
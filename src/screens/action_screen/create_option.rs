@@ -1,26 +1,28 @@
-use crate::builder::{Action, CommandContext, Token};
-use crate::event::{Event};
-use crate::opencli::v0_1::OptionElement;
-use crate::screens::input_screen::fzf_select::FzfSelectScreen;
+use crate::builder::{Action, ScopedOption, Token};
+use crate::config::Selector;
+use crate::event::Event;
+use crate::screens::input_screen::select::{SelectItem, select_screen};
 use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
-use log::{warn};
+use log::warn;
 use ratatui::Frame;
 
 pub struct SelectOptionScreen {
-    options: Vec<OptionElement>,
-    ctx: Option<CommandContext>,
+    options: Vec<ScopedOption>,
     action: Action,
+    selector: Selector,
     complete: bool,
-    selection: Option<OptionElement>,
+    selection: Option<ScopedOption>,
 }
 
 impl SelectOptionScreen {
     fn build_token(&self) -> Token {
+        let selection = self
+            .selection
+            .clone()
+            .unwrap();
         Token::OptionToken {
-            ctx: self
-                .ctx
-                .clone(),
-            spec: self.selection.clone().unwrap(),
+            ctx: selection.ctx,
+            spec: selection.spec,
             arg: vec![],
             details: vec![],
         }
@@ -28,10 +30,10 @@ impl SelectOptionScreen {
 }
 
 impl SelectOptionScreen {
-    pub fn new(options: &Vec<OptionElement>, ctx: Option<&CommandContext>, action: &Action) -> Box<dyn Screen> {
+    pub fn new(options: Vec<ScopedOption>, action: &Action, selector: Selector) -> Box<dyn Screen> {
         let screen = Self {
-            options: options.clone(),
-            ctx: ctx.cloned(),
+            options,
+            selector,
             action: action.clone(),
             complete: false,
             selection: None,
@@ -46,7 +48,15 @@ impl Screen for SelectOptionScreen {
             Event::Exit => Ok(Transition::Exit("Exit Event".to_string())),
             _ => {
                 if !self.complete {
-                    Ok(Transition::Push(FzfSelectScreen::new_with_options(&self.options)))
+                    Ok(
+                        Transition::Push(
+                            select_screen(
+                                self.selector,
+                                SelectItem::options(&self.options),
+                                false,
+                            ),
+                        ),
+                    )
                 } else {
                     if self.selection.is_none() {
                         Ok(Transition::Complete(Return::Noop))
@@ -67,11 +77,11 @@ impl Screen for SelectOptionScreen {
         }
     }
 
-    fn process(&mut self, return_value: Return) -> color_eyre::Result<()> {
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
         match return_value {
             Return::Noop => {
                 self.complete = true;
-                Ok(())
+                Ok(Transition::Continue)
             },
             Return::Selection(selections) => {
                 self.complete = true;
@@ -89,9 +99,9 @@ impl Screen for SelectOptionScreen {
                     )
                     .cloned();
 
-                Ok(())
+                Ok(Transition::Continue)
             }
-            _ => Ok(())
+            _ => Ok(Transition::Continue),
         }
     }
 
@@ -0,0 +1,673 @@
+use crate::builder::{Action, Token};
+use crate::config::Selector;
+use crate::event::Event;
+use crate::opencli::v0_1::ArgumentElement;
+use crate::screens::action_screen::add_arguments::AddArgumentsScreen;
+use crate::screens::input_screen::noop::NoopScreen;
+use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use ratatui::Frame;
+use ratatui::crossterm::event::Event::Key;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Clear, List, ListItem, ListState};
+
+/// A line of the editor, each points into `values`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Row {
+    Value {
+        argument: usize,
+        index: usize,
+    },
+    /// Room for one more value of the argument
+    Add {
+        argument: usize,
+    },
+}
+
+impl Row {
+    fn argument(&self) -> usize {
+        match self {
+            Row::Value {
+                argument,
+                ..
+            }
+            | Row::Add {
+                argument,
+            } => *argument,
+        }
+    }
+}
+
+/// Edit the values of an option one by one, grouped by the named argument they belong to
+///
+/// Adding stops at each argument's maximum arity and removing at its minimum. Every value is
+/// entered through [`AddArgumentsScreen`] so accepted values and completions still apply.
+/// Completes with the option token holding all values, or [`Return::Noop`] when cancelled.
+pub struct EditValuesScreen {
+    token: Token,
+    action: Action,
+    selector: Selector,
+    arguments: Vec<ArgumentElement>,
+    values: Vec<Vec<String>>,
+    cursor: usize,
+    /// Row whose value entry is in progress
+    editing: Option<Row>,
+}
+
+impl EditValuesScreen {
+    pub fn new(token: &Token, action: &Action, selector: Selector) -> Box<dyn Screen> {
+        let Token::OptionToken {
+            spec,
+            arg,
+            ..
+        } = token
+        else {
+            return NoopScreen::new();
+        };
+        let arguments: Vec<ArgumentElement> = spec
+            .arguments
+            .clone()
+            .unwrap_or_default();
+        if arguments.is_empty() {
+            return NoopScreen::new();
+        }
+
+        let (assigned, rest) = spec.split_values(arg);
+        let mut values: Vec<Vec<String>> = assigned
+            .into_iter()
+            .map(<[String]>::to_vec)
+            .collect();
+        // Surplus values stay editable with the last argument, validation flags them
+        if let Some(last) = values.last_mut() {
+            last.extend_from_slice(rest);
+        }
+
+        Box::new(
+            Self {
+                token: token.clone(),
+                action: action.clone(),
+                selector,
+                arguments,
+                values,
+                cursor: 0,
+                editing: None,
+            },
+        )
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        for (argument, spec) in self
+            .arguments
+            .iter()
+            .enumerate()
+        {
+            let count = self.values[argument].len();
+            rows.extend(
+                (0..count).map(
+                    |index| Row::Value {
+                        argument,
+                        index,
+                    },
+                ),
+            );
+            if spec
+                .max_values()
+                .is_none_or(|max| count < max)
+            {
+                rows.push(
+                    Row::Add {
+                        argument,
+                    },
+                );
+            }
+        }
+        rows
+    }
+
+    fn current(&self) -> Option<Row> {
+        self.rows()
+            .get(self.cursor)
+            .copied()
+    }
+
+    fn move_cursor(&mut self, up: bool) {
+        let len = self
+            .rows()
+            .len();
+        if len == 0 {
+            return;
+        }
+        self.cursor = if up {
+            (self.cursor + len - 1) % len
+        } else {
+            (self.cursor + 1) % len
+        };
+    }
+
+    fn clamp_cursor(&mut self) {
+        let len = self
+            .rows()
+            .len();
+        self.cursor = self
+            .cursor
+            .min(len.saturating_sub(1));
+    }
+
+    /// Enter a value for `row` with the argument's picker or free text
+    fn enter_value(&mut self, row: Row) -> Transition {
+        let argument = row.argument();
+        let value = match row {
+            Row::Value {
+                index,
+                ..
+            } => self.values[argument][index].clone(),
+            Row::Add {
+                ..
+            } => String::new(),
+        };
+        let ctx = match &self.token {
+            Token::OptionToken {
+                ctx,
+                ..
+            } => ctx.clone(),
+            _ => None,
+        };
+        self.editing = Some(row);
+
+        Transition::Push(
+            AddArgumentsScreen::new(
+                &Token::ArgumentToken {
+                    ctx,
+                    spec: self.arguments[argument].clone(),
+                    arg: value,
+                    details: vec![],
+                },
+                &self.action,
+                self.selector,
+            ),
+        )
+    }
+
+    /// Add a value to the argument under the cursor if its arity allows one more
+    fn add_value(&mut self) -> Transition {
+        let Some(argument) = self
+            .current()
+            .map(|row| row.argument())
+        else {
+            return Transition::Continue;
+        };
+        let add = Row::Add {
+            argument,
+        };
+        if self
+            .rows()
+            .contains(&add)
+        {
+            self.enter_value(add)
+        } else {
+            Transition::Continue
+        }
+    }
+
+    fn remove_value(&mut self) {
+        if let Some(Row::Value {
+            argument,
+            index,
+        }) = self.current()
+            && self.values[argument].len() > self.arguments[argument].min_values()
+        {
+            self.values[argument].remove(index);
+            self.clamp_cursor();
+        }
+    }
+
+    /// The first argument short of its minimum while a later one has values
+    ///
+    /// Values are saved as one list, so the later ones would be read as this argument's.
+    fn short_argument(&self) -> Option<usize> {
+        let short = self
+            .arguments
+            .iter()
+            .zip(&self.values)
+            .position(|(argument, values)| values.len() < argument.min_values())?;
+
+        self.values[short + 1..]
+            .iter()
+            .any(|values| !values.is_empty())
+            .then_some(short)
+    }
+
+    /// Save the values, unless an argument is short of some, then go to it instead
+    fn save(&mut self) -> Transition {
+        if let Some(short) = self.short_argument() {
+            if let Some(pos) = self
+                .rows()
+                .iter()
+                .position(
+                    |row| {
+                        *row == Row::Add {
+                            argument: short,
+                        }
+                    },
+                )
+            {
+                self.cursor = pos;
+            }
+            return Transition::Continue;
+        }
+
+        let mut token = self
+            .token
+            .clone();
+        if let Token::OptionToken {
+            arg,
+            ..
+        } = &mut token
+        {
+            *arg = self
+                .values
+                .concat();
+        }
+
+        Transition::Complete(
+            Return::TokenAction(
+                token,
+                self.action
+                    .clone(),
+            ),
+        )
+    }
+
+    /// How many values the argument takes, like `1`, `0..2` or `1..`
+    fn bounds(argument: &ArgumentElement) -> String {
+        let min = argument.min_values();
+        match argument.max_values() {
+            Some(max) if max == min => min.to_string(),
+            Some(max) => format!(
+                "{}..{}",
+                min, max
+            ),
+            None => format!(
+                "{}..",
+                min
+            ),
+        }
+    }
+}
+
+impl Screen for EditValuesScreen {
+    fn update(&mut self, event: Event) -> color_eyre::Result<Transition> {
+        let transition = match event {
+            Event::Crossterm(Key(KeyEvent {
+                code,
+                ..
+            })) => match code {
+                KeyCode::Esc | KeyCode::Char('q') => Transition::Complete(Return::Noop),
+                KeyCode::Char('s') => self.save(),
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.move_cursor(true);
+                    Transition::Continue
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.move_cursor(false);
+                    Transition::Continue
+                }
+                KeyCode::Enter => match self.current() {
+                    Some(row) => self.enter_value(row),
+                    None => Transition::Continue,
+                },
+                KeyCode::Char('a') => self.add_value(),
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    self.remove_value();
+                    Transition::Continue
+                }
+                _ => Transition::Continue,
+            },
+            _ => Transition::Continue,
+        };
+        Ok(transition)
+    }
+
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
+        let Some(row) = self
+            .editing
+            .take()
+        else {
+            return Ok(Transition::Continue);
+        };
+
+        if let Return::TokenAction(
+            entered @ Token::ArgumentToken {
+                ..
+            },
+            _,
+        ) = return_value
+        {
+            // The option's source is the one of its latest value
+            if let Some(source) = entered.source() {
+                self.token
+                    .set_source(source);
+            }
+            let arg = entered
+                .args()
+                .concat();
+            match row {
+                Row::Value {
+                    argument,
+                    index,
+                } => self.values[argument][index] = arg,
+                Row::Add {
+                    argument,
+                } => {
+                    self.values[argument].push(arg);
+                    // Stay on the argument so values can be added in a row
+                    if let Some(pos) = self
+                        .rows()
+                        .iter()
+                        .rposition(|row| row.argument() == argument)
+                    {
+                        self.cursor = pos;
+                    }
+                }
+            }
+        }
+
+        Ok(Transition::Continue)
+    }
+
+    fn render_frame(
+        &self,
+        frame: &mut Frame,
+        ctx: &RenderContext,
+    ) -> color_eyre::Result<Rendering> {
+        let styles = ctx
+            .theme
+            .screen_styles();
+        let rows = self.rows();
+        let name_width = self
+            .arguments
+            .iter()
+            .map(
+                |argument| {
+                    argument
+                        .name
+                        .len()
+                        + Self::bounds(argument).len()
+                        + 3
+                },
+            )
+            .max()
+            .unwrap_or_default();
+
+        let items: Vec<ListItem> = rows
+            .iter()
+            .enumerate()
+            .map(
+                |(i, row)| {
+                    let argument = &self.arguments[row.argument()];
+                    let first = i == 0 || rows[i - 1].argument() != row.argument();
+                    let label = if first {
+                        format!(
+                            "{} ({})",
+                            argument
+                                .name
+                                .to_uppercase(),
+                            Self::bounds(argument)
+                        )
+                    } else {
+                        String::new()
+                    };
+                    let value = match row {
+                        Row::Value {
+                            argument,
+                            index,
+                        } => Span::styled(
+                            self.values[*argument][*index].clone(),
+                            styles.default,
+                        ),
+                        Row::Add {
+                            ..
+                        } => Span::styled(
+                            format!(
+                                "+ {}",
+                                t!("values.add")
+                            ),
+                            styles.key_modifier,
+                        ),
+                    };
+
+                    ListItem::new(
+                        Line::from(
+                            vec![
+                                Span::styled(
+                                    format!(
+                                        "{:<width$}",
+                                        label,
+                                        width = name_width
+                                    ),
+                                    styles.boarder,
+                                ),
+                                value,
+                            ],
+                        ),
+                    )
+                },
+            )
+            .collect();
+
+        let title = match &self.token {
+            Token::OptionToken {
+                spec,
+                ..
+            } => format!(
+                "[{}]",
+                spec.name
+            ),
+            _ => String::new(),
+        };
+        let hints = format!(
+            " [Enter] {}  [a] {}  [d] {}  [s] {}  [Esc] {} ",
+            t!("values.edit"),
+            t!("values.add"),
+            t!("values.remove"),
+            t!("values.save"),
+            t!("values.cancel"),
+        );
+
+        let [
+            _header_area,
+            area,
+        ] = Layout::vertical(
+            [
+                Constraint::Length(1),
+                Constraint::Length(rows.len() as u16 + 2),
+            ],
+        )
+        .areas(frame.area());
+
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .border_style(styles.boarder)
+                    .title_top(Line::from(title).style(styles.default))
+                    .title_bottom(Line::from(hints).style(styles.default)),
+            )
+            .highlight_symbol(">> ")
+            .highlight_style(styles.highlight);
+
+        frame.render_widget(
+            Clear, area,
+        );
+        frame.render_stateful_widget(
+            list,
+            area,
+            &mut ListState::default().with_selected(Some(self.cursor)),
+        );
+
+        Ok(Rendering::Complete)
+    }
+
+    fn delegation(&self, _ctx: &RenderContext) -> Option<DeligationInfo> {
+        None
+    }
+
+    fn delegate_terminal(&self, _ctx: &RenderContext) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opencli::v0_1::OptionElement;
+    use ratatui::crossterm::event::{
+        Event as CrosstermEvent, KeyEventKind, KeyEventState, KeyModifiers,
+    };
+
+    fn key(code: KeyCode) -> Event {
+        Event::Crossterm(
+            CrosstermEvent::Key(
+                KeyEvent {
+                    code,
+                    modifiers: KeyModifiers::NONE,
+                    kind: KeyEventKind::Press,
+                    state: KeyEventState::NONE,
+                },
+            ),
+        )
+    }
+
+    fn screen(values: &[&str]) -> Box<dyn Screen> {
+        let spec: OptionElement = serde_yml::from_str(
+            r#"
+name: "--port-forward"
+arguments:
+  - name: local
+    required: true
+  - name: remote
+    arity:
+      minimum: 0
+      maximum: 2
+"#,
+        )
+        .unwrap();
+
+        EditValuesScreen::new(
+            &Token::OptionToken {
+                ctx: None,
+                spec,
+                arg: values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect(),
+                details: vec![],
+            },
+            &Action::InsertArgument,
+            Selector::Native,
+        )
+    }
+
+    fn saved(screen: &mut Box<dyn Screen>) -> color_eyre::Result<Vec<String>> {
+        match screen.update(key(KeyCode::Char('s')))? {
+            Transition::Complete(Return::TokenAction(token, _)) => Ok(token.args()),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// What value entry completes with
+    fn entered(value: &str) -> Return {
+        Return::TokenAction(
+            Token::ArgumentToken {
+                ctx: None,
+                spec: serde_yml::from_str("name: value").unwrap(),
+                arg: value.to_string(),
+                details: vec![],
+            },
+            Action::InsertArgument,
+        )
+    }
+
+    #[test]
+    fn test_values_are_added_per_argument_up_to_arity() -> color_eyre::Result<()> {
+        let mut screen = screen(&["8080"]);
+        // rows: 8080, + remote
+        screen.update(key(KeyCode::Down))?;
+        for port in [
+            "80", "443",
+        ] {
+            assert!(
+                matches!(
+                    screen.update(key(KeyCode::Char('a')))?,
+                    Transition::Push(_)
+                )
+            );
+            screen.process(entered(port))?;
+        }
+        // remote is full, adding again does nothing
+        assert!(
+            matches!(
+                screen.update(key(KeyCode::Char('a')))?,
+                Transition::Continue
+            )
+        );
+
+        assert_eq!(
+            saved(&mut screen)?,
+            vec![
+                "8080", "80", "443"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_and_remove_respect_minimum() -> color_eyre::Result<()> {
+        let mut screen = screen(
+            &[
+                "8080", "80",
+            ],
+        );
+        // the required local value can't be removed
+        screen.update(key(KeyCode::Char('d')))?;
+        screen.update(key(KeyCode::Enter))?;
+        screen.process(entered("9090"))?;
+        screen.update(key(KeyCode::Down))?;
+        screen.update(key(KeyCode::Char('d')))?;
+
+        assert_eq!(
+            saved(&mut screen)?,
+            vec!["9090"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_values_are_not_saved_into_a_short_argument() -> color_eyre::Result<()> {
+        let mut screen = screen(&[]);
+        // rows: + local, + remote
+        screen.update(key(KeyCode::Down))?;
+        screen.update(key(KeyCode::Char('a')))?;
+        screen.process(entered("80"))?;
+
+        // remote's value would become local's, so local is to be filled first
+        assert!(
+            matches!(
+                screen.update(key(KeyCode::Char('s')))?,
+                Transition::Continue
+            )
+        );
+        screen.update(key(KeyCode::Char('a')))?;
+        screen.process(entered("8080"))?;
+
+        assert_eq!(
+            saved(&mut screen)?,
+            vec![
+                "8080", "80"
+            ]
+        );
+        Ok(())
+    }
+}
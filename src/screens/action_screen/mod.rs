@@ -1,2 +1,6 @@
+pub mod add_arguments;
 pub mod create_option;
-pub mod add_arguments;
\ No newline at end of file
+pub mod edit_values;
+pub mod run;
+pub mod select_command;
+pub mod select_context;
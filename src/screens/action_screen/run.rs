@@ -0,0 +1,229 @@
+use crate::event::Event;
+use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use ratatui::Frame;
+use ratatui::crossterm::cursor::{MoveTo, Show};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{Clear, ClearType};
+use ratatui::layout::Position;
+use std::io::Write;
+use std::process::Command;
+
+/// Run the composed command with the terminal handed over, then wait for Enter
+///
+/// The argv is executed directly, no shell is involved. Completes with
+/// [`Return::CommandExited`].
+pub struct RunScreen {
+    argv: Vec<String>,
+    display: String,
+    ran: bool,
+}
+
+impl RunScreen {
+    /// `display` is the quoted command line echoed before it runs
+    pub fn new(argv: Vec<String>, display: String) -> Box<dyn Screen> {
+        Box::new(
+            Self {
+                argv,
+                display,
+                ran: false,
+            },
+        )
+    }
+
+    fn run(&self) -> Option<i32> {
+        let (program, args) = self
+            .argv
+            .split_first()?;
+
+        match Command::new(program)
+            .args(args)
+            .status()
+        {
+            Ok(status) => status.code(),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    t!(
+                        "run.failed",
+                        program = program,
+                        error = e
+                    )
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Keeps Ctrl-C from killing cmdi along with the command it runs, until dropped
+///
+/// A handler that does nothing is installed rather than ignoring the signal, as the command
+/// gets the default action back on exec and can still be interrupted.
+struct InterruptGuard {
+    #[cfg(unix)]
+    previous: libc::sigaction,
+}
+
+impl InterruptGuard {
+    #[cfg(unix)]
+    fn new() -> Self {
+        extern "C" fn interrupted(_signal: libc::c_int) {}
+
+        // SAFETY: both sigaction structs are fully initialized before being handed over
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = interrupted as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            libc::sigaction(
+                libc::SIGINT,
+                &action,
+                &mut previous,
+            );
+            Self {
+                previous,
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(unix)]
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        // SAFETY: restores the action saved by `new`
+        unsafe {
+            libc::sigaction(
+                libc::SIGINT,
+                &self.previous,
+                std::ptr::null_mut(),
+            );
+        }
+    }
+}
+
+impl Screen for RunScreen {
+    fn update(&mut self, event: Event) -> color_eyre::Result<Transition> {
+        self.ran = true;
+
+        Ok(
+            match event {
+                Event::CommandExited(code) => Transition::Complete(Return::CommandExited(code)),
+                _ => Transition::Continue,
+            },
+        )
+    }
+
+    fn process(&mut self, _return_value: Return) -> color_eyre::Result<Transition> {
+        Ok(Transition::Continue)
+    }
+
+    fn render_frame(
+        &self,
+        _frame: &mut Frame,
+        _ctx: &RenderContext,
+    ) -> color_eyre::Result<Rendering> {
+        Ok(Rendering::Complete)
+    }
+
+    fn delegation(&self, _ctx: &RenderContext) -> Option<DeligationInfo> {
+        if !self.ran {
+            Some(
+                DeligationInfo {
+                    pos: Position {
+                        x: 0,
+                        y: 0,
+                    },
+                },
+            )
+        } else {
+            None
+        }
+    }
+
+    fn delegate_terminal(&self, ctx: &RenderContext) -> color_eyre::Result<()> {
+        let mut stdout = std::io::stdout();
+        execute!(
+            stdout,
+            Clear(ClearType::All),
+            MoveTo(
+                0, 0
+            ),
+            Show
+        )?;
+        println!(
+            "$ {}\n",
+            self.display
+        );
+
+        // Held over the prompt too, the signal may only reach cmdi after the command is gone
+        let interrupt = InterruptGuard::new();
+        let code = self.run();
+
+        print!(
+            "\n{} ",
+            t!("run.press_enter")
+        );
+        stdout.flush()?;
+        std::io::stdin().read_line(&mut String::new())?;
+        drop(interrupt);
+
+        ctx.event
+            .send(Event::CommandExited(code));
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completes_with_exit_code() -> color_eyre::Result<()> {
+        let mut screen = RunScreen::new(
+            vec!["true".to_string()],
+            "true".to_string(),
+        );
+
+        assert!(
+            matches!(
+                screen.update(Event::Tick)?,
+                Transition::Continue
+            )
+        );
+        assert!(
+            matches!(
+                screen.update(Event::CommandExited(Some(3)))?,
+                Transition::Complete(Return::CommandExited(Some(3)))
+            )
+        );
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_interrupted_command_still_dies() {
+        let _interrupt = InterruptGuard::new();
+        let screen = RunScreen {
+            argv: vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                "kill -INT $$; sleep 5".to_string(),
+            ],
+            display: String::new(),
+            ran: false,
+        };
+
+        // Killed by the signal, so there is no exit code
+        assert_eq!(
+            screen.run(),
+            None
+        );
+    }
+}
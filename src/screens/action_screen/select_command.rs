@@ -0,0 +1,120 @@
+use crate::builder::{Action, CommandContext, Token};
+use crate::config::Selector;
+use crate::event::Event;
+use crate::opencli::v0_1::CommandElement;
+use crate::screens::input_screen::select::{SelectItem, select_screen};
+use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use log::warn;
+use ratatui::Frame;
+
+/// Pick a subcommand, completes with a [`Token::SubCommandToken`] at `level`
+pub struct SelectCommandScreen {
+    commands: Vec<CommandElement>,
+    level: usize,
+    action: Action,
+    selector: Selector,
+    complete: bool,
+    selection: Option<CommandElement>,
+}
+
+impl SelectCommandScreen {
+    fn build_token(&self, selection: &CommandElement) -> Token {
+        Token::SubCommandToken {
+            ctx: CommandContext::new(
+                self.level,
+                selection.clone(),
+            ),
+            details: vec![],
+        }
+    }
+}
+
+impl SelectCommandScreen {
+    pub fn new(
+        commands: &[CommandElement],
+        level: usize,
+        action: &Action,
+        selector: Selector,
+    ) -> Box<dyn Screen> {
+        let screen = Self {
+            commands: commands
+                .iter()
+                .filter(|command| !command.hidden)
+                .cloned()
+                .collect(),
+            level,
+            action: action.clone(),
+            selector,
+            complete: false,
+            selection: None,
+        };
+
+        Box::new(screen)
+    }
+}
+
+impl Screen for SelectCommandScreen {
+    fn update(&mut self, _event: Event) -> color_eyre::Result<Transition> {
+        if !self.complete {
+            return Ok(
+                Transition::Push(
+                    select_screen(
+                        self.selector,
+                        SelectItem::commands(&self.commands),
+                        false,
+                    ),
+                ),
+            );
+        }
+
+        Ok(
+            match &self.selection {
+                None => Transition::Complete(Return::Noop),
+                Some(selection) => Transition::Complete(
+                    Return::TokenAction(
+                        self.build_token(selection),
+                        self.action
+                            .clone(),
+                    ),
+                ),
+            },
+        )
+    }
+
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
+        self.complete = true;
+
+        if let Return::Selection(selections) = return_value {
+            if selections.len() >= 2 {
+                warn!("Selection was greater than 1")
+            }
+            self.selection = selections
+                .first()
+                .and_then(
+                    |s| {
+                        self.commands
+                            .get(*s)
+                    },
+                )
+                .cloned();
+        }
+
+        Ok(Transition::Continue)
+    }
+
+    fn render_frame(
+        &self,
+        _frame: &mut Frame,
+        _ctx: &RenderContext,
+    ) -> color_eyre::Result<Rendering> {
+        Ok(Rendering::Complete)
+    }
+
+    fn delegation(&self, _ctx: &RenderContext) -> Option<DeligationInfo> {
+        None
+    }
+
+    fn delegate_terminal(&self, _ctx: &RenderContext) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
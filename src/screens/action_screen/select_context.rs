@@ -0,0 +1,126 @@
+use crate::builder::{Action, Token};
+use crate::config::Selector;
+use crate::event::Event;
+use crate::screens::input_screen::select::{SelectItem, select_screen};
+use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use log::warn;
+use ratatui::Frame;
+
+/// Pick one of the root or sub commands already in the builder
+///
+/// Completes with the chosen command token, the builder resolves it back to its position.
+pub struct SelectContextScreen {
+    commands: Vec<Token>,
+    labels: Vec<String>,
+    action: Action,
+    selector: Selector,
+    complete: bool,
+    selection: Option<Token>,
+}
+
+impl SelectContextScreen {
+    pub fn new(
+        commands: Vec<Token>,
+        title: &str,
+        action: &Action,
+        selector: Selector,
+    ) -> Box<dyn Screen> {
+        let labels = commands
+            .iter()
+            .map(
+                |command| match command {
+                    Token::SubCommandToken {
+                        ctx,
+                        ..
+                    } => format!(
+                        "{}{}",
+                        "  ".repeat(ctx.level),
+                        ctx.spec
+                            .name
+                    ),
+                    _ => title.to_owned(),
+                },
+            )
+            .collect();
+
+        Box::new(
+            Self {
+                commands,
+                labels,
+                action: action.clone(),
+                selector,
+                complete: false,
+                selection: None,
+            },
+        )
+    }
+}
+
+impl Screen for SelectContextScreen {
+    fn update(&mut self, _event: Event) -> color_eyre::Result<Transition> {
+        if !self.complete {
+            return Ok(
+                Transition::Push(
+                    select_screen(
+                        self.selector,
+                        SelectItem::strings(&self.labels),
+                        false,
+                    ),
+                ),
+            );
+        }
+
+        Ok(
+            match self
+                .selection
+                .take()
+            {
+                None => Transition::Complete(Return::Noop),
+                Some(selection) => Transition::Complete(
+                    Return::TokenAction(
+                        selection,
+                        self.action
+                            .clone(),
+                    ),
+                ),
+            },
+        )
+    }
+
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
+        self.complete = true;
+
+        if let Return::Selection(selections) = return_value {
+            if selections.len() >= 2 {
+                warn!("Selection was greater than 1")
+            }
+            self.selection = selections
+                .first()
+                .and_then(
+                    |s| {
+                        self.commands
+                            .get(*s)
+                    },
+                )
+                .cloned();
+        }
+
+        Ok(Transition::Continue)
+    }
+
+    fn render_frame(
+        &self,
+        _frame: &mut Frame,
+        _ctx: &RenderContext,
+    ) -> color_eyre::Result<Rendering> {
+        Ok(Rendering::Complete)
+    }
+
+    fn delegation(&self, _ctx: &RenderContext) -> Option<DeligationInfo> {
+        None
+    }
+
+    fn delegate_terminal(&self, _ctx: &RenderContext) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
@@ -1,57 +1,138 @@
-use crate::builder::{Action, Token};
+use crate::builder::{Action, HistoryAction, Token};
 use crate::event::Event;
+use crate::opencli::v0_1::OptionElement;
 use crate::screens::Transition::{Continue, Push};
 use crate::screens::action_screen::add_arguments::AddArgumentsScreen;
 use crate::screens::action_screen::create_option::SelectOptionScreen;
+use crate::screens::action_screen::edit_values::EditValuesScreen;
+use crate::screens::action_screen::run::RunScreen;
+use crate::screens::action_screen::select_command::SelectCommandScreen;
+use crate::screens::action_screen::select_context::SelectContextScreen;
 use crate::screens::builder_screen::model::BuilderScreen;
-use crate::screens::{Return, Transition};
+use crate::screens::input_screen::confirm::ConfirmScreen;
+use crate::screens::{Return, Screen, Transition};
 use log::warn;
 use ratatui::crossterm::event::Event::Key;
 use ratatui::crossterm::event::KeyCode;
 
 impl BuilderScreen {
-    fn delegate_action(&self, action: &Action, token: &Token) -> color_eyre::Result<Transition> {
+    fn delegate_action(
+        &mut self,
+        action: &Action,
+        token: &Token,
+    ) -> color_eyre::Result<Transition> {
+        if matches!(
+            action,
+            Action::RemoveToken | Action::MoveUp | Action::MoveDown
+        ) {
+            self.builder
+                .do_token_action(
+                    token.clone(),
+                    action.clone(),
+                )?;
+            return Ok(Continue);
+        }
+
+        if *action == Action::MoveToContext {
+            return Ok(
+                Push(
+                    SelectContextScreen::new(
+                        self.builder
+                            .context_targets_at_selected(),
+                        self.builder
+                            .cmd_title(),
+                        action,
+                        self.settings
+                            .selector,
+                    ),
+                ),
+            );
+        }
+
         let result = match token {
             Token::CommandToken {
                 ctx,
             } => match action {
-                Action::InsertOptionBelow => match &ctx.options {
+                Action::InsertOptionBelow => self.select_option(action),
+                Action::InsertCommand => match &ctx.commands {
                     None => Continue,
-                    Some(options) => Push(
-                        SelectOptionScreen::new(
-                            &options, None, &action,
+                    Some(commands) => Push(
+                        SelectCommandScreen::new(
+                            commands,
+                            1,
+                            action,
+                            self.settings
+                                .selector,
                         ),
                     ),
                 },
+                Action::LookupArguments => self.next_positional(action),
                 _ => Continue,
             },
             Token::SubCommandToken {
                 ctx,
                 ..
             } => match action {
-                Action::InsertOptionBelow => {
+                Action::InsertOptionBelow => self.select_option(action),
+                Action::InsertCommand => {
                     match &ctx
                         .spec
-                        .options
+                        .commands
                     {
                         None => Continue,
-                        Some(options) => Push(
-                            SelectOptionScreen::new(
-                                &options,
-                                Some(ctx),
-                                &action,
+                        Some(commands) => Push(
+                            SelectCommandScreen::new(
+                                commands,
+                                ctx.level + 1,
+                                action,
+                                self.settings
+                                    .selector,
                             ),
                         ),
                     }
                 }
+                Action::LookupArguments => self.next_positional(action),
+                Action::ReplaceToken => match self
+                    .builder
+                    .sibling_commands_at_selected()
+                {
+                    None => Continue,
+                    Some(commands) => Push(
+                        SelectCommandScreen::new(
+                            commands,
+                            ctx.level,
+                            action,
+                            self.settings
+                                .selector,
+                        ),
+                    ),
+                },
                 _ => Continue,
             },
             Token::OptionToken {
+                spec,
                 ..
             } => match action {
                 Action::InsertArgument => Push(
+                    self.value_screen(
+                        token, spec, action,
+                    ),
+                ),
+                Action::ReplaceToken => self.select_option(action),
+                _ => Continue,
+            },
+            Token::ArgumentToken {
+                ..
+            }
+            | Token::UnknownToken {
+                ..
+            } => match action {
+                Action::ReplaceToken => Push(
                     AddArgumentsScreen::new(
-                        token, action,
+                        token,
+                        action,
+                        self.settings
+                            .selector,
                     ),
                 ),
                 _ => Continue,
@@ -67,11 +148,143 @@ impl BuilderScreen {
 
         Ok(result)
     }
+    /// Enter a value for the command's next unfilled positional argument
+    fn next_positional(&self, action: &Action) -> Transition {
+        match self
+            .builder
+            .next_positional_at_selected()
+        {
+            None => Continue,
+            Some((ctx, argument)) => Push(
+                AddArgumentsScreen::new(
+                    &Token::ArgumentToken {
+                        ctx,
+                        spec: argument,
+                        arg: String::new(),
+                        details: vec![],
+                    },
+                    action,
+                    self.settings
+                        .selector,
+                ),
+            ),
+        }
+    }
+
+    /// Value entry for an option
+    ///
+    /// Several values are edited one by one, unless they all come from one argument's accepted
+    /// values and can be picked together.
+    fn value_screen(
+        &self,
+        token: &Token,
+        spec: &OptionElement,
+        action: &Action,
+    ) -> Box<dyn Screen> {
+        let arguments = spec
+            .arguments
+            .as_deref()
+            .unwrap_or_default();
+        let picked_together = arguments.len() == 1
+            && arguments[0]
+                .accepted_values
+                .is_some();
+
+        if spec.takes_several_values() && !picked_together {
+            EditValuesScreen::new(
+                token,
+                action,
+                self.settings
+                    .selector,
+            )
+        } else {
+            AddArgumentsScreen::new(
+                token,
+                action,
+                self.settings
+                    .selector,
+            )
+        }
+    }
+
+    /// Pick from the options usable at the selection, including inherited recursive ones
+    fn select_option(&self, action: &Action) -> Transition {
+        let options = self
+            .builder
+            .options_in_scope_at_selected();
+        if options.is_empty() {
+            Continue
+        } else {
+            Push(
+                SelectOptionScreen::new(
+                    options,
+                    action,
+                    self.settings
+                        .selector,
+                ),
+            )
+        }
+    }
+
+    fn emit_command(&self) -> Transition {
+        Transition::Complete(
+            Return::Command(
+                self.builder
+                    .render(
+                        self.settings
+                            .shell
+                            .quoter(),
+                    ),
+            ),
+        )
+    }
+
+    /// Run the command in the terminal and come back to the builder
+    fn run_command(&self) -> Transition {
+        Push(
+            RunScreen::new(
+                self.builder
+                    .argv(),
+                self.builder
+                    .render(
+                        self.settings
+                            .shell
+                            .quoter(),
+                    ),
+            ),
+        )
+    }
+
+    /// Emit the command, asking first when validation found errors
+    fn request_exit(&self) -> Transition {
+        let problems = self
+            .builder
+            .validate()
+            .len();
+
+        if self
+            .builder
+            .has_errors()
+        {
+            Push(
+                ConfirmScreen::new(
+                    t!(
+                        "diagnostics.confirm_exit",
+                        count = problems
+                    )
+                    .to_string(),
+                ),
+            )
+        } else {
+            self.emit_command()
+        }
+    }
+
     pub fn update(&mut self, event: Event) -> color_eyre::Result<Transition> {
         match event {
             Event::Crossterm(event) => match event {
                 Key(key_event) => match key_event.code {
-                    KeyCode::Esc | KeyCode::Char('q') => Ok(Transition::Complete(Return::Noop)),
+                    KeyCode::Esc | KeyCode::Char('q') => Ok(self.request_exit()),
                     KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('K') => {
                         self.builder
                             .selected_up();
@@ -86,25 +299,43 @@ impl BuilderScreen {
                         // Display help screen
                         Ok(Continue)
                     }
-                    KeyCode::Char(key_char) => match self
-                        .builder
-                        .token_at_selected()
-                    {
-                        None => Ok(Continue), // Should never happen, means there is no selected token
-                        Some(token) => {
-                            if let Some(action) = Action::keybinding_to_action(
-                                key_char,
-                                self.builder
-                                    .available_actions(),
-                            ) {
-                                self.delegate_action(
-                                    &action, &token,
-                                )
-                            } else {
-                                Ok(Continue) // No keybinding for current token (normal)
+                    KeyCode::Char('x') => Ok(self.run_command()),
+                    KeyCode::Char('p') => {
+                        self.preview_multiline = !self.preview_multiline;
+                        Ok(Continue)
+                    }
+                    KeyCode::Char(key_char) => {
+                        if let Some(action) = HistoryAction::keybinding_to_action(
+                            key_char,
+                            self.builder
+                                .available_history_actions(),
+                        ) {
+                            self.builder
+                                .do_history_action(action);
+                            return Ok(Continue);
+                        }
+
+                        match self
+                            .builder
+                            .token_at_selected()
+                        {
+                            None => Ok(Continue), // Should never happen, means there is no selected token
+                            Some(token) => {
+                                let token = token.clone();
+                                if let Some(action) = Action::keybinding_to_action(
+                                    key_char,
+                                    self.builder
+                                        .available_actions(),
+                                ) {
+                                    self.delegate_action(
+                                        &action, &token,
+                                    )
+                                } else {
+                                    Ok(Continue) // No keybinding for current token (normal)
+                                }
                             }
                         }
-                    },
+                    }
                     _key_code => Ok(Continue),
                 },
                 _ => Ok(Continue),
@@ -113,14 +344,21 @@ impl BuilderScreen {
         }
     }
 
-    pub fn process(&mut self, return_value: Return) -> color_eyre::Result<()> {
+    pub fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
         match return_value {
-            Return::TokenAction(token, action) => self
-                .builder
-                .do_token_action(
-                    token, action,
-                ),
-            _ => Ok(()),
+            Return::TokenAction(token, action) => {
+                self.builder
+                    .do_token_action(
+                        token, action,
+                    )?;
+                Ok(Continue)
+            }
+            Return::Confirm(true) => Ok(self.emit_command()),
+            Return::CommandExited(code) => {
+                self.last_run = Some(code);
+                Ok(Continue)
+            }
+            _ => Ok(Continue),
         }
     }
 }
@@ -1,5 +1,6 @@
 use crate::builder::Token::{ArgumentToken, CommandToken, SubCommandToken};
-use crate::builder::{Builder, CommandContext};
+use crate::builder::{Builder, CommandContext, parse_command_line};
+use crate::config::Settings;
 use crate::event::Event;
 use crate::opencli::v0_1::V0_1;
 use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
@@ -7,6 +8,11 @@ use ratatui::Frame;
 
 pub struct BuilderScreen {
     pub(crate) builder: Builder,
+    pub(crate) settings: Settings,
+    /// Exit code of the last run, `Some(None)` when it ended without one
+    pub(crate) last_run: Option<Option<i32>>,
+    /// Show the preview with one option per line
+    pub(crate) preview_multiline: bool,
 }
 
 impl Screen for BuilderScreen {
@@ -14,7 +20,7 @@ impl Screen for BuilderScreen {
         self.update(event)
     }
 
-    fn process(&mut self, return_value: Return) -> color_eyre::Result<()> {
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition> {
         self.process(return_value)
     }
 
@@ -40,49 +46,96 @@ impl Screen for BuilderScreen {
     }
 }
 impl BuilderScreen {
-    pub fn demo(command_spec: V0_1) -> Box<dyn Screen> {
-        let get_command_element = command_spec
+    /// Start editing `args`, an existing command line without the program name
+    pub fn new(command_spec: V0_1, args: &[String], settings: Settings) -> Box<dyn Screen> {
+        let tokens = parse_command_line(
+            &command_spec,
+            args,
+        );
+
+        let mut builder = Builder::new_demo(
+            command_spec,
+            tokens,
+        );
+        builder.set_group_options(settings.group_options);
+
+        Box::new(
+            Self {
+                builder,
+                settings,
+                last_run: None,
+                preview_multiline: false,
+            },
+        )
+    }
+
+    pub fn demo(command_spec: V0_1, settings: Settings) -> Box<dyn Screen> {
+        // The first subcommand and its first argument, for specs that have them
+        let command = command_spec
             .commands
-            .as_ref()
-            .unwrap()
-            .first()
-            .map(|c| c.to_owned())
-            .unwrap();
+            .iter()
+            .flatten()
+            .next()
+            .map(
+                |command| {
+                    CommandContext::new(
+                        1,
+                        command.clone(),
+                    )
+                },
+            );
+        let argument = match &command {
+            Some(ctx) => {
+                &ctx.spec
+                    .arguments
+            }
+            None => &command_spec.arguments,
+        }
+        .iter()
+        .flatten()
+        .next()
+        .cloned();
+
+        let mut tokens = vec![
+            CommandToken {
+                ctx: command_spec.clone(),
+            },
+        ];
+        if let Some(ctx) = &command {
+            tokens.push(
+                SubCommandToken {
+                    ctx: ctx.clone(),
+                    details: vec![],
+                },
+            );
+        }
+        if let Some(spec) = argument {
+            tokens.push(
+                ArgumentToken {
+                    ctx: command,
+                    spec,
+                    arg: "event".to_string(),
+                    details: vec![],
+                },
+            );
+        }
 
-        let screen = Self {
+        let mut screen = Self {
             builder: Builder::new_demo(
-                command_spec.clone(),
-                vec![
-                    CommandToken {
-                        ctx: command_spec,
-                    },
-                    SubCommandToken {
-                        ctx: CommandContext::new(
-                            1,
-                            get_command_element.clone(),
-                        ),
-                        details: vec![],
-                    },
-                    ArgumentToken {
-                        ctx: Some(
-                            CommandContext::new(
-                                1,
-                                get_command_element.clone(),
-                            ),
-                        ),
-                        spec: get_command_element
-                            .arguments
-                            .as_ref()
-                            .unwrap()
-                            .first()
-                            .map(|c| c.to_owned())
-                            .unwrap(),
-                        arg: "event".to_string(),
-                        details: vec![],
-                    },
-                ],
+                command_spec,
+                tokens,
             ),
+            settings,
+            last_run: None,
+            preview_multiline: false,
         };
+        screen
+            .builder
+            .set_group_options(
+                screen
+                    .settings
+                    .group_options,
+            );
 
         Box::new(screen)
     }
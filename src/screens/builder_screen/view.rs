@@ -1,13 +1,16 @@
 use crate::builder::Token::{
-    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken,
+    ArgumentToken, CommandToken, OptionToken, PlaceholderToken, SubCommandToken, UnknownToken,
+};
+use crate::builder::{
+    Builder, Detail, Diagnostic, GhostPositional, RenderedSegment, Severity, Token, ValueSource,
 };
-use crate::builder::{Builder, Token};
 use crate::opencli::usage::WidgetWriter;
 use crate::screens::builder_screen::model::BuilderScreen;
 use crate::screens::{KeyBinding, KeyBindingType, RenderContext, RenderLayer, Rendering};
 use itertools::Itertools;
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Stylize;
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, BorderType, List, ListItem, ListState, Paragraph, Wrap};
 use std::iter;
@@ -74,6 +77,7 @@ impl BuilderScreen {
                 )
                 .right_aligned(),
             )
+            .title_top(self.run_status(ctx))
             .title_bottom(
                 match ctx.layer {
                     RenderLayer::Foreground => self
@@ -83,14 +87,35 @@ impl BuilderScreen {
                 },
             );
 
+        let inner = builder_block.inner(space);
+        let preview = self.preview_text(ctx);
+        // Rows the wrapped preview needs inside its borders
+        let preview_width = inner
+            .width
+            .saturating_sub(2)
+            .max(1) as usize;
+        let preview_height = preview
+            .lines
+            .iter()
+            .map(
+                |line| {
+                    line.width()
+                        .div_ceil(preview_width)
+                        .max(1)
+                },
+            )
+            .sum::<usize>() as u16
+            + 2;
+
         let builder_layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(
                 [
                     Constraint::Fill(100), // for the list, takes the remaining space
+                    Constraint::Max(preview_height),
                 ],
             )
-            .split(builder_block.inner(space));
+            .split(inner);
 
         frame.render_widget(
             builder_block,
@@ -102,17 +127,132 @@ impl BuilderScreen {
         self.render_builder_tokens(
             token_list, frame, ctx,
         )?;
+        self.render_preview(
+            preview,
+            builder_layout[1],
+            frame,
+            ctx,
+        );
 
         Ok(Rendering::Complete)
     }
 
+    /// The quoted command with the selected token's words highlighted
+    fn preview_text(&self, ctx: &RenderContext) -> Text<'static> {
+        let styles = ctx
+            .theme
+            .screen_styles();
+        let quoter = self
+            .settings
+            .shell
+            .quoter();
+        let selected = self
+            .builder
+            .pos_at_selected();
+        let lines = self
+            .builder
+            .preview_lines(
+                quoter,
+                self.preview_multiline,
+            );
+        let last = lines
+            .len()
+            .saturating_sub(1);
+
+        lines
+            .into_iter()
+            .enumerate()
+            .map(
+                |(i, line)| {
+                    let mut spans = vec![];
+                    if i > 0 {
+                        spans.push(Span::raw("  "));
+                    }
+                    for (n, (segment, text)) in line
+                        .into_iter()
+                        .enumerate()
+                    {
+                        if n > 0 {
+                            spans.push(Span::raw(" "));
+                        }
+                        let style = if (segment.pos..segment.pos + segment.len).contains(&selected)
+                        {
+                            styles.highlight
+                        } else {
+                            styles.default
+                        };
+                        spans.push(
+                            Span::styled(
+                                text, style,
+                            ),
+                        );
+                    }
+                    if i < last {
+                        spans.push(
+                            Span::styled(
+                                format!(
+                                    " {}",
+                                    quoter.continuation()
+                                ),
+                                styles.boarder,
+                            ),
+                        );
+                    }
+                    Line::from(spans)
+                },
+            )
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn render_preview(
+        &self,
+        preview: Text<'static>,
+        space: Rect,
+        frame: &mut Frame,
+        ctx: &RenderContext,
+    ) {
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(
+                ctx.theme
+                    .screen_styles()
+                    .boarder,
+            )
+            .title_top(
+                Line::from(
+                    format!(
+                        "[{}]",
+                        t!("headings.preview")
+                    ),
+                )
+                .style(
+                    ctx.theme
+                        .screen_styles()
+                        .default,
+                ),
+            );
+
+        frame.render_widget(
+            Paragraph::new(preview)
+                .block(block)
+                .wrap(
+                    Wrap {
+                        trim: false,
+                    },
+                ),
+            space,
+        );
+    }
+
     fn render_builder_tokens(
         &self,
         space: Rect,
         frame: &mut Frame,
         ctx: &RenderContext,
     ) -> color_eyre::Result<Rendering> {
-        let list = List::from(&self.builder)
+        let (list, selected) = self.token_list(ctx);
+        let list = list
             .highlight_symbol(">> ")
             .highlight_style(
                 ctx.theme
@@ -123,12 +263,201 @@ impl BuilderScreen {
         frame.render_stateful_widget(
             list,
             space,
-            &mut ListState::from(&self.builder),
+            &mut ListState::default().with_selected(Some(selected)),
         );
 
         Ok(Rendering::Complete)
     }
 
+    /// The tokens with their diagnostics shown inline, and the row of the selected token
+    ///
+    /// Required positionals that are still missing get a ghost row at the end of their
+    /// command's scope.
+    fn token_list(
+        &self,
+        ctx: &RenderContext,
+    ) -> (
+        List<'_>,
+        usize,
+    ) {
+        let diagnostics = self
+            .builder
+            .validate();
+        let groups: Vec<RenderedSegment> = self
+            .builder
+            .render_segments()
+            .into_iter()
+            .filter(|segment| segment.len > 1)
+            .collect();
+
+        let ghosts = self
+            .builder
+            .ghost_positionals();
+        let selected_pos = self
+            .builder
+            .pos_at_selected();
+        let mut items = vec![];
+        let mut selected = 0;
+        let ghost_rows = |items: &mut Vec<ListItem<'_>>, pos: usize| {
+            ghosts
+                .iter()
+                .filter(|ghost| ghost.pos == pos)
+                .for_each(
+                    |ghost| {
+                        items.push(
+                            Self::ghost_row(
+                                ghost, ctx,
+                            ),
+                        )
+                    },
+                );
+        };
+
+        for (pos, item) in self
+            .builder
+            .tokens()
+            .iter()
+            .enumerate()
+            .map(
+                |(pos, token)| {
+                    let mut text = Text::from(token);
+                    if let Some(line) = text
+                        .lines
+                        .last_mut()
+                    {
+                        if let Some(marker) = Self::group_marker(
+                            &groups, pos,
+                        ) {
+                            line.push_span(
+                                Span::styled(
+                                    marker,
+                                    ctx.theme
+                                        .screen_styles()
+                                        .boarder,
+                                ),
+                            );
+                        }
+                        diagnostics
+                            .iter()
+                            .filter(|diagnostic| diagnostic.pos == pos)
+                            .for_each(
+                                |diagnostic| {
+                                    line.push_span(
+                                        Self::styled_diagnostic(
+                                            diagnostic, ctx,
+                                        ),
+                                    )
+                                },
+                            );
+                    }
+                    (
+                        pos,
+                        ListItem::new(text),
+                    )
+                },
+            )
+        {
+            ghost_rows(
+                &mut items, pos,
+            );
+            if pos == selected_pos {
+                selected = items.len();
+            }
+            items.push(item);
+        }
+        ghost_rows(
+            &mut items,
+            self.builder
+                .tokens()
+                .len(),
+        );
+
+        (
+            List::new(items),
+            selected,
+        )
+    }
+
+    /// Placeholder for a required positional argument that has no value yet
+    fn ghost_row<'a>(ghost: &GhostPositional, ctx: &RenderContext) -> ListItem<'a> {
+        let style = ctx
+            .theme
+            .screen_styles()
+            .boarder;
+        let mut spans = vec![
+            Span::from("  ".repeat(ghost.level)),
+            Span::styled(
+                format!(
+                    "<{}>",
+                    ghost
+                        .argument
+                        .name
+                        .to_uppercase()
+                ),
+                style,
+            )
+            .italic(),
+        ];
+        if ghost.missing > 1 {
+            spans.push(
+                Span::styled(
+                    format!(
+                        " ×{}",
+                        ghost.missing
+                    ),
+                    style,
+                ),
+            );
+        }
+
+        ListItem::new(Line::from(spans))
+    }
+
+    /// Bracket joining short options that are rendered as one grouped word
+    fn group_marker(groups: &[RenderedSegment], pos: usize) -> Option<String> {
+        let group = groups
+            .iter()
+            .find(|group| (group.pos..group.pos + group.len).contains(&pos))?;
+
+        Some(
+            if pos == group.pos {
+                format!(
+                    "  ┐ {}",
+                    group.words[0]
+                )
+            } else if pos + 1 == group.pos + group.len {
+                "  ┘".to_string()
+            } else {
+                "  │".to_string()
+            },
+        )
+    }
+
+    fn styled_diagnostic(diagnostic: &Diagnostic, ctx: &RenderContext) -> Span<'static> {
+        let (marker, style) = match diagnostic.severity {
+            Severity::Error => (
+                "✗",
+                ctx.theme
+                    .screen_styles()
+                    .error,
+            ),
+            Severity::Warning => (
+                "!",
+                ctx.theme
+                    .screen_styles()
+                    .warning,
+            ),
+        };
+
+        Span::styled(
+            format!(
+                "  {} {}",
+                marker, diagnostic.message
+            ),
+            style,
+        )
+    }
+
     fn render_builder_info(
         &self,
         space: Rect,
@@ -176,8 +505,11 @@ impl BuilderScreen {
             .token_at_selected()
         {
             None => Text::from(""),
-            Some(token) => WidgetWriter::token_to_usage_text(
+            Some(token) => WidgetWriter::token_in_scope_to_usage_text(
                 token,
+                &self
+                    .builder
+                    .inherited_options_at_selected(),
                 ctx.theme
                     .usage_styles(),
             ),
@@ -231,14 +563,166 @@ impl BuilderScreen {
             doc_paragraph,
             doc_space,
         );
+        let details = self
+            .builder
+            .details_at_selected()
+            .iter()
+            .flat_map(
+                |detail| {
+                    Self::detail_lines(
+                        detail, ctx,
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+
         frame.render_widget(
-            details_block,
+            Paragraph::new(details)
+                .block(details_block)
+                .wrap(
+                    Wrap {
+                        trim: false,
+                    },
+                ),
             details_space,
         );
 
         Ok(Rendering::Complete)
     }
 
+    fn detail_lines(detail: &Detail, ctx: &RenderContext) -> Vec<Line<'static>> {
+        let styles = ctx
+            .theme
+            .usage_styles();
+        let line = |text: String| {
+            vec![
+                Line::styled(
+                    text,
+                    styles.default,
+                ),
+            ]
+        };
+
+        match detail {
+            Detail::Diagnostic(diagnostic) => vec![
+                Line::from(
+                    BuilderScreen::styled_diagnostic(
+                        diagnostic, ctx,
+                    ),
+                ),
+            ],
+            Detail::InheritedFrom(origin) => line(
+                t!(
+                    "details.inherited_from",
+                    origin = origin
+                )
+                .to_string(),
+            ),
+            Detail::Default(value) => line(
+                t!(
+                    "details.default",
+                    value = value
+                )
+                .to_string(),
+            ),
+            Detail::Source(source) => line(
+                match source {
+                    ValueSource::Typed => t!("details.typed"),
+                    ValueSource::Completed => t!("details.completed"),
+                    ValueSource::History => t!("details.history"),
+                }
+                .to_string(),
+            ),
+            Detail::ExitCodes(codes) => once(
+                Line::styled(
+                    t!("details.exit_codes").to_string(),
+                    styles.header,
+                ),
+            )
+            .chain(
+                codes
+                    .iter()
+                    .map(
+                        |exit| {
+                            Line::from(
+                                vec![
+                                    Span::styled(
+                                        format!(
+                                            "  {:>3}",
+                                            exit.code
+                                        ),
+                                        styles.name,
+                                    ),
+                                    Span::styled(
+                                        format!(
+                                            "  {}",
+                                            exit.description
+                                                .as_deref()
+                                                .unwrap_or_default()
+                                        ),
+                                        styles.description,
+                                    ),
+                                ],
+                            )
+                        },
+                    ),
+            )
+            .collect(),
+        }
+    }
+
+    /// Exit status of the last run with the spec's description of it
+    fn run_status(&self, ctx: &RenderContext) -> Line<'_> {
+        let styles = ctx
+            .theme
+            .screen_styles();
+        let (text, style) = match self.last_run {
+            None => return Line::default(),
+            Some(None) => (
+                t!("run.no_status").to_string(),
+                styles.warning,
+            ),
+            Some(Some(code)) => {
+                let status = t!(
+                    "run.exit_status",
+                    code = code
+                )
+                .to_string();
+                let text = match self
+                    .builder
+                    .exit_code(code as i64)
+                    .and_then(
+                        |exit| {
+                            exit.description
+                                .as_deref()
+                        },
+                    ) {
+                    Some(description) => format!(
+                        "{}: {}",
+                        status, description
+                    ),
+                    None => status,
+                };
+                (
+                    text,
+                    if code == 0 {
+                        styles.default
+                    } else {
+                        styles.error
+                    },
+                )
+            }
+        };
+
+        Line::from(
+            format!(
+                " {} ",
+                text
+            ),
+        )
+        .style(style)
+    }
+
     fn render_keys(&'_ self, ctx: &RenderContext) -> Line<'_> {
         // confident that intersperse will be same when stabilize
         #[allow(unstable_name_collisions)]
@@ -254,6 +738,37 @@ impl BuilderScreen {
                 ctx,
             ),
         )
+        .chain(
+            once(
+                BuilderScreen::styled_keybind_hint(
+                    (
+                        'x',
+                        KeyBindingType::Informative,
+                    ),
+                    t!("action_hints.run")
+                        .to_string()
+                        .as_str(),
+                    ctx,
+                ),
+            ),
+        )
+        .chain(
+            once(
+                BuilderScreen::styled_keybind_hint(
+                    (
+                        'p',
+                        KeyBindingType::Informative,
+                    ),
+                    match self.preview_multiline {
+                        true => t!("action_hints.preview_single_line"),
+                        false => t!("action_hints.preview_multi_line"),
+                    }
+                    .to_string()
+                    .as_str(),
+                    ctx,
+                ),
+            ),
+        )
         .chain(
             self.builder
                 .available_actions()
@@ -272,6 +787,22 @@ impl BuilderScreen {
                 )
                 .collect::<Vec<_>>(),
         )
+        .chain(
+            self.builder
+                .available_history_actions()
+                .iter()
+                .map(
+                    |action| {
+                        BuilderScreen::styled_keybind_hint(
+                            action.keybinding(),
+                            action
+                                .display_name()
+                                .as_str(),
+                            ctx,
+                        )
+                    },
+                ),
+        )
         .chain(
             once(
                 BuilderScreen::styled_keybind_hint(
@@ -361,10 +892,10 @@ impl<'a> From<&Token> for Text<'a> {
                         "{} {}",
                         spec.name
                             .clone(),
-                        arg.join(",")
+                        arg.join(" ")
                     ),
                 ),
-            ], // FIXME
+            ],
             SubCommandToken {
                 ctx: context,
                 ..
@@ -381,6 +912,13 @@ impl<'a> From<&Token> for Text<'a> {
                 arg,
                 ..
             } => vec![Span::from(arg.clone())],
+            UnknownToken {
+                arg,
+                ..
+            } => vec![
+                Span::from(arg.clone()).italic(),
+                Span::from(" ?"),
+            ],
             PlaceholderToken {
                 ..
             } => vec![Span::from("")],
@@ -399,17 +937,6 @@ impl<'a> From<&Token> for Text<'a> {
     }
 }
 
-impl<'a> From<&Builder> for List<'a> {
-    fn from(builder: &Builder) -> Self {
-        List::new(
-            builder
-                .tokens()
-                .iter()
-                .map(|value| ListItem::new(value))
-                .collect::<Vec<ListItem>>(),
-        )
-    }
-}
 
 impl From<&Builder> for ListState {
     fn from(value: &Builder) -> Self {
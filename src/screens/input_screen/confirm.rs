@@ -0,0 +1,107 @@
+use crate::event::Event;
+use crate::screens::Return::Confirm;
+use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use ratatui::Frame;
+use ratatui::crossterm::event::Event::Key;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::widgets::{Block, BorderType, Clear, Paragraph, Wrap};
+
+/// Yes/no question, completes with [`Return::Confirm`]
+pub struct ConfirmScreen {
+    question: String,
+}
+
+impl ConfirmScreen {
+    pub(crate) fn new(question: String) -> Box<dyn Screen> {
+        Box::new(
+            Self {
+                question,
+            },
+        )
+    }
+}
+
+impl Screen for ConfirmScreen {
+    fn update(&mut self, event: Event) -> color_eyre::Result<Transition> {
+        let transition = match event {
+            Event::Crossterm(Key(KeyEvent {
+                code,
+                ..
+            })) => match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => Transition::Complete(Confirm(true)),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    Transition::Complete(Confirm(false))
+                }
+                _ => Transition::Continue,
+            },
+            _ => Transition::Continue,
+        };
+        Ok(transition)
+    }
+
+    fn process(&mut self, _return_value: Return) -> color_eyre::Result<Transition> {
+        // Can't process returns
+        Ok(Transition::Continue)
+    }
+
+    fn render_frame(
+        &self,
+        frame: &mut Frame,
+        ctx: &RenderContext,
+    ) -> color_eyre::Result<Rendering> {
+        let [
+            _header_area,
+            question_area,
+        ] = Layout::vertical(
+            [
+                Constraint::Length(1),
+                Constraint::Length(4),
+            ],
+        )
+        .areas(frame.area());
+
+        let question = Paragraph::new(
+            self.question
+                .as_str(),
+        )
+        .style(
+            ctx.theme
+                .screen_styles()
+                .default,
+        )
+        .wrap(
+            Wrap {
+                trim: true,
+            },
+        )
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .border_style(
+                    ctx.theme
+                        .screen_styles()
+                        .key_exit,
+                ),
+        );
+
+        frame.render_widget(
+            Clear,
+            question_area,
+        );
+        frame.render_widget(
+            question,
+            question_area,
+        );
+
+        Ok(Rendering::Complete)
+    }
+
+    fn delegation(&self, _ctx: &RenderContext) -> Option<DeligationInfo> {
+        None
+    }
+
+    fn delegate_terminal(&self, _ctx: &RenderContext) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,535 @@
+use crate::event::Event;
+use crate::screens::Return::{Noop, Selection};
+use crate::screens::input_screen::select::SelectItem;
+use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
+use crate::util::fuzzy::{FuzzyMatch, fuzzy_match};
+use ratatui::Frame;
+use ratatui::crossterm::event::Event::Key;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use std::collections::BTreeSet;
+use tui_input::Input;
+use tui_input::backend::crossterm::EventHandler;
+
+/// Fuzzy finder drawn inside the TUI
+///
+/// Type to filter, Up/Down (or Ctrl-p/Ctrl-n) to move, Tab to mark when multi-select is on,
+/// Enter to pick and Esc to cancel. Completes with [`Return::Selection`] like fzf does.
+pub struct FuzzySelectScreen {
+    items: Vec<SelectItem>,
+    labels: Vec<String>,
+    multi: bool,
+    query: Input,
+    /// Indices into `items` with their match, best first
+    matches: Vec<(
+        usize,
+        FuzzyMatch,
+    )>,
+    cursor: usize,
+    marked: BTreeSet<usize>,
+}
+
+impl FuzzySelectScreen {
+    pub fn new(items: Vec<SelectItem>, multi: bool) -> Box<dyn Screen> {
+        let labels = items
+            .iter()
+            .map(SelectItem::label)
+            .collect();
+        let mut screen = Self {
+            items,
+            labels,
+            multi,
+            query: Input::default(),
+            matches: vec![],
+            cursor: 0,
+            marked: BTreeSet::new(),
+        };
+        screen.filter();
+
+        Box::new(screen)
+    }
+
+    fn filter(&mut self) {
+        let query = self
+            .query
+            .value();
+        self.matches = self
+            .labels
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(i, label)| {
+                    fuzzy_match(
+                        query, label,
+                    )
+                    .map(
+                        |found| {
+                            (
+                                i, found,
+                            )
+                        },
+                    )
+                },
+            )
+            .collect();
+        // Stable sort keeps the spec order between equal scores
+        self.matches
+            .sort_by(
+                |(_, a), (_, b)| {
+                    b.score
+                        .cmp(&a.score)
+                },
+            );
+        self.cursor = 0;
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.matches
+            .get(self.cursor)
+            .map(|(i, _)| *i)
+    }
+
+    fn move_cursor(&mut self, up: bool) {
+        if self
+            .matches
+            .is_empty()
+        {
+            return;
+        }
+        let len = self
+            .matches
+            .len();
+        self.cursor = if up {
+            (self.cursor + len - 1) % len
+        } else {
+            (self.cursor + 1) % len
+        };
+    }
+
+    fn toggle_mark(&mut self) {
+        let Some(i) = self.current() else {
+            return;
+        };
+        if !self
+            .marked
+            .remove(&i)
+        {
+            self.marked
+                .insert(i);
+        }
+    }
+
+    fn accept(&self) -> Return {
+        if self.multi
+            && !self
+                .marked
+                .is_empty()
+        {
+            return Selection(
+                self.marked
+                    .iter()
+                    .copied()
+                    .collect(),
+            );
+        }
+
+        match self.current() {
+            None => Noop,
+            Some(i) => Selection(vec![i]),
+        }
+    }
+}
+
+impl Screen for FuzzySelectScreen {
+    fn update(&mut self, event: Event) -> color_eyre::Result<Transition> {
+        let transition = match event {
+            Event::Crossterm(
+                ref crossterm_event @ Key(KeyEvent {
+                    code,
+                    modifiers,
+                    ..
+                }),
+            ) => {
+                let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+                match code {
+                    KeyCode::Esc => Transition::Complete(Noop),
+                    KeyCode::Enter => Transition::Complete(self.accept()),
+                    KeyCode::Up => {
+                        self.move_cursor(true);
+                        Transition::Continue
+                    }
+                    KeyCode::Down => {
+                        self.move_cursor(false);
+                        Transition::Continue
+                    }
+                    KeyCode::Char('p') if ctrl => {
+                        self.move_cursor(true);
+                        Transition::Continue
+                    }
+                    KeyCode::Char('n') if ctrl => {
+                        self.move_cursor(false);
+                        Transition::Continue
+                    }
+                    KeyCode::Tab if self.multi => {
+                        self.toggle_mark();
+                        self.move_cursor(false);
+                        Transition::Continue
+                    }
+                    KeyCode::BackTab if self.multi => {
+                        self.toggle_mark();
+                        self.move_cursor(true);
+                        Transition::Continue
+                    }
+                    _ => {
+                        if self
+                            .query
+                            .handle_event(crossterm_event)
+                            .is_some_and(|changed| changed.value)
+                        {
+                            self.filter();
+                        }
+                        Transition::Continue
+                    }
+                }
+            }
+            _ => Transition::Continue,
+        };
+        Ok(transition)
+    }
+
+    fn process(&mut self, _return_value: Return) -> color_eyre::Result<Transition> {
+        // Can't process returns
+        Ok(Transition::Continue)
+    }
+
+    fn render_frame(
+        &self,
+        frame: &mut Frame,
+        ctx: &RenderContext,
+    ) -> color_eyre::Result<Rendering> {
+        let [
+            _header_area,
+            area,
+        ] = Layout::vertical(
+            [
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ],
+        )
+        .areas(frame.area());
+        let [
+            list_area,
+            preview_area,
+        ] = Layout::horizontal(
+            [
+                Constraint::Percentage(55),
+                Constraint::Percentage(45),
+            ],
+        )
+        .areas(area);
+        let [
+            query_area,
+            matches_area,
+        ] = Layout::vertical(
+            [
+                Constraint::Length(3),
+                Constraint::Fill(1),
+            ],
+        )
+        .areas(list_area);
+
+        frame.render_widget(
+            Clear, area,
+        );
+        self.render_query(
+            frame, query_area, ctx,
+        );
+        self.render_matches(
+            frame,
+            matches_area,
+            ctx,
+        );
+        self.render_preview(
+            frame,
+            preview_area,
+            ctx,
+        );
+
+        Ok(Rendering::Complete)
+    }
+
+    fn delegation(&self, _ctx: &RenderContext) -> Option<DeligationInfo> {
+        None
+    }
+
+    fn delegate_terminal(&self, _ctx: &RenderContext) -> color_eyre::Result<()> {
+        Ok(())
+    }
+}
+
+impl FuzzySelectScreen {
+    fn block<'a>(ctx: &RenderContext) -> Block<'a> {
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(
+                ctx.theme
+                    .screen_styles()
+                    .boarder,
+            )
+    }
+
+    fn render_query(&self, frame: &mut Frame, area: Rect, ctx: &RenderContext) {
+        // keep 2 for borders, 3 for the prompt and 1 for cursor
+        let width = area
+            .width
+            .max(6)
+            - 6;
+        let scroll = self
+            .query
+            .visual_scroll(width as usize);
+        let query = Paragraph::new(
+            Line::from(
+                vec![
+                    Span::styled(
+                        ">> ",
+                        ctx.theme
+                            .screen_styles()
+                            .key_modifier,
+                    ),
+                    Span::raw(
+                        self.query
+                            .value()
+                            .chars()
+                            .skip(scroll)
+                            .collect::<String>(),
+                    ),
+                ],
+            ),
+        )
+        .block(
+            Self::block(ctx).title_top(
+                Line::from(
+                    format!(
+                        " {}/{} ",
+                        self.matches
+                            .len(),
+                        self.items
+                            .len()
+                    ),
+                )
+                .right_aligned(),
+            ),
+        );
+        frame.render_widget(
+            query, area,
+        );
+
+        let x = self
+            .query
+            .visual_cursor()
+            .max(scroll)
+            - scroll
+            + 4;
+        frame.set_cursor_position(
+            (
+                area.x + x as u16,
+                area.y + 1,
+            ),
+        );
+    }
+
+    fn render_matches(&self, frame: &mut Frame, area: Rect, ctx: &RenderContext) {
+        let matched_style = Style::from(
+            ctx.theme
+                .screen_styles()
+                .key_modifier,
+        )
+        .add_modifier(Modifier::BOLD);
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(
+                |(i, found)| {
+                    let marker = if self
+                        .marked
+                        .contains(i)
+                    {
+                        Span::styled(
+                            "> ",
+                            ctx.theme
+                                .screen_styles()
+                                .key_modifier,
+                        )
+                    } else {
+                        Span::raw("  ")
+                    };
+                    let spans = self.labels[*i]
+                        .chars()
+                        .enumerate()
+                        .map(
+                            |(pos, c)| {
+                                if found
+                                    .positions
+                                    .binary_search(&pos)
+                                    .is_ok()
+                                {
+                                    Span::styled(
+                                        c.to_string(),
+                                        matched_style,
+                                    )
+                                } else {
+                                    Span::raw(c.to_string())
+                                }
+                            },
+                        );
+
+                    ListItem::new(Line::from_iter(std::iter::once(marker).chain(spans)))
+                },
+            )
+            .collect();
+
+        let list = List::new(items)
+            .block(Self::block(ctx))
+            .highlight_symbol(">> ")
+            .highlight_style(
+                ctx.theme
+                    .screen_styles()
+                    .highlight,
+            );
+
+        frame.render_stateful_widget(
+            list,
+            area,
+            &mut ListState::default().with_selected(
+                self.current()
+                    .map(|_| self.cursor),
+            ),
+        );
+    }
+
+    fn render_preview(&self, frame: &mut Frame, area: Rect, ctx: &RenderContext) {
+        let preview = self
+            .current()
+            .map(
+                |i| {
+                    self.items[i].preview(
+                        ctx.theme
+                            .usage_styles(),
+                    )
+                },
+            )
+            .unwrap_or_default();
+
+        frame.render_widget(
+            Paragraph::new(preview)
+                .block(
+                    Self::block(ctx).title_top(
+                        Line::from(
+                            format!(
+                                "[{}]",
+                                t!("headings.doc")
+                            ),
+                        )
+                        .style(
+                            ctx.theme
+                                .screen_styles()
+                                .default,
+                        )
+                        .centered(),
+                    ),
+                )
+                .wrap(
+                    Wrap {
+                        trim: false,
+                    },
+                ),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::{Event as CrosstermEvent, KeyEventKind, KeyEventState};
+
+    fn key(code: KeyCode) -> Event {
+        Event::Crossterm(
+            CrosstermEvent::Key(
+                KeyEvent {
+                    code,
+                    modifiers: KeyModifiers::NONE,
+                    kind: KeyEventKind::Press,
+                    state: KeyEventState::NONE,
+                },
+            ),
+        )
+    }
+
+    fn screen(multi: bool) -> Box<dyn Screen> {
+        FuzzySelectScreen::new(
+            SelectItem::strings(
+                &[
+                    "get", "apply", "delete", "describe",
+                ],
+            ),
+            multi,
+        )
+    }
+
+    fn completion(transition: Transition) -> Option<Vec<usize>> {
+        match transition {
+            Transition::Complete(Selection(selection)) => Some(selection),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_typing_filters_and_enter_picks() -> color_eyre::Result<()> {
+        let mut screen = screen(false);
+        for c in "dsc".chars() {
+            screen.update(key(KeyCode::Char(c)))?;
+        }
+
+        assert_eq!(
+            completion(screen.update(key(KeyCode::Enter))?),
+            Some(vec![3])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_select_returns_marked_items() -> color_eyre::Result<()> {
+        let mut screen = screen(true);
+        screen.update(key(KeyCode::Tab))?;
+        screen.update(key(KeyCode::Down))?;
+        screen.update(key(KeyCode::Tab))?;
+
+        assert_eq!(
+            completion(screen.update(key(KeyCode::Enter))?),
+            Some(
+                vec![
+                    0, 2
+                ]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_escape_cancels() -> color_eyre::Result<()> {
+        let mut screen = screen(false);
+
+        assert!(
+            matches!(
+                screen.update(key(KeyCode::Esc))?,
+                Transition::Complete(Noop)
+            )
+        );
+        Ok(())
+    }
+}
@@ -1,42 +1,70 @@
 use crate::event::Event;
-use crate::opencli::usage::StringWriter;
-use crate::opencli::v0_1::OptionElement;
-use crate::screens::input_screen::fzf_select::Item::{CmdOption, Plain};
 use crate::screens::Rendering::Complete;
 use crate::screens::Return::Noop;
+use crate::screens::input_screen::select::SelectItem;
 use crate::screens::{DeligationInfo, RenderContext, Rendering, Return, Screen, Transition};
-use fzf_wrapped::{run_with_output, Fzf, Layout};
-use ratatui::layout::Position;
+use crate::shell::{PosixQuoter, Quoter};
+use color_eyre::eyre::WrapErr;
+use fzf_wrapped::{Fzf, Layout};
 use ratatui::Frame;
+use ratatui::layout::Position;
+use tempfile::TempDir;
 
+/// Selector backed by the external `fzf` binary, the terminal is handed over while it runs
 pub struct FzfSelectScreen {
-    options: Vec<Item>,
+    options: Vec<SelectItem>,
+    multi: bool,
     ran: bool,
 }
 
-#[allow(dead_code)]
-enum Item {
-    Plain(String),
-    CmdOption(OptionElement)
+/// Whether `fzf` is on the `$PATH`
+pub fn fzf_installed() -> bool {
+    std::env::var_os("PATH").is_some_and(
+        |path| {
+            std::env::split_paths(&path).any(
+                |dir| {
+                    dir.join("fzf")
+                        .is_file()
+                },
+            )
+        },
+    )
 }
 
 impl FzfSelectScreen {
-    #[allow(dead_code)]
-    pub fn new_with_strings<T: AsRef<str>>(options: &Vec<T>) -> Box<dyn Screen> {
+    pub fn new(options: Vec<SelectItem>, multi: bool) -> Box<dyn Screen> {
         let screen = Self {
-            options: options.iter().map(|x| Plain(x.as_ref().to_owned())).collect(),
+            options,
+            multi,
             ran: false,
         };
 
         Box::new(screen)
     }
-    pub fn new_with_options(options: &Vec<OptionElement>) -> Box<dyn Screen> {
-        let screen = Self {
-            options: options.iter().map(|x| CmdOption(x.to_owned())).collect(),
-            ran: false,
-        };
 
-        Box::new(screen)
+    /// Write every item's full usage to its own file so fzf can `cat` it as a preview
+    ///
+    /// The directory is private to the user and removed when dropped, whatever fzf does.
+    fn write_previews(&self, ctx: &RenderContext) -> color_eyre::Result<TempDir> {
+        let dir = tempfile::Builder::new()
+            .prefix("cmdi-preview-")
+            .tempdir()?;
+        for (i, option) in self
+            .options
+            .iter()
+            .enumerate()
+        {
+            std::fs::write(
+                dir.path()
+                    .join(i.to_string()),
+                option.usage(
+                    ctx.theme
+                        .usage_styles(),
+                ),
+            )?;
+        }
+
+        Ok(dir)
     }
 }
 
@@ -45,28 +73,24 @@ impl Screen for FzfSelectScreen {
         self.ran = true;
 
         let rtn = match event {
-            Event::Selection(selections) => {
-                match selections.is_empty() {
-                    true => Transition::Complete(Noop),
-                    false => Transition::Complete(
-                        Return::Selection(selections)
-                    )
-                }
-            }
+            Event::Selection(selections) => match selections.is_empty() {
+                true => Transition::Complete(Noop),
+                false => Transition::Complete(Return::Selection(selections)),
+            },
             _ => Transition::Continue,
         };
 
         Ok(rtn)
     }
 
-    fn process(&mut self, _result: Return) -> color_eyre::Result<()> {
-        Ok(())
+    fn process(&mut self, _result: Return) -> color_eyre::Result<Transition> {
+        Ok(Transition::Continue)
     }
 
     fn render_frame(
         &self,
         _frame: &mut Frame,
-        _ctx: &RenderContext
+        _ctx: &RenderContext,
     ) -> color_eyre::Result<Rendering> {
         Ok(Complete)
     }
@@ -86,11 +110,19 @@ impl Screen for FzfSelectScreen {
         }
     }
 
-    fn delegate_terminal(
-        &self,
-        ctx: &RenderContext
-    ) -> color_eyre::Result<()> {
-        let fzf = Fzf::builder()
+    fn delegate_terminal(&self, ctx: &RenderContext) -> color_eyre::Result<()> {
+        let previews = self.write_previews(ctx)?;
+        // Quoted for sh, which runs the preview whatever the user's $SHELL is
+        let preview = format!(
+            "--preview=cat {}/{{1}}",
+            PosixQuoter.quote(
+                &previews
+                    .path()
+                    .to_string_lossy()
+            )
+        );
+
+        let mut fzf = Fzf::builder()
             .custom_args(
                 vec![
                     "--height=99%",
@@ -106,7 +138,16 @@ impl Screen for FzfSelectScreen {
                     "--accept-nth=1",
                     "--with-nth=2",
                     "--ansi",
-                ],
+                    "--preview-window=right,45%,wrap",
+                    "--with-shell=sh -c",
+                    &preview,
+                ]
+                .into_iter()
+                .chain(
+                    self.multi
+                        .then_some("--multi"),
+                )
+                .collect::<Vec<_>>(),
             )
             .layout(Layout::Reverse)
             .build()?;
@@ -120,15 +161,10 @@ impl Screen for FzfSelectScreen {
                     format!(
                         "{}\t{}",
                         i,
-                        match o {
-                            Plain(str) => str.to_owned(),
-                            CmdOption(option) => {
-                                StringWriter::spec_to_usage_line(
-                                    option,
-                                    ctx.theme.usage_styles()
-                                )
-                            }
-                        }
+                        o.usage_line(
+                            ctx.theme
+                                .usage_styles()
+                        )
                         .replace(
                             "\n", " -- "
                         )
@@ -137,17 +173,26 @@ impl Screen for FzfSelectScreen {
             )
             .collect();
 
-        let selections: Vec<usize> = run_with_output(
-            fzf, options,
-        )
-        .unwrap_or_default()
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .filter_map(|line| line.parse::<usize>().ok())
-        .collect();
-
-        ctx.event.send(Event::Selection(selections));
+        fzf.run()
+            .wrap_err("Failed to start fzf")?;
+        // fzf stops reading when quit early, the selection is all that matters then
+        let _ = fzf.add_items(options);
+        let selections: Vec<usize> = fzf
+            .output()
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .filter_map(
+                |line| {
+                    line.parse::<usize>()
+                        .ok()
+                },
+            )
+            .collect();
+
+        ctx.event
+            .send(Event::Selection(selections));
 
         Ok(())
     }
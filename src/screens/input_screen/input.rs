@@ -52,9 +52,9 @@ impl Screen for InputScreen {
         Ok(transition)
     }
 
-    fn process(&mut self, _result: Return) -> color_eyre::Result<()> {
+    fn process(&mut self, _result: Return) -> color_eyre::Result<Transition> {
         // Can't process returns
-        Ok(())
+        Ok(Transition::Continue)
     }
 
     fn render_frame(
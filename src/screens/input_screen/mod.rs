@@ -1,3 +1,6 @@
+pub mod confirm;
+pub mod fuzzy_select;
 pub mod fzf_select;
 pub mod input;
-pub mod noop;
\ No newline at end of file
+pub mod noop;
+pub mod select;
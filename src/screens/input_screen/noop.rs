@@ -14,8 +14,8 @@ impl Screen for NoopScreen {
         Ok(Transition::Complete(Return::Noop))
     }
 
-    fn process(&mut self, _return_value: Return) -> color_eyre::Result<()> {
-        Ok(())
+    fn process(&mut self, _return_value: Return) -> color_eyre::Result<Transition> {
+        Ok(Transition::Continue)
     }
 
     fn render_frame(&self, _frame: &mut Frame, _ctx: &RenderContext) -> color_eyre::Result<Rendering> {
@@ -0,0 +1,174 @@
+use crate::builder::ScopedOption;
+use crate::config::Selector;
+use crate::opencli::usage::{StringWriter, WidgetWriter};
+use crate::opencli::v0_1::CommandElement;
+use crate::screens::Screen;
+use crate::screens::input_screen::fuzzy_select::FuzzySelectScreen;
+use crate::screens::input_screen::fzf_select::{FzfSelectScreen, fzf_installed};
+use crate::theme::UsageStyle;
+use log::warn;
+use ratatui::text::Text;
+
+/// Something that can be picked from a selector
+pub enum SelectItem {
+    Plain(String),
+    Option(ScopedOption),
+    Command(CommandElement),
+}
+
+impl SelectItem {
+    pub fn options(options: &[ScopedOption]) -> Vec<SelectItem> {
+        options
+            .iter()
+            .cloned()
+            .map(SelectItem::Option)
+            .collect()
+    }
+
+    pub fn commands(commands: &[CommandElement]) -> Vec<SelectItem> {
+        commands
+            .iter()
+            .cloned()
+            .map(SelectItem::Command)
+            .collect()
+    }
+
+    pub fn strings<T: AsRef<str>>(strings: &[T]) -> Vec<SelectItem> {
+        strings
+            .iter()
+            .map(
+                |string| {
+                    SelectItem::Plain(
+                        string
+                            .as_ref()
+                            .to_owned(),
+                    )
+                },
+            )
+            .collect()
+    }
+
+    /// Single line plain text, what the native selector matches against and shows
+    pub fn label(&self) -> String {
+        let label = self.name_and_description();
+        match self {
+            SelectItem::Option(option) if option.inherited => format!(
+                "{}  [{}]",
+                label,
+                t!(
+                    "usage.inherited_from",
+                    origin = option.origin
+                )
+            ),
+            _ => label,
+        }
+    }
+
+    fn name_and_description(&self) -> String {
+        let (name, description) = match self {
+            SelectItem::Plain(string) => return string.to_owned(),
+            SelectItem::Option(option) => (
+                option
+                    .spec
+                    .aliases
+                    .iter()
+                    .flatten()
+                    .chain(
+                        std::iter::once(
+                            &option
+                                .spec
+                                .name,
+                        ),
+                    )
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                &option
+                    .spec
+                    .description,
+            ),
+            SelectItem::Command(command) => (
+                command
+                    .name
+                    .to_owned(),
+                &command.description,
+            ),
+        };
+
+        match description
+            .as_deref()
+            .and_then(
+                |description| {
+                    description
+                        .lines()
+                        .next()
+                },
+            ) {
+            Some(description) => format!(
+                "{}  {}",
+                name, description
+            ),
+            None => name,
+        }
+    }
+
+    /// Styled usage line, for fzf
+    pub fn usage_line(&self, style: &UsageStyle) -> String {
+        match self {
+            SelectItem::Plain(string) => string.to_owned(),
+            SelectItem::Option(option) => StringWriter::spec_to_usage_line(
+                option, style,
+            ),
+            SelectItem::Command(command) => StringWriter::spec_to_usage_line(
+                command, style,
+            ),
+        }
+    }
+
+    /// Full styled usage, for the fzf preview
+    pub fn usage(&self, style: &UsageStyle) -> String {
+        match self {
+            SelectItem::Plain(string) => string.to_owned(),
+            SelectItem::Option(option) => StringWriter::spec_to_usage(
+                option, style,
+            ),
+            SelectItem::Command(command) => StringWriter::spec_to_usage(
+                command, style,
+            ),
+        }
+    }
+
+    /// Full usage, for the preview pane
+    pub fn preview<'a>(&self, style: &UsageStyle) -> Text<'a> {
+        match self {
+            SelectItem::Plain(string) => Text::from(string.to_owned()),
+            SelectItem::Option(option) => WidgetWriter::spec_to_text(
+                option, style,
+            ),
+            SelectItem::Command(command) => WidgetWriter::spec_to_text(
+                command, style,
+            ),
+        }
+    }
+}
+
+/// The selector screen for `items` using the configured backend
+///
+/// Completes with [`crate::screens::Return::Selection`] holding item indices, or
+/// [`crate::screens::Return::Noop`] when cancelled.
+pub fn select_screen(selector: Selector, items: Vec<SelectItem>, multi: bool) -> Box<dyn Screen> {
+    match selector {
+        Selector::Native => FuzzySelectScreen::new(
+            items, multi,
+        ),
+        Selector::Fzf if fzf_installed() => FzfSelectScreen::new(
+            items, multi,
+        ),
+        Selector::Fzf => {
+            warn!("fzf is not installed, using the native selector");
+            FuzzySelectScreen::new(
+                items, multi,
+            )
+        }
+    }
+}
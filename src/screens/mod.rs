@@ -24,6 +24,9 @@ pub enum Return {
     Noop,
     Selection(Vec<usize>),
     InputString(String),
+    Command(String),
+    Confirm(bool),
+    CommandExited(Option<i32>),
 }
 
 #[allow(dead_code)]
@@ -78,7 +81,8 @@ pub trait Screen: Any {
             .unwrap()
     }
     fn update(&mut self, event: Event) -> color_eyre::Result<Transition>;
-    fn process(&mut self, return_value: Return) -> color_eyre::Result<()>;
+    /// Take the result of the child screen that completed, and move on from it right away
+    fn process(&mut self, return_value: Return) -> color_eyre::Result<Transition>;
     fn render_frame(&self, frame: &mut Frame, ctx: &RenderContext) -> color_eyre::Result<Rendering>;
 
     fn delegation(&self, ctx: &RenderContext) -> Option<DeligationInfo>;
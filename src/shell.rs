@@ -0,0 +1,468 @@
+use clap::ValueEnum;
+use color_eyre::eyre::bail;
+use serde::Deserialize;
+use std::env;
+use std::fmt::{Display, Formatter};
+
+/// Shells cmdi can produce command lines for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+// `PowerShell` is the shell's name, not a `Shell` suffix
+#[allow(clippy::enum_variant_names)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell", alias = "pwsh")]
+    #[serde(alias = "pwsh")]
+    PowerShell,
+}
+
+impl Shell {
+    /// Guess the user's shell from `$SHELL`, falling back to bash
+    pub fn detect() -> Self {
+        env::var("SHELL")
+            .ok()
+            .and_then(
+                |path| {
+                    path.rsplit('/')
+                        .next()
+                        .and_then(
+                            |name| {
+                                Shell::from_str(
+                                    name, true,
+                                )
+                                .ok()
+                            },
+                        )
+                },
+            )
+            .unwrap_or(Shell::Bash)
+    }
+
+    pub fn quoter(&self) -> &'static dyn Quoter {
+        match self {
+            Shell::Bash | Shell::Zsh => &PosixQuoter,
+            Shell::Fish => &FishQuoter,
+            Shell::PowerShell => &PowerShellQuoter,
+        }
+    }
+}
+
+impl Shell {
+    /// Script binding a key that edits the current line with cmdi, see `cmdi init`
+    pub fn init_script(&self) -> color_eyre::Result<&'static str> {
+        match self {
+            Shell::Bash => Ok(include_str!("shell/init.bash")),
+            Shell::Zsh => Ok(include_str!("shell/init.zsh")),
+            Shell::Fish => Ok(include_str!("shell/init.fish")),
+            Shell::PowerShell => bail!(
+                "No shell integration for {} yet",
+                self
+            ),
+        }
+    }
+}
+
+/// Split a line into words the way a POSIX shell would, without any expansion
+///
+/// Handles single and double quotes and backslash escapes, which also covers what bash, zsh and
+/// fish buffers hold in practice.
+pub fn split_words(line: &str) -> color_eyre::Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\\' => match chars.next() {
+                // A line continuation
+                Some('\n') => {}
+                escaped => word
+                    .get_or_insert_with(String::new)
+                    .push(escaped.unwrap_or('\\')),
+            },
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => bail!(
+                            "Unterminated single quote in `{}`",
+                            line
+                        ),
+                    }
+                }
+            }
+            '"' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$' | '`')) => current.push(escaped),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => bail!(
+                                "Unterminated double quote in `{}`",
+                                line
+                            ),
+                        },
+                        Some(c) => current.push(c),
+                        None => bail!(
+                            "Unterminated double quote in `{}`",
+                            line
+                        ),
+                    }
+                }
+            }
+            c => word
+                .get_or_insert_with(String::new)
+                .push(c),
+        }
+    }
+    words.extend(word);
+
+    Ok(words)
+}
+
+impl Display for Shell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = self
+            .to_possible_value()
+            .map(
+                |value| {
+                    value
+                        .get_name()
+                        .to_owned()
+                },
+            )
+            .unwrap_or_default();
+        write!(
+            f,
+            "{}",
+            name
+        )
+    }
+}
+
+/// A quoting dialect, turns a raw argument into a word the shell reads back unchanged
+pub trait Quoter {
+    fn quote(&self, word: &str) -> String;
+
+    fn join(&self, words: &[String]) -> String {
+        words
+            .iter()
+            .map(|word| self.quote(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// What ends a line that the command continues on, for multi-line layouts
+    fn continuation(&self) -> &'static str {
+        "\\"
+    }
+}
+
+fn is_plain(word: &str, extra: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || extra.contains(c))
+}
+
+/// bash and zsh
+///
+/// Single quotes for ordinary text, `$'...'` when the word holds control characters.
+pub struct PosixQuoter;
+
+impl Quoter for PosixQuoter {
+    fn quote(&self, word: &str) -> String {
+        // zsh expands a leading `=` to a command path
+        if is_plain(
+            word,
+            "@%+=:,./-_",
+        ) && !word.starts_with('=')
+        {
+            word.to_owned()
+        } else if word
+            .chars()
+            .any(|c| c.is_control())
+        {
+            let mut quoted = String::from("$'");
+            for c in word.chars() {
+                match c {
+                    '\\' => quoted.push_str(r"\\"),
+                    '\'' => quoted.push_str(r"\'"),
+                    '\n' => quoted.push_str(r"\n"),
+                    '\t' => quoted.push_str(r"\t"),
+                    '\r' => quoted.push_str(r"\r"),
+                    c if c.is_control() => quoted.push_str(
+                        &format!(
+                            "\\x{:02x}",
+                            c as u32
+                        ),
+                    ),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('\'');
+            quoted
+        } else {
+            format!(
+                "'{}'",
+                word.replace(
+                    '\'', r"'\''",
+                )
+            )
+        }
+    }
+}
+
+/// fish
+///
+/// fish has no `$'...'`, but backslash escapes `\` and `'` inside single quotes.
+pub struct FishQuoter;
+
+impl Quoter for FishQuoter {
+    fn quote(&self, word: &str) -> String {
+        if is_plain(
+            word,
+            "@+=:,./-_",
+        ) {
+            word.to_owned()
+        } else if word
+            .chars()
+            .any(|c| c.is_control())
+        {
+            // Control characters only have an escaped form outside of quotes
+            let mut quoted = String::new();
+            for c in word.chars() {
+                match c {
+                    '\n' => quoted.push_str(r"\n"),
+                    '\t' => quoted.push_str(r"\t"),
+                    '\r' => quoted.push_str(r"\r"),
+                    c if c.is_control() => quoted.push_str(
+                        &format!(
+                            "\\x{:02x}",
+                            c as u32
+                        ),
+                    ),
+                    c if c.is_ascii_alphanumeric() => quoted.push(c),
+                    c => {
+                        quoted.push('\\');
+                        quoted.push(c);
+                    }
+                }
+            }
+            quoted
+        } else {
+            format!(
+                "'{}'",
+                word.replace(
+                    '\\', r"\\",
+                )
+                .replace(
+                    '\'', r"\'",
+                )
+            )
+        }
+    }
+}
+
+/// PowerShell
+///
+/// Single quotes with `'` doubled, double quotes with backtick escapes for control characters.
+pub struct PowerShellQuoter;
+
+impl PowerShellQuoter {
+    /// PowerShell also treats typographic single quotes as quote characters
+    const SINGLE_QUOTES: [char; 5] = [
+        '\'', '\u{2018}', '\u{2019}', '\u{201A}', '\u{201B}',
+    ];
+    const DOUBLE_QUOTES: [char; 4] = [
+        '"', '\u{201C}', '\u{201D}', '\u{201E}',
+    ];
+}
+
+impl Quoter for PowerShellQuoter {
+    fn continuation(&self) -> &'static str {
+        "`"
+    }
+
+    fn quote(&self, word: &str) -> String {
+        if is_plain(
+            word, "+=:./-_",
+        ) {
+            word.to_owned()
+        } else if word
+            .chars()
+            .any(|c| c.is_control())
+        {
+            let mut quoted = String::from("\"");
+            for c in word.chars() {
+                match c {
+                    '\n' => quoted.push_str("`n"),
+                    '\t' => quoted.push_str("`t"),
+                    '\r' => quoted.push_str("`r"),
+                    '\0' => quoted.push_str("`0"),
+                    c if c.is_control() => quoted.push_str(
+                        &format!(
+                            "`u{{{:x}}}",
+                            c as u32
+                        ),
+                    ),
+                    '`' | '$' => {
+                        quoted.push('`');
+                        quoted.push(c);
+                    }
+                    c if Self::DOUBLE_QUOTES.contains(&c) => {
+                        quoted.push('`');
+                        quoted.push(c);
+                    }
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            quoted
+        } else {
+            let mut quoted = String::from("'");
+            for c in word.chars() {
+                if Self::SINGLE_QUOTES.contains(&c) {
+                    quoted.push(c);
+                }
+                quoted.push(c);
+            }
+            quoted.push('\'');
+            quoted
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posix_quoting() {
+        let q = Shell::Bash.quoter();
+        assert_eq!(
+            q.quote("--namespace"),
+            "--namespace"
+        );
+        assert_eq!(
+            q.quote(""),
+            "''"
+        );
+        assert_eq!(
+            q.quote("it's"),
+            r"'it'\''s'"
+        );
+        assert_eq!(
+            q.quote("$HOME"),
+            "'$HOME'"
+        );
+        assert_eq!(
+            q.quote("a\nb"),
+            r"$'a\nb'"
+        );
+        assert_eq!(
+            Shell::Zsh
+                .quoter()
+                .quote("=ls"),
+            "'=ls'"
+        );
+    }
+
+    #[test]
+    fn test_fish_quoting() {
+        let q = Shell::Fish.quoter();
+        assert_eq!(
+            q.quote("app=web"),
+            "app=web"
+        );
+        assert_eq!(
+            q.quote("it's"),
+            r"'it\'s'"
+        );
+        assert_eq!(
+            q.quote(r"C:\tmp"),
+            r"'C:\\tmp'"
+        );
+        assert_eq!(
+            q.quote("a b\n"),
+            r"a\ b\n"
+        );
+    }
+
+    #[test]
+    fn test_powershell_quoting() {
+        let q = Shell::PowerShell.quoter();
+        assert_eq!(
+            q.quote("-o"),
+            "-o"
+        );
+        assert_eq!(
+            q.quote("it's"),
+            "'it''s'"
+        );
+        assert_eq!(
+            q.quote("$env:HOME"),
+            "'$env:HOME'"
+        );
+        assert_eq!(
+            q.quote("@list"),
+            "'@list'"
+        );
+        assert_eq!(
+            q.quote("a\n$b"),
+            "\"a`n`$b\""
+        );
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"kubectl get  pods -l 'app=web tier' --output="json path" a\ b ''"#)
+                .unwrap(),
+            vec![
+                "kubectl",
+                "get",
+                "pods",
+                "-l",
+                "app=web tier",
+                "--output=json path",
+                "a b",
+                ""
+            ]
+        );
+        assert_eq!(
+            split_words(r#"echo "a \"quoted\" \x""#).unwrap(),
+            vec![
+                "echo",
+                r#"a "quoted" \x"#
+            ]
+        );
+        assert!(split_words("echo 'open").is_err());
+    }
+
+    #[test]
+    fn test_shell_names() {
+        assert_eq!(
+            Shell::PowerShell.to_string(),
+            "powershell"
+        );
+        assert_eq!(
+            Shell::from_str(
+                "pwsh", true
+            )
+            .unwrap(),
+            Shell::PowerShell
+        );
+    }
+}
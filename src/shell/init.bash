@@ -0,0 +1,15 @@
+# cmdi integration for bash, load it with: eval "$(cmdi init bash)"
+# Ctrl-X c hands the current line to cmdi and puts the composed command back for review.
+
+__cmdi_widget() {
+    local output
+    output="$(mktemp "${TMPDIR:-/tmp}/cmdi.XXXXXX")" || return
+    cmdi --shell bash --output "$output" --line="$READLINE_LINE" </dev/tty >/dev/tty
+    if [[ -s "$output" ]]; then
+        READLINE_LINE="$(<"$output")"
+        READLINE_POINT=${#READLINE_LINE}
+    fi
+    rm -f "$output"
+}
+
+bind -x '"\C-xc": __cmdi_widget'
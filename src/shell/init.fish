@@ -0,0 +1,15 @@
+# cmdi integration for fish, load it with: cmdi init fish | source
+# Ctrl-X c hands the current buffer to cmdi and puts the composed command back for review.
+
+function __cmdi_widget
+    set -l output (mktemp)
+    or return
+    cmdi --shell fish --output $output --line=(commandline | string collect --allow-empty) </dev/tty >/dev/tty
+    if test -s $output
+        commandline --replace -- (string collect < $output)
+    end
+    rm -f $output
+    commandline --function repaint
+end
+
+bind \cxc __cmdi_widget
@@ -8,6 +8,9 @@ pub struct ScreenStyle {
 
     pub boarder: Style,
 
+    pub error: Style,
+    pub warning: Style,
+
     pub key_exit: Style,
     pub key_informative: Style,
     pub key_modifier: Style,
@@ -47,6 +50,9 @@ impl DefaultTheme {
 
                 boarder: Style::new().with(Color::DarkGrey),
 
+                error: Style::new().with(Color::Red),
+                warning: Style::new().with(Color::Yellow),
+
                 key_exit: Style::new().with(Color::Red),
                 key_informative: Style::new().with(Color::Yellow),
                 key_modifier: Style::new().with(Color::Cyan),
@@ -1,3 +1,6 @@
+pub mod fuzzy;
+pub mod process;
+
 pub mod util {
     use color_eyre::eyre::eyre;
 
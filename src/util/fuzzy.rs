@@ -0,0 +1,236 @@
+//! Fuzzy matching for the native selector, modelled on fzf's default scoring
+//!
+//! The pattern is split on whitespace and every term has to match as a subsequence. Matches on
+//! word starts and runs of consecutive characters score higher, gaps cost a little.
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 6;
+const BONUS_FIRST_CHAR: i64 = 4;
+const PENALTY_GAP: i64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices of `text` that matched, sorted
+    pub positions: Vec<usize>,
+}
+
+/// Match `pattern` against `text`, `None` when some term of the pattern does not match
+///
+/// Case is ignored unless the term contains an uppercase letter. An empty pattern matches
+/// everything with a score of zero.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let chars: Vec<char> = text
+        .chars()
+        .collect();
+    let mut result = FuzzyMatch {
+        score: 0,
+        positions: vec![],
+    };
+
+    for term in pattern.split_whitespace() {
+        let term_match = match_term(
+            term, &chars,
+        )?;
+        result.score += term_match.score;
+        result
+            .positions
+            .extend(term_match.positions);
+    }
+
+    result
+        .positions
+        .sort_unstable();
+    result
+        .positions
+        .dedup();
+    Some(result)
+}
+
+fn match_term(term: &str, text: &[char]) -> Option<FuzzyMatch> {
+    let case_sensitive = term
+        .chars()
+        .any(char::is_uppercase);
+    let eq = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a.to_lowercase()
+                .eq(b.to_lowercase())
+        }
+    };
+    let term: Vec<char> = term
+        .chars()
+        .collect();
+
+    // Forward pass finds where the first complete match ends
+    let mut t = 0;
+    let mut end = None;
+    for (i, c) in text
+        .iter()
+        .enumerate()
+    {
+        if eq(
+            *c, term[t],
+        ) {
+            t += 1;
+            if t == term.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    // Backward pass from that end gives the tightest window
+    let mut positions = Vec::with_capacity(term.len());
+    let mut t = term.len();
+    for i in (0..=end).rev() {
+        if eq(
+            text[i],
+            term[t - 1],
+        ) {
+            positions.push(i);
+            t -= 1;
+            if t == 0 {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    Some(
+        FuzzyMatch {
+            score: score(
+                text, &positions,
+            ),
+            positions,
+        },
+    )
+}
+
+fn score(text: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+
+    for &pos in positions {
+        score += SCORE_MATCH;
+        if is_boundary(
+            text, pos,
+        ) {
+            score += BONUS_BOUNDARY;
+        }
+        if pos == 0 {
+            score += BONUS_FIRST_CHAR;
+        }
+        match previous {
+            Some(previous) if previous + 1 == pos => score += BONUS_CONSECUTIVE,
+            Some(previous) => score -= PENALTY_GAP * (pos - previous - 1) as i64,
+            None => score -= PENALTY_GAP * pos.min(8) as i64,
+        }
+        previous = Some(pos);
+    }
+
+    score
+}
+
+/// Start of a word: after a separator or at a lower to upper case change
+fn is_boundary(text: &[char], pos: usize) -> bool {
+    match pos
+        .checked_sub(1)
+        .map(|previous| text[previous])
+    {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && text[pos].is_uppercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_matches() {
+        let found = fuzzy_match(
+            "nsp",
+            "--namespace",
+        )
+        .unwrap();
+
+        assert_eq!(
+            found.positions,
+            vec![
+                2, 6, 7
+            ]
+        );
+        assert!(
+            fuzzy_match(
+                "xyz",
+                "--namespace"
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_smart_case() {
+        assert!(
+            fuzzy_match(
+                "out", "--Output"
+            )
+            .is_some()
+        );
+        assert!(
+            fuzzy_match(
+                "Out", "--output"
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_word_starts_and_runs_score_higher() {
+        let prefix = fuzzy_match(
+            "get", "get pods",
+        )
+        .unwrap();
+        let scattered = fuzzy_match(
+            "get", "target",
+        )
+        .unwrap();
+
+        assert!(prefix.score > scattered.score);
+    }
+
+    #[test]
+    fn test_every_term_must_match() {
+        assert!(
+            fuzzy_match(
+                "out yaml",
+                "--output  Output format: json|yaml"
+            )
+            .is_some()
+        );
+        assert!(
+            fuzzy_match(
+                "out csv",
+                "--output  Output format: json|yaml"
+            )
+            .is_none()
+        );
+        assert_eq!(
+            fuzzy_match(
+                "", "anything"
+            ),
+            Some(
+                FuzzyMatch {
+                    score: 0,
+                    positions: vec![],
+                }
+            )
+        );
+    }
+}
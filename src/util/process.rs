@@ -0,0 +1,88 @@
+//! Running helper programs that might never finish
+
+use color_eyre::eyre::{bail, eyre};
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+/// Drain a pipe on the side so a chatty program can't block on a full buffer
+///
+/// The output arrives once the pipe closes, which a program left running in the background by
+/// the command may never let happen.
+fn drain(mut pipe: impl Read + Send + 'static) -> Receiver<std::io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(
+        move || {
+            let mut output = vec![];
+            let _ = sender.send(
+                pipe.read_to_end(&mut output)
+                    .map(|_| output),
+            );
+        },
+    );
+    receiver
+}
+
+/// Run `command` with stdin closed and collect its output, killing it after `timeout`
+pub fn output_with_timeout(mut command: Command, timeout: Duration) -> color_eyre::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = drain(
+        child
+            .stdout
+            .take()
+            .ok_or_else(|| eyre!("Child stdout unavailable"))?,
+    );
+    let stderr = drain(
+        child
+            .stderr
+            .take()
+            .ok_or_else(|| eyre!("Child stderr unavailable"))?,
+    );
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!(
+                "{:?} timed out after {}ms",
+                command,
+                timeout.as_millis()
+            );
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    // The rest of the timeout is all the pipes get to close
+    let deadline = started + timeout;
+    let join = |reader: Receiver<std::io::Result<Vec<u8>>>| {
+        reader
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .map_err(
+                |_| {
+                    eyre!(
+                        "{:?} left its output open after exiting",
+                        command
+                    )
+                },
+            )?
+            .map_err(color_eyre::Report::from)
+    };
+
+    Ok(
+        Output {
+            status,
+            stdout: join(stdout)?,
+            stderr: join(stderr)?,
+        },
+    )
+}